@@ -1 +0,0 @@
-cargo fmt --check -- --color=never
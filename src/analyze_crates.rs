@@ -1,12 +1,12 @@
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{collections::HashMap, vec};
 
 use clap::Parser;
 
 use rust_digger::{
-    analyzed_crates_root, crates_root, create_data_folders, get_data_folder, CargoTomlErrors,
-    CrateDetails, CrateErrors, ElapsedTimer,
+    analyzed_crates_root, crates_root, create_data_folders, get_data_folder, load_crate_details,
+    Binary, CargoTomlErrors, CrateDetails, CrateErrors, ElapsedTimer,
 };
 
 mod cargo_toml_parser;
@@ -53,7 +53,7 @@ fn collect_data_from_crates(limit: usize) -> Result<(), Box<dyn std::error::Erro
     if 0 < limit {
         log::info!("We are going to process only {limit} crates");
     } else {
-        log::info!("We are going to process all the crates we find locally",);
+        log::info!("We are going to process all the crates we find locally");
     }
     create_data_folders()?;
     let mut crate_details = vec![];
@@ -92,11 +92,7 @@ fn collect_data_from_crates(limit: usize) -> Result<(), Box<dyn std::error::Erro
         // }
 
         // if it fails collect all the data and save to the disk
-        let mut details = CrateDetails::new();
-        details.has_files(&dir_entry.path())?;
-        log::info!("details: {details:#?}");
-        details.disk_size(&dir_entry.path());
-        details.save(filepath)?;
+        let mut details = analyze_crate_folder(&dir_entry.path(), &filepath)?;
 
         let path_or_none = if details.has_cargo_toml {
             Some(dir_entry.path().join("Cargo.toml"))
@@ -113,6 +109,11 @@ fn collect_data_from_crates(limit: usize) -> Result<(), Box<dyn std::error::Erro
                     if details.has_cargo_toml_in_lower_case {
                         released_cargo_toml_in_lower_case.push(cargo.package.name.clone());
                     }
+                    details.binaries = find_binaries(&dir_entry.path(), &cargo);
+                    details
+                        .default_dash_run
+                        .clone_from(&cargo.package.default_dash_run);
+                    details.check_changelog(&dir_entry.path(), &cargo.package.version);
                     released_crates.push(cargo.clone());
                 }
                 Err(err) => {
@@ -140,6 +141,7 @@ fn collect_data_from_crates(limit: usize) -> Result<(), Box<dyn std::error::Erro
             released_cargo_toml_missing.push(dir_entry.file_name().display().to_string());
         }
 
+        details.save(filepath)?;
         crate_details.push(details);
     }
 
@@ -172,3 +174,164 @@ fn collect_data_from_crates(limit: usize) -> Result<(), Box<dyn std::error::Erro
 
     Ok(())
 }
+
+/// Collect the details we can get from the files of the crate, without its Cargo.toml.
+fn analyze_crate_folder(
+    path: &Path,
+    filepath: &PathBuf,
+) -> Result<CrateDetails, Box<dyn std::error::Error>> {
+    let mut details = CrateDetails::new();
+    // running rustdoc takes a lot longer than the rest of the analysis, keep its result
+    if let Ok(previous) = load_crate_details(filepath) {
+        details.doc_coverage = previous.doc_coverage;
+    }
+    details.has_files(path)?;
+    log::info!("details: {details:#?}");
+    details.disk_size(path);
+    details.scan_source(path);
+    details.check_missing_docs_lint(path);
+
+    Ok(details)
+}
+
+/// Collect the binary targets of the crate the same way Cargo does.
+///
+/// Unless `autobins` is turned off, `src/main.rs`, `src/bin/*.rs` and `src/bin/*/main.rs` are binaries.
+/// The `[[bin]]` entries of the Cargo.toml file override the discovered targets with the same name or path.
+fn find_binaries(path: &Path, cargo: &Cargo) -> Vec<Binary> {
+    let package_name = &cargo.package.name;
+    let mut binaries: Vec<Binary> = vec![];
+
+    if cargo.package.autobins != Some(false) {
+        if path.join("src/main.rs").exists() {
+            binaries.push(Binary {
+                name: package_name.clone(),
+                path: String::from("src/main.rs"),
+            });
+        }
+
+        if let Ok(entries) = path.join("src/bin").read_dir() {
+            for entry in entries.flatten() {
+                let entry_path = entry.path();
+                let Some(file_name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                    continue;
+                };
+                if entry_path.is_file() {
+                    if let Some(name) = file_name.strip_suffix(".rs") {
+                        binaries.push(Binary {
+                            name: name.to_owned(),
+                            path: format!("src/bin/{file_name}"),
+                        });
+                    }
+                } else if entry_path.join("main.rs").exists() {
+                    binaries.push(Binary {
+                        path: format!("src/bin/{file_name}/main.rs"),
+                        name: file_name,
+                    });
+                } else {
+                    log::debug!("Not a binary: {:?}", entry_path.display());
+                }
+            }
+        }
+    }
+
+    for bin in cargo.bin.iter().flatten() {
+        let name = bin.name.clone().unwrap_or_else(|| package_name.clone());
+        let bin_path = bin
+            .path
+            .clone()
+            .unwrap_or_else(|| default_bin_path(path, &name, package_name));
+
+        match binaries
+            .iter_mut()
+            .find(|binary| binary.name == name || binary.path == bin_path)
+        {
+            Some(binary) => {
+                binary.name = name;
+                binary.path = bin_path;
+            }
+            None => binaries.push(Binary {
+                name,
+                path: bin_path,
+            }),
+        }
+    }
+
+    #[expect(clippy::min_ident_chars)]
+    binaries.sort_by(|a, b| a.name.cmp(&b.name));
+    log::info!("binaries: {binaries:?}");
+
+    binaries
+}
+
+/// The path Cargo uses for a `[[bin]]` entry that has no `path` field.
+fn default_bin_path(root: &Path, name: &str, package_name: &str) -> String {
+    let candidates = [
+        format!("src/bin/{name}.rs"),
+        format!("src/bin/{name}/main.rs"),
+    ];
+    for candidate in candidates {
+        if root.join(&candidate).exists() {
+            return candidate;
+        }
+    }
+    if name == package_name {
+        return String::from("src/main.rs");
+    }
+
+    format!("src/bin/{name}.rs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_find_binaries() {
+        use tempdir::TempDir;
+        let tmp_dir = TempDir::new("demo").unwrap();
+        let root = tmp_dir.path();
+        std::fs::create_dir_all(root.join("src/bin/server")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("src/bin/client.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("src/bin/server/main.rs"), "fn main() {}").unwrap();
+
+        let mut cargo: Cargo =
+            toml::from_str("[package]\nname = \"demo\"\nversion = \"0.1.0\"\n").unwrap();
+        assert_eq!(
+            find_binaries(root, &cargo),
+            vec![
+                Binary {
+                    name: String::from("client"),
+                    path: String::from("src/bin/client.rs")
+                },
+                Binary {
+                    name: String::from("demo"),
+                    path: String::from("src/main.rs")
+                },
+                Binary {
+                    name: String::from("server"),
+                    path: String::from("src/bin/server/main.rs")
+                },
+            ]
+        );
+
+        cargo = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nautobins = false\ndefault-run = \"dm\"\n\n[[bin]]\nname = \"dm\"\npath = \"src/main.rs\"\n\n[[bin]]\nname = \"client\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            find_binaries(root, &cargo),
+            vec![
+                Binary {
+                    name: String::from("client"),
+                    path: String::from("src/bin/client.rs")
+                },
+                Binary {
+                    name: String::from("dm"),
+                    path: String::from("src/main.rs")
+                },
+            ]
+        );
+    }
+}
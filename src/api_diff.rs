@@ -0,0 +1,528 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::rustdoc_json::{id_key, item_kind};
+use crate::{semver_root, RustVersion};
+
+/// How many of the removed, changed and added items we keep in a diff.
+const MAX_LISTED_ITEMS: usize = 50;
+
+/// Fields of the rustdoc JSON that hold the ids of other items or the location of the item.
+/// The ids are not stable between two runs of rustdoc, so we remove them before comparing the items.
+const UNSTABLE_FIELDS: [&str; 9] = [
+    "id",
+    "items",
+    "impls",
+    "implementations",
+    "fields",
+    "variants",
+    "blanket_impl",
+    "span",
+    "links",
+];
+
+/// The attribute as rustdoc lists it among the `attrs` of the item, `#[non_exhaustive]` in the older versions of the format.
+const NON_EXHAUSTIVE: &str = "non_exhaustive";
+
+/// The difference between the public API of two consecutive releases of a crate.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ApiDiff {
+    pub old_version: String,
+    pub new_version: String,
+    pub checked_at: String,
+
+    /// The version bump according to the rules of Cargo: `major`, `minor`, `patch`, `pre-release` or `unknown`.
+    /// A change of the minor version of a `0.y.z` release is `major` as Cargo considers it incompatible.
+    pub bump: String,
+
+    /// The public items of the old release that are missing from the new one, e.g. `function foo::parse`.
+    pub removed: Vec<String>,
+    pub removed_count: usize,
+
+    /// The public items that are in both releases but with a different signature.
+    pub changed: Vec<String>,
+    pub changed_count: usize,
+
+    pub added: Vec<String>,
+    pub added_count: usize,
+
+    /// Some items were removed or changed, or an added item breaks the code using the crate,
+    /// e.g. a variant of an exhaustive enum or a trait method without a default.
+    pub breaking: bool,
+
+    /// A breaking change was released with a `minor` or `patch` bump.
+    pub violation: bool,
+
+    /// Why we could not compare the releases, e.g. rustdoc failed on one of them.
+    pub error: String,
+}
+
+impl ApiDiff {
+    pub fn new(old_version: &str, new_version: &str) -> Self {
+        Self {
+            old_version: old_version.to_owned(),
+            new_version: new_version.to_owned(),
+            checked_at: String::new(),
+            bump: version_bump(old_version, new_version).to_owned(),
+            removed: vec![],
+            removed_count: 0,
+            changed: vec![],
+            changed_count: 0,
+            added: vec![],
+            added_count: 0,
+            breaking: false,
+            violation: false,
+            error: String::new(),
+        }
+    }
+
+    /// Compare the public API of the two releases.
+    pub fn compare(&mut self, old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) {
+        let removed = old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .collect::<Vec<_>>();
+        let changed = old
+            .iter()
+            .filter(|item| new.get(item.0).is_some_and(|signature| signature != item.1))
+            .map(|item| item.0)
+            .collect::<Vec<_>>();
+        let added = new
+            .keys()
+            .filter(|name| !old.contains_key(*name))
+            .collect::<Vec<_>>();
+
+        self.removed_count = removed.len();
+        self.removed = listed(&removed);
+        self.changed_count = changed.len();
+        self.changed = listed(&changed);
+        self.added_count = added.len();
+        self.added = listed(&added);
+        self.breaking = 0 < self.removed_count + self.changed_count
+            || added
+                .iter()
+                .any(|name| is_breaking_addition(name, old, new));
+        self.violation = self.breaking && matches!(self.bump.as_str(), "minor" | "patch");
+    }
+}
+
+/// Adding the item breaks the code using the crate:
+///
+/// * a variant of an enum without `#[non_exhaustive]` (a `match` on it is no longer exhaustive),
+/// * a field of a struct or variant without `#[non_exhaustive]` and without private fields (a struct literal is missing it),
+/// * a method without a default of a trait (the implementations are missing it).
+///
+/// Only items added to an existing parent count, the children of a new enum or struct are not breaking.
+fn is_breaking_addition(
+    name: &str,
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> bool {
+    let Some((kind, path)) = name.split_once(' ') else {
+        return false;
+    };
+    let Some((parent_path, _)) = path.rsplit_once("::") else {
+        return false;
+    };
+    let old_parent = |parent_kinds: &[&str]| {
+        parent_kinds
+            .iter()
+            .find_map(|parent_kind| old.get(&format!("{parent_kind} {parent_path}")))
+            .and_then(|parent| serde_json::from_str::<Value>(parent).ok())
+    };
+
+    match kind {
+        "variant" => old_parent(&["enum"]).is_some_and(|parent| !is_non_exhaustive(&parent)),
+        "struct_field" => old_parent(&["struct", "variant"])
+            .is_some_and(|parent| !is_non_exhaustive(&parent) && !has_private_fields(&parent)),
+        "function" => {
+            old_parent(&["trait"]).is_some()
+                && new
+                    .get(name)
+                    .and_then(|method| serde_json::from_str::<Value>(method).ok())
+                    .and_then(|method| method.get("function")?.get("has_body")?.as_bool())
+                    == Some(false)
+        }
+        _ => false,
+    }
+}
+
+/// The signature of an item marked with `#[non_exhaustive]`, see `signature`.
+fn is_non_exhaustive(signature: &Value) -> bool {
+    signature.get(NON_EXHAUSTIVE).and_then(Value::as_bool) == Some(true)
+}
+
+/// rustdoc leaves out the private fields of a struct or variant and records that it did so,
+/// tuple structs have `null` in place of the private fields.
+fn has_private_fields(value: &Value) -> bool {
+    #[expect(clippy::pattern_type_mismatch, reason = "we only borrow the values")]
+    match value {
+        Value::Object(object) => object.iter().any(|field| match field.0.as_str() {
+            "has_stripped_fields" | "fields_stripped" => field.1.as_bool() == Some(true),
+            "tuple" => field
+                .1
+                .as_array()
+                .is_some_and(|fields| fields.iter().any(Value::is_null)),
+            _ => has_private_fields(field.1),
+        }),
+        Value::Array(values) => values.iter().any(has_private_fields),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => false,
+    }
+}
+
+fn listed(names: &[&String]) -> Vec<String> {
+    names
+        .iter()
+        .take(MAX_LISTED_ITEMS)
+        .map(|name| (*name).clone())
+        .collect()
+}
+
+/// The kind of the version change between two releases according to the rules of Cargo.
+pub fn version_bump(old: &str, new: &str) -> &'static str {
+    let parse = |version: &str| {
+        let release = version.split(['-', '+']).next().unwrap_or_default();
+        RustVersion::parse(release)
+    };
+    let (Some(old_release), Some(new_release)) = (parse(old), parse(new)) else {
+        return "unknown";
+    };
+
+    if old_release == new_release {
+        // only the pre-release or the build metadata changed
+        "pre-release"
+    } else if old_release.major != new_release.major
+        || (old_release.major == 0
+            && (old_release.minor != new_release.minor || old_release.minor == 0))
+    {
+        "major"
+    } else if old_release.minor != new_release.minor {
+        "minor"
+    } else {
+        "patch"
+    }
+}
+
+/// The public items of the crate in the JSON output of rustdoc and their signatures.
+///
+/// The keys are the kind and the path of the items, e.g. `function foo::parse` or `struct_field foo::Config::name`.
+/// The items are taken from `paths`, their fields, variants, trait items and the methods of their inherent impls are included as well.
+pub fn api_surface(rustdoc: &Value) -> BTreeMap<String, String> {
+    let mut surface = BTreeMap::new();
+    let (Some(index), Some(paths)) = (
+        rustdoc.get("index").and_then(Value::as_object),
+        rustdoc.get("paths").and_then(Value::as_object),
+    ) else {
+        return surface;
+    };
+    let item_by_id = |id: &Value| index.get(&id_key(id));
+
+    for (id, summary) in paths {
+        if summary.get("crate_id").and_then(Value::as_u64) != Some(0) {
+            continue;
+        }
+        let Some(item) = index.get(id) else {
+            continue;
+        };
+        if item.get("visibility").and_then(Value::as_str) != Some("public") {
+            continue;
+        }
+        let path = summary
+            .get("path")
+            .and_then(Value::as_array)
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("::")
+            })
+            .unwrap_or_default();
+        let kind = item_kind(item).unwrap_or("unknown");
+        surface.insert(format!("{kind} {path}"), signature(item));
+        if kind == "module" {
+            continue;
+        }
+
+        let inner = item.get("inner").and_then(|inner| inner.get(kind));
+        let mut children = inner.map(child_ids).unwrap_or_default();
+        for impl_id in inner
+            .and_then(|kind_inner| kind_inner.get("impls"))
+            .map(ids)
+            .unwrap_or_default()
+        {
+            let Some(inherent_impl) = item_by_id(impl_id)
+                .and_then(|impl_item| impl_item.get("inner"))
+                .and_then(|impl_inner| impl_inner.get("impl"))
+                .filter(|impl_inner| impl_inner.get("trait").is_none_or(Value::is_null))
+            else {
+                continue;
+            };
+            children.extend(inherent_impl.get("items").map(ids).unwrap_or_default());
+        }
+
+        for child in children.into_iter().filter_map(item_by_id) {
+            let Some(name) = child.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            // the private methods of the inherent impls are also listed
+            if child.get("visibility").and_then(Value::as_str) == Some("crate")
+                || child.get("visibility").is_some_and(Value::is_object)
+            {
+                continue;
+            }
+            surface.insert(
+                format!("{} {path}::{name}", item_kind(child).unwrap_or("unknown")),
+                signature(child),
+            );
+        }
+    }
+
+    surface
+}
+
+/// The ids of the fields, variants or trait items of a struct, enum, union or trait.
+/// The place of the fields changed between the versions of the format, so we look for them at any depth.
+fn child_ids(inner: &Value) -> Vec<&Value> {
+    let mut found = vec![];
+    if let Some(object) = inner.as_object() {
+        for (key, value) in object {
+            match key.as_str() {
+                "fields" | "variants" | "items" => found.extend(ids(value)),
+                "impls" => {}
+                _ => found.extend(child_ids(value)),
+            }
+        }
+    }
+    found
+}
+
+fn ids(value: &Value) -> Vec<&Value> {
+    value
+        .as_array()
+        .map(|ids| ids.iter().filter(|id| !id.is_null()).collect())
+        .unwrap_or_default()
+}
+
+/// The `inner` field of the item without the ids, as a string we can compare.
+/// Adding `#[non_exhaustive]` is a breaking change, so we include it in the signature.
+fn signature(item: &Value) -> String {
+    let mut inner = item
+        .get("inner")
+        .map(without_unstable_fields)
+        .unwrap_or_default();
+    let non_exhaustive = item
+        .get("attrs")
+        .and_then(Value::as_array)
+        .is_some_and(|attrs| {
+            attrs
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|attr| attr.contains(NON_EXHAUSTIVE))
+        });
+    if let Some(object) = inner.as_object_mut().filter(|_| non_exhaustive) {
+        object.insert(NON_EXHAUSTIVE.to_owned(), Value::Bool(true));
+    }
+    inner.to_string()
+}
+
+fn without_unstable_fields(value: &Value) -> Value {
+    #[expect(clippy::pattern_type_mismatch, reason = "we only borrow the values")]
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .filter(|field| !UNSTABLE_FIELDS.contains(&field.0.as_str()))
+                .map(|field| (field.0.clone(), without_unstable_fields(field.1)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(without_unstable_fields).collect()),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => value.clone(),
+    }
+}
+
+fn semver_history_path(name: &str) -> PathBuf {
+    semver_root().join(format!("{name}.json"))
+}
+
+/// The diffs of the releases of the crate we compared, the oldest first.
+pub fn load_semver_history(name: &str) -> Vec<ApiDiff> {
+    let filepath = semver_history_path(name);
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return vec![];
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        vec![]
+    })
+}
+
+pub fn save_semver_history(name: &str, history: &[ApiDiff]) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(semver_root())?;
+    let content = serde_json::to_string(history)?;
+    fs::write(semver_history_path(name), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_version_bump() {
+        assert_eq!(version_bump("1.2.3", "2.0.0"), "major");
+        assert_eq!(version_bump("1.2.3", "1.3.0"), "minor");
+        assert_eq!(version_bump("1.2.3", "1.2.4"), "patch");
+        assert_eq!(version_bump("0.2.3", "0.3.0"), "major");
+        assert_eq!(version_bump("0.2.3", "0.2.4"), "patch");
+        assert_eq!(version_bump("0.0.3", "0.0.4"), "major");
+        assert_eq!(version_bump("1.0.0-beta.1", "1.0.0-beta.2"), "pre-release");
+        assert_eq!(version_bump("1.0.0-rc.1", "1.0.1"), "patch");
+        assert_eq!(version_bump("x", "1.0.0"), "unknown");
+    }
+
+    fn rustdoc(parse_output: &str, with_name: bool) -> Value {
+        let mut index = serde_json::json!({
+            "0": {"crate_id": 0, "name": "foo", "visibility": "public", "inner": {"module": {"items": [1, 2, 5]}}},
+            "1": {"crate_id": 0, "name": "parse", "visibility": "public", "inner": {"function": {"sig": {"inputs": [["text", {"primitive": "str"}]], "output": {"resolved_path": {"path": parse_output, "id": 7}}}}}},
+            "2": {"crate_id": 0, "name": "Config", "visibility": "public", "inner": {"struct": {"kind": {"plain": {"fields": [3], "has_stripped_fields": false}}, "impls": [4]}}},
+            "3": {"crate_id": 0, "name": "path", "visibility": "public", "inner": {"struct_field": {"primitive": "str"}}},
+            "4": {"crate_id": 0, "name": null, "visibility": "default", "inner": {"impl": {"trait": null, "items": [6]}}},
+            "5": {"crate_id": 0, "name": "helper", "visibility": "crate", "inner": {"function": {}}},
+            "6": {"crate_id": 0, "name": "load", "visibility": "public", "inner": {"function": {"sig": {}}}},
+        });
+        if with_name {
+            index["8"] = serde_json::json!({"crate_id": 0, "name": "name", "visibility": "public", "inner": {"struct_field": {"primitive": "str"}}});
+            index["2"]["inner"]["struct"]["kind"]["plain"]["fields"] = serde_json::json!([3, 8]);
+        }
+        serde_json::json!({
+            "root": 0,
+            "index": index,
+            "paths": {
+                "0": {"crate_id": 0, "kind": "module", "path": ["foo"]},
+                "1": {"crate_id": 0, "kind": "function", "path": ["foo", "parse"]},
+                "2": {"crate_id": 0, "kind": "struct", "path": ["foo", "Config"]},
+                "7": {"crate_id": 1, "kind": "struct", "path": ["std", "string", "String"]},
+            },
+        })
+    }
+
+    #[test]
+    fn check_api_surface() {
+        let surface = api_surface(&rustdoc("String", false));
+        assert_eq!(
+            surface.keys().collect::<Vec<_>>(),
+            vec![
+                "function foo::Config::load",
+                "function foo::parse",
+                "module foo",
+                "struct foo::Config",
+                "struct_field foo::Config::path",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_compare() {
+        let old = api_surface(&rustdoc("String", false));
+        let new = api_surface(&rustdoc("Config", true));
+
+        let mut diff = ApiDiff::new("1.2.3", "1.2.4");
+        diff.compare(&old, &new);
+        assert_eq!(diff.bump, "patch");
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, vec!["function foo::parse"]);
+        assert_eq!(diff.added, vec!["struct_field foo::Config::name"]);
+        assert!(diff.breaking);
+        assert!(diff.violation);
+
+        let mut same = ApiDiff::new("1.2.3", "1.3.0");
+        same.compare(&old, &old);
+        assert!(!same.breaking);
+        assert!(!same.violation);
+
+        let breaking = |old_index: Value, new_index: Value, kind: &str| {
+            let surface = |index: Value| {
+                api_surface(&serde_json::json!({
+                    "root": 0,
+                    "index": index,
+                    "paths": {"1": {"crate_id": 0, "kind": kind, "path": ["foo", "Item"]}},
+                }))
+            };
+            let mut addition = ApiDiff::new("1.2.3", "1.3.0");
+            addition.compare(&surface(old_index), &surface(new_index));
+            assert_eq!(addition.added_count, 1, "{:?}", addition.added);
+            assert!(addition.changed.is_empty(), "{:?}", addition.changed);
+            addition.breaking
+        };
+
+        // a new field of a struct without private fields
+        let field = serde_json::json!({"crate_id": 0, "name": "name", "visibility": "public", "inner": {"struct_field": {"primitive": "str"}}});
+        let plain_struct = |attrs: Value, fields: Value, has_stripped_fields: bool| {
+            serde_json::json!({"crate_id": 0, "name": "Item", "visibility": "public", "attrs": attrs,
+                "inner": {"struct": {"kind": {"plain": {"fields": fields, "has_stripped_fields": has_stripped_fields}}, "impls": []}}})
+        };
+        assert!(breaking(
+            serde_json::json!({"1": plain_struct(serde_json::json!([]), serde_json::json!([]), false)}),
+            serde_json::json!({"1": plain_struct(serde_json::json!([]), serde_json::json!([2]), false), "2": field}),
+            "struct",
+        ));
+        assert!(!breaking(
+            serde_json::json!({"1": plain_struct(serde_json::json!([]), serde_json::json!([]), true)}),
+            serde_json::json!({"1": plain_struct(serde_json::json!([]), serde_json::json!([2]), true), "2": field}),
+            "struct",
+        ));
+        assert!(!breaking(
+            serde_json::json!({"1": plain_struct(serde_json::json!(["#[non_exhaustive]"]), serde_json::json!([]), false)}),
+            serde_json::json!({"1": plain_struct(serde_json::json!(["#[non_exhaustive]"]), serde_json::json!([2]), false), "2": field}),
+            "struct",
+        ));
+
+        // a new variant
+        let variant = |name: &str| serde_json::json!({"crate_id": 0, "name": name, "visibility": "default", "inner": {"variant": {"kind": "plain", "discriminant": null}}});
+        let enumeration = |attrs: Value, variants: Value| {
+            serde_json::json!({"crate_id": 0, "name": "Item", "visibility": "public", "attrs": attrs,
+                "inner": {"enum": {"variants": variants, "has_stripped_variants": false, "impls": []}}})
+        };
+        assert!(breaking(
+            serde_json::json!({"1": enumeration(serde_json::json!([]), serde_json::json!([2])), "2": variant("A")}),
+            serde_json::json!({"1": enumeration(serde_json::json!([]), serde_json::json!([2, 3])), "2": variant("A"), "3": variant("B")}),
+            "enum",
+        ));
+        assert!(!breaking(
+            serde_json::json!({"1": enumeration(serde_json::json!(["non_exhaustive"]), serde_json::json!([2])), "2": variant("A")}),
+            serde_json::json!({"1": enumeration(serde_json::json!(["non_exhaustive"]), serde_json::json!([2, 3])), "2": variant("A"), "3": variant("B")}),
+            "enum",
+        ));
+
+        // a new trait method
+        let method = |has_body: bool| serde_json::json!({"crate_id": 0, "name": "run", "visibility": "default", "inner": {"function": {"sig": {}, "has_body": has_body}}});
+        let trait_item = |items: Value| serde_json::json!({"crate_id": 0, "name": "Item", "visibility": "public", "inner": {"trait": {"items": items, "is_auto": false, "implementations": []}}});
+        assert!(breaking(
+            serde_json::json!({"1": trait_item(serde_json::json!([]))}),
+            serde_json::json!({"1": trait_item(serde_json::json!([2])), "2": method(false)}),
+            "trait",
+        ));
+        assert!(!breaking(
+            serde_json::json!({"1": trait_item(serde_json::json!([]))}),
+            serde_json::json!({"1": trait_item(serde_json::json!([2])), "2": method(true)}),
+            "trait",
+        ));
+
+        // the variants of a new enum are not breaking
+        let mut new_enum = ApiDiff::new("1.2.3", "1.3.0");
+        new_enum.compare(
+            &BTreeMap::new(),
+            &BTreeMap::from([
+                (String::from("enum foo::Item"), String::from("{}")),
+                (String::from("variant foo::Item::A"), String::from("{}")),
+            ]),
+        );
+        assert!(!new_enum.breaking);
+    }
+}
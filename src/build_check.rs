@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::path::Path;
+
+use clap::Parser;
+
+use rust_digger::{
+    crates_root, load_build_report, load_cargo_toml_simplified, save_build_report, BuildReport,
+    ElapsedTimer, Runner,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Limit the number of crates we process."
+    )]
+    limit: usize,
+}
+
+/// For each released crate we have locally
+///     skip it if we already checked the same version with the same compiler
+///     run `cargo check --offline` in the container using only the vendored registry
+///     save the outcome and the first error code.
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("Start checking if the released crates build");
+    let start_time = std::time::Instant::now();
+
+    if let Err(err) = run() {
+        log::error!("Error: {err}");
+    }
+
+    log::info!("Elapsed time: {} sec.", start_time.elapsed().as_secs());
+    log::info!("End checking if the released crates build");
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("build_check");
+
+    let args = Cli::parse();
+    log::info!("Limit: {}", args.limit);
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let rustc_version = runner.version("rustc");
+
+    let mut count: usize = 0;
+    for entry in crates_root().read_dir()? {
+        if 0 < args.limit && args.limit <= count {
+            break;
+        }
+        let crate_path = entry?.path();
+
+        let (name, version) = match load_cargo_toml_simplified(&crate_path.join("Cargo.toml")) {
+            Ok(name_version) => name_version,
+            Err(err) => {
+                log::warn!(
+                    "Could not read Cargo.toml in {:?}: {err}",
+                    crate_path.display()
+                );
+                continue;
+            }
+        };
+        if load_build_report(&name).is_up_to_date(&version, &rustc_version) {
+            continue;
+        }
+
+        log::info!("cargo check ({count}/{}) {name} {version}", args.limit);
+        let mut report = match runner.run("check", &crate_path) {
+            Ok(result) => BuildReport::from_job_result(&result),
+            Err(err) => {
+                log::error!("Could not run cargo check: {err}");
+                let mut report = BuildReport::new();
+                report.error = err.to_string();
+                report
+            }
+        };
+        log::info!("{name} {version}: {}", report.outcome.name());
+        report.version = version;
+        report.rustc_version.clone_from(&rustc_version);
+        save_build_report(&name, &report)?;
+
+        count += 1;
+    }
+
+    log::info!("Checked {count} crates");
+
+    Ok(())
+}
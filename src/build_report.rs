@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cargo_messages::{compiler_messages_by_package, Diagnostic};
+use crate::{builds_root, JobResult};
+
+/// Parts of the error output of cargo telling us that a dependency is not in our vendored registry.
+const MISSING_DEPENDENCY: [&str; 3] = [
+    "no matching package",
+    "failed to select a version",
+    "failed to load source",
+];
+
+/// How many lines of the error output we keep if there was no compiler error.
+const ERROR_LINES: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum BuildOutcome {
+    #[default]
+    NotChecked,
+    Success,
+    /// The crate itself failed to compile.
+    CompileError,
+    /// A dependency was not available in the vendored registry or it failed to compile.
+    DependencyError,
+    Timeout,
+    /// `cargo check` failed without a compiler error, e.g. a build script failed.
+    Failure,
+}
+
+impl BuildOutcome {
+    /// The name we use in the URLs and the templates, e.g. `compile-error`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NotChecked => "not-checked",
+            Self::Success => "success",
+            Self::CompileError => "compile-error",
+            Self::DependencyError => "dependency-error",
+            Self::Timeout => "timeout",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+/// The result of running `cargo check` on a released crate.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BuildReport {
+    /// The version of the crate we checked, empty if we have not checked the crate.
+    pub version: String,
+
+    pub checked_at: String,
+
+    /// The output of `rustc --version` in the container.
+    pub rustc_version: String,
+
+    pub outcome: BuildOutcome,
+
+    /// `None` if `cargo check` timed out or could not be run at all.
+    pub exit_code: Option<i32>,
+
+    /// The code of the first compiler error, e.g. `E0308`. Empty if the error had no code.
+    pub error_code: String,
+
+    /// The message of the first compiler error or the first lines of the error output.
+    pub error: String,
+
+    pub duration_secs: u64,
+}
+
+impl BuildReport {
+    pub const fn new() -> Self {
+        Self {
+            version: String::new(),
+            checked_at: String::new(),
+            rustc_version: String::new(),
+            outcome: BuildOutcome::NotChecked,
+            exit_code: None,
+            error_code: String::new(),
+            error: String::new(),
+            duration_secs: 0,
+        }
+    }
+
+    /// We have already checked this version with this compiler.
+    pub fn is_up_to_date(&self, version: &str, rustc_version: &str) -> bool {
+        self.version == version && self.rustc_version == rustc_version
+    }
+
+    /// Classify the result of the `check` job, see `runner.yaml`.
+    pub fn from_job_result(result: &JobResult) -> Self {
+        let mut report = Self::new();
+        report.checked_at.clone_from(&result.started_at);
+        report.exit_code = result.exit_code;
+        report.duration_secs = result.duration_secs;
+
+        if result.timed_out {
+            report.outcome = BuildOutcome::Timeout;
+            return report;
+        }
+        if result.succeeded() {
+            report.outcome = BuildOutcome::Success;
+            return report;
+        }
+
+        // the errors of the dependencies are not the fault of the crate we check
+        let (local, dependencies) = compiler_messages_by_package(&result.stdout);
+        for (diagnostics, outcome) in [
+            (local, BuildOutcome::CompileError),
+            (dependencies, BuildOutcome::DependencyError),
+        ] {
+            if let Some(diagnostic) = diagnostics.into_iter().find(Diagnostic::is_compiler_error) {
+                report.outcome = outcome;
+                diagnostic.code().clone_into(&mut report.error_code);
+                report.error = diagnostic.message;
+                return report;
+            }
+        }
+
+        report.outcome = if MISSING_DEPENDENCY
+            .iter()
+            .any(|text| result.stderr.contains(text))
+        {
+            BuildOutcome::DependencyError
+        } else {
+            BuildOutcome::Failure
+        };
+        report.error = result
+            .stderr
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(ERROR_LINES)
+            .collect::<Vec<_>>()
+            .join("\n");
+        report
+    }
+}
+
+impl Default for BuildReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn build_report_path(name: &str) -> PathBuf {
+    builds_root().join(format!("{name}.json"))
+}
+
+pub fn load_build_report(name: &str) -> BuildReport {
+    let filepath = build_report_path(name);
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return BuildReport::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        BuildReport::new()
+    })
+}
+
+pub fn save_build_report(name: &str, report: &BuildReport) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(builds_root())?;
+    let content = serde_json::to_string(report)?;
+    fs::write(build_report_path(name), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_result(exit_code: Option<i32>, stdout: &str, stderr: &str) -> JobResult {
+        JobResult {
+            job: String::from("check"),
+            commit: String::new(),
+            started_at: String::from("2024-01-01T00:00:00+00:00"),
+            duration_secs: 3,
+            exit_code,
+            timed_out: false,
+            stdout: stdout.to_owned(),
+            stderr: stderr.to_owned(),
+        }
+    }
+
+    #[test]
+    fn check_from_job_result() {
+        let success = BuildReport::from_job_result(&job_result(Some(0), "", ""));
+        assert_eq!(success.outcome, BuildOutcome::Success);
+        assert_eq!(success.duration_secs, 3);
+
+        let dependency = r#"{"reason":"compiler-message","package_id":"registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0","message":{"message":"use of unstable library feature","level":"error","code":{"code":"E0658","explanation":"..."}}}"#;
+        let stdout = [
+            dependency,
+            r#"{"reason":"compiler-message","package_id":"path+file:///work#foo@0.1.0","message":{"message":"unused variable: `x`","level":"warning","code":{"code":"unused_variables","explanation":null}}}"#,
+            r#"{"reason":"compiler-message","package_id":"path+file:///work#foo@0.1.0","message":{"message":"mismatched types","level":"error","code":{"code":"E0308","explanation":"..."}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0 (path+file:///work)","message":{"message":"cannot find value `y` in this scope","level":"error","code":{"code":"E0425","explanation":"..."}}}"#,
+        ]
+        .join("\n");
+        let compile_error = BuildReport::from_job_result(&job_result(Some(101), &stdout, ""));
+        assert_eq!(compile_error.outcome, BuildOutcome::CompileError);
+        assert_eq!(compile_error.error_code, "E0308");
+        assert_eq!(compile_error.error, "mismatched types");
+
+        let broken_dependency =
+            BuildReport::from_job_result(&job_result(Some(101), dependency, ""));
+        assert_eq!(broken_dependency.outcome, BuildOutcome::DependencyError);
+        assert_eq!(broken_dependency.error_code, "E0658");
+
+        let stderr = "error: no matching package named `serde` found\nlocation searched: directory source `/registry`\n";
+        let dependency_error = BuildReport::from_job_result(&job_result(Some(101), "", stderr));
+        assert_eq!(dependency_error.outcome, BuildOutcome::DependencyError);
+        assert_eq!(dependency_error.error, stderr.trim_end());
+
+        let failure = BuildReport::from_job_result(&job_result(
+            Some(101),
+            "",
+            "error: failed to run custom build command",
+        ));
+        assert_eq!(failure.outcome, BuildOutcome::Failure);
+
+        let mut result = job_result(None, "", "");
+        result.timed_out = true;
+        assert_eq!(
+            BuildReport::from_job_result(&result).outcome,
+            BuildOutcome::Timeout
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use serde::Deserialize;
+
+/// A line of the output of cargo with `--message-format=json`.
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+
+    /// The package the message is about, e.g. `path+file:///work#foo@0.1.0`
+    /// or `registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0` for a dependency.
+    #[serde(default)]
+    package_id: String,
+
+    message: Option<Diagnostic>,
+}
+
+impl CargoMessage {
+    /// The package we ran cargo on, not one of its dependencies from the registry.
+    /// Older versions of cargo write the package id as `foo 0.1.0 (path+file:///work)`.
+    fn is_local_package(&self) -> bool {
+        self.package_id.contains("path+file://")
+    }
+}
+
+/// A warning or an error of the compiler or of clippy.
+#[derive(Debug, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub level: String,
+    pub code: Option<DiagnosticCode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiagnosticCode {
+    pub code: String,
+}
+
+impl Diagnostic {
+    /// e.g. `E0308`, `unused_variables` or `clippy::needless_return`. Empty for messages like "3 warnings emitted".
+    pub fn code(&self) -> &str {
+        self.code.as_ref().map_or("", |code| code.code.as_str())
+    }
+
+    /// An error of the compiler, not a lint denied by default.
+    pub fn is_compiler_error(&self) -> bool {
+        let code = self.code();
+        self.level == "error" && (code.is_empty() || code.starts_with('E'))
+    }
+}
+
+/// The compiler messages in the output of a cargo command run with `--message-format=json`.
+/// Lines that are not JSON (e.g. the output of build scripts) are skipped.
+fn cargo_messages(stdout: &str) -> impl Iterator<Item = CargoMessage> + '_ {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|cargo_message| cargo_message.reason == "compiler-message")
+}
+
+/// The diagnostics in the output of a cargo command run with `--message-format=json`.
+pub fn compiler_messages(stdout: &str) -> Vec<Diagnostic> {
+    cargo_messages(stdout)
+        .filter_map(|cargo_message| cargo_message.message)
+        .collect()
+}
+
+/// The diagnostics of the package we ran cargo on and the diagnostics of its dependencies.
+pub fn compiler_messages_by_package(stdout: &str) -> (Vec<Diagnostic>, Vec<Diagnostic>) {
+    let (local, dependencies): (Vec<_>, Vec<_>) =
+        cargo_messages(stdout).partition(CargoMessage::is_local_package);
+    (
+        local
+            .into_iter()
+            .filter_map(|cargo_message| cargo_message.message)
+            .collect(),
+        dependencies
+            .into_iter()
+            .filter_map(|cargo_message| cargo_message.message)
+            .collect(),
+    )
+}
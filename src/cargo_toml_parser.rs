@@ -132,10 +132,18 @@ impl Default for Package {
 // }
 // CargoDependencyValue
 
+/// A `[[bin]]` entry of the Cargo.toml file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bin {
+    pub name: Option<String>,
+    pub path: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Cargo {
     pub package: Package,
     pub dependencies: Option<HashMap<String, Value>>,
+    pub bin: Option<Vec<Bin>>,
 }
 
 impl Cargo {
@@ -143,6 +151,7 @@ impl Cargo {
         Self {
             package: Package::new(),
             dependencies: None,
+            bin: None,
         }
     }
 }
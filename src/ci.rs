@@ -0,0 +1,817 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+/// The folder of the GitHub Actions workflows relative to the root of the repository.
+const GITHUB_WORKFLOWS: &str = ".github/workflows";
+
+/// The keys of the strategy matrix people use for the Rust toolchain.
+const TOOLCHAIN_MATRIX_KEYS: [&str; 6] = [
+    "rust",
+    "toolchain",
+    "rust-version",
+    "rust_version",
+    "channel",
+    "msrv",
+];
+
+/// What the GitHub Actions workflows of a repository do.
+/// The checks are heuristics based on the commands in the `run` steps and on the actions used.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct CiDetails {
+    /// The file names of the workflows in `.github/workflows`.
+    pub workflows: BTreeSet<String>,
+
+    /// The workflows we could not parse, with the error message.
+    pub invalid_workflows: BTreeSet<String>,
+
+    /// The events triggering the workflows, e.g. `push`, `pull_request`, `schedule`.
+    pub triggers: BTreeSet<String>,
+
+    pub runs_tests: bool,
+    pub runs_clippy: bool,
+    pub runs_fmt_check: bool,
+    pub runs_doc: bool,
+    pub runs_miri: bool,
+    pub runs_coverage: bool,
+
+    /// The Rust toolchains used: `stable`, `beta`, `nightly`, a version number such as `1.70`
+    /// or `msrv` if the version comes from a variable we cannot resolve.
+    pub toolchains: BTreeSet<String>,
+
+    /// The runners, e.g. `ubuntu-latest`, including the entries of the matrix.
+    pub operating_systems: BTreeSet<String>,
+
+    /// Actions referenced by a full commit SHA, e.g. `actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683`.
+    pub pinned_actions: BTreeSet<String>,
+
+    /// Actions referenced by a branch or a tag, e.g. `actions/checkout@v4`.
+    pub floating_actions: BTreeSet<String>,
+
+    /// Risky patterns in the workflows.
+    #[serde(default = "crate::no_entries")]
+    pub findings: Vec<CiFinding>,
+}
+
+/// A risky pattern in a workflow.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CiFinding {
+    /// The file name of the workflow.
+    pub workflow: String,
+
+    pub risk: CiRisk,
+
+    /// Where we found it, e.g. the name of the action or the line of the `run` step.
+    pub details: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum CiRisk {
+    /// A `pull_request_target` workflow checks out the code of the pull request and runs it with access to the secrets.
+    PullRequestTargetCheckout,
+
+    /// An action referenced by a branch, the owner of the action can change the code we run at any time.
+    ActionByBranch,
+
+    /// Downloading a script and running it, e.g. `curl ... | sh`.
+    CurlPipeShell,
+
+    /// Printing a secret in a `run` step.
+    SecretInRun,
+
+    /// `permissions: write-all` for the workflow or a job.
+    WriteAllPermissions,
+}
+
+impl CiRisk {
+    /// The same as the serialized value.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::PullRequestTargetCheckout => "pull-request-target-checkout",
+            Self::ActionByBranch => "action-by-branch",
+            Self::CurlPipeShell => "curl-pipe-shell",
+            Self::SecretInRun => "secret-in-run",
+            Self::WriteAllPermissions => "write-all-permissions",
+        }
+    }
+}
+
+impl CiDetails {
+    pub const fn new() -> Self {
+        Self {
+            workflows: BTreeSet::new(),
+            invalid_workflows: BTreeSet::new(),
+            triggers: BTreeSet::new(),
+            runs_tests: false,
+            runs_clippy: false,
+            runs_fmt_check: false,
+            runs_doc: false,
+            runs_miri: false,
+            runs_coverage: false,
+            toolchains: BTreeSet::new(),
+            operating_systems: BTreeSet::new(),
+            pinned_actions: BTreeSet::new(),
+            floating_actions: BTreeSet::new(),
+            findings: vec![],
+        }
+    }
+
+    /// At least one of the toolchains is a specific Rust version, usually the MSRV.
+    pub fn tests_msrv(&self) -> bool {
+        self.toolchains
+            .iter()
+            .any(|toolchain| toolchain == "msrv" || is_rust_version(toolchain))
+    }
+
+    /// At least one of the runners is Windows.
+    pub fn runs_on_windows(&self) -> bool {
+        self.operating_systems
+            .iter()
+            .any(|os| os.to_lowercase().starts_with("windows"))
+    }
+
+    /// At least one of the runners is macOS.
+    pub fn runs_on_macos(&self) -> bool {
+        self.operating_systems
+            .iter()
+            .any(|os| os.to_lowercase().starts_with("macos"))
+    }
+
+    /// The kinds of the risky patterns found, each one once.
+    pub fn risks(&self) -> BTreeSet<CiRisk> {
+        self.findings.iter().map(|finding| finding.risk).collect()
+    }
+
+    /// Parse a workflow file and add what it does to the details.
+    fn add_workflow(&mut self, name: &str, content: &str) -> Result<(), serde_yaml::Error> {
+        let workflow: Value = serde_yaml::from_str(content)?;
+
+        self.triggers.extend(triggers(workflow.get("on")));
+        self.findings.extend(find_risks(name, &workflow));
+
+        if let Some(jobs) = workflow.get("jobs").and_then(Value::as_mapping) {
+            for job in jobs.values() {
+                self.add_job(job);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_job(&mut self, job: &Value) {
+        let matrix = job
+            .get("strategy")
+            .and_then(|strategy| strategy.get("matrix"))
+            .and_then(Value::as_mapping);
+
+        let runners = job.get("runs-on").map_or_else(Vec::new, scalars);
+        for runner in runners {
+            self.operating_systems.extend(resolve(&runner, matrix));
+        }
+
+        // A job calling a reusable workflow.
+        if let Some(uses) = job.get("uses").and_then(Value::as_str) {
+            self.add_action(uses);
+        }
+
+        // Toolchains listed in the matrix are used even if the steps refer to them with another name.
+        if let Some(entries) = matrix {
+            for key in TOOLCHAIN_MATRIX_KEYS {
+                for value in matrix_values(entries, key) {
+                    self.add_toolchain(&value);
+                }
+            }
+        }
+
+        let Some(steps) = job.get("steps").and_then(Value::as_sequence) else {
+            return;
+        };
+        for step in steps {
+            self.add_step(step, matrix);
+        }
+    }
+
+    fn add_step(&mut self, step: &Value, matrix: Option<&Mapping>) {
+        let mut commands = String::new();
+
+        if let Some(run) = step.get("run").and_then(Value::as_str) {
+            commands.push_str(run);
+            commands.push('\n');
+        }
+
+        let with = step.get("with").and_then(Value::as_mapping);
+        if let Some(uses) = step.get("uses").and_then(Value::as_str) {
+            self.add_action(uses);
+            self.add_uses(uses);
+
+            // e.g. actions-rs/cargo with `command: test`
+            if let Some(command) = with
+                .and_then(|inputs| inputs.get("command"))
+                .and_then(Value::as_str)
+            {
+                let args = with
+                    .and_then(|inputs| inputs.get("args"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                commands.push_str("cargo ");
+                commands.push_str(command);
+                commands.push(' ');
+                commands.push_str(args);
+                commands.push('\n');
+            }
+        }
+
+        if let Some(toolchain) = with
+            .and_then(|inputs| inputs.get("toolchain"))
+            .and_then(scalar)
+        {
+            for value in resolve(&toolchain, matrix) {
+                self.add_toolchain(&value);
+            }
+        }
+
+        self.add_commands(&commands, matrix);
+    }
+
+    /// Detect the tools used in the `run` steps.
+    fn add_commands(&mut self, commands: &str, matrix: Option<&Mapping>) {
+        static TEST: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"\bcargo(?:\s+\+\S+)?(?:\s+hack)?\s+(?:test|nextest)\b").unwrap()
+        });
+        static CLIPPY: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\bcargo(?:\s+\+\S+)?(?:\s+hack)?\s+clippy\b").unwrap());
+        static FMT_CHECK: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\bcargo(?:\s+\+\S+)?\s+fmt\b.*--check\b").unwrap());
+        static DOC: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"\bcargo(?:\s+\+\S+)?(?:\s+hack)?\s+(?:doc|rustdoc)\b").unwrap()
+        });
+        static MIRI: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\bcargo(?:\s+\+\S+)?\s+miri\b").unwrap());
+        static COVERAGE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"\b(?:cargo[\s-]+)?(?:tarpaulin|llvm-cov|grcov)\b").unwrap()
+        });
+        static TOOLCHAIN: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?:\bcargo\s+\+|\brustup\s+(?:toolchain\s+install|install|default|override\s+set)\s+)(\S+)").unwrap()
+        });
+
+        self.runs_tests |= TEST.is_match(commands);
+        self.runs_clippy |= CLIPPY.is_match(commands);
+        self.runs_fmt_check |= FMT_CHECK.is_match(commands);
+        self.runs_doc |= DOC.is_match(commands);
+        self.runs_miri |= MIRI.is_match(commands);
+        self.runs_coverage |= COVERAGE.is_match(commands);
+
+        let toolchains = TOOLCHAIN
+            .captures_iter(commands)
+            .filter_map(|captures| captures.get(1))
+            .map(|toolchain| toolchain.as_str().to_owned())
+            .collect::<Vec<String>>();
+        for toolchain in toolchains {
+            for value in resolve(&toolchain, matrix) {
+                self.add_toolchain(&value);
+            }
+        }
+    }
+
+    /// Detect the tools used via actions.
+    fn add_uses(&mut self, uses: &str) {
+        let (name, reference) = uses.split_once('@').unwrap_or((uses, ""));
+        let action = name.to_lowercase();
+
+        if action.ends_with("clippy-check") || action.ends_with("/clippy") {
+            self.runs_clippy = true;
+        }
+        if action.ends_with("/rustfmt") || action.ends_with("rustfmt-check") {
+            self.runs_fmt_check = true;
+        }
+        if action.starts_with("codecov/")
+            || action.starts_with("coverallsapp/")
+            || action.ends_with("/tarpaulin")
+            || action.ends_with("grcov")
+        {
+            self.runs_coverage = true;
+        }
+
+        // dtolnay/rust-toolchain@nightly uses the ref as the name of the toolchain
+        if action == "dtolnay/rust-toolchain" && !is_commit_sha(reference) {
+            let toolchain = reference.to_owned();
+            self.add_toolchain(&toolchain);
+        }
+    }
+
+    fn add_action(&mut self, uses: &str) {
+        if uses.starts_with("./") || uses.starts_with("docker://") {
+            return;
+        }
+        let pinned = uses
+            .split_once('@')
+            .is_some_and(|(_, reference)| is_commit_sha(reference));
+        if pinned {
+            self.pinned_actions.insert(uses.to_owned());
+        } else {
+            self.floating_actions.insert(uses.to_owned());
+        }
+    }
+
+    fn add_toolchain(&mut self, toolchain: &str) {
+        if let Some(name) = normalize_toolchain(toolchain) {
+            self.toolchains.insert(name);
+        }
+    }
+}
+
+impl Default for CiDetails {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the GitHub Actions workflows of the repository.
+pub fn github_workflows(repo_path: &Path) -> CiDetails {
+    let mut details = CiDetails::new();
+
+    let Ok(entries) = repo_path.join(GITHUB_WORKFLOWS).read_dir() else {
+        return details;
+    };
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            !path.is_dir()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        log::info!("workflow: {name}");
+        let result = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                details
+                    .add_workflow(&name, &content)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            log::warn!("Could not parse workflow {name}: {err}");
+            details.invalid_workflows.insert(format!("{name}: {err}"));
+        }
+        details.workflows.insert(name);
+    }
+
+    details
+}
+
+/// The events triggering a workflow, `on` can be a single event, a list or a mapping with the settings of each event.
+fn triggers(on: Option<&Value>) -> Vec<String> {
+    let Some(events) = on else {
+        return vec![];
+    };
+    if let Some(sequence) = events.as_sequence() {
+        return sequence
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect();
+    }
+    if let Some(mapping) = events.as_mapping() {
+        return mapping
+            .keys()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect();
+    }
+    events.as_str().map(str::to_owned).into_iter().collect()
+}
+
+/// Look for risky patterns in a workflow. Everything is based on the content of the file, we don't
+/// check the referenced actions.
+fn find_risks(workflow_name: &str, workflow: &Value) -> Vec<CiFinding> {
+    let mut findings = vec![];
+    let mut add = |risk: CiRisk, details: &str| {
+        findings.push(CiFinding {
+            workflow: workflow_name.to_owned(),
+            risk,
+            details: details.to_owned(),
+        });
+    };
+
+    let pull_request_target = triggers(workflow.get("on"))
+        .iter()
+        .any(|event| event == "pull_request_target");
+
+    if is_write_all(workflow.get("permissions")) {
+        add(CiRisk::WriteAllPermissions, "workflow");
+    }
+
+    let Some(jobs) = workflow.get("jobs").and_then(Value::as_mapping) else {
+        return findings;
+    };
+    for (key, job) in jobs {
+        let job_name = key.as_str().unwrap_or_default();
+        if is_write_all(job.get("permissions")) {
+            add(CiRisk::WriteAllPermissions, &format!("job {job_name}"));
+        }
+        if let Some(uses) = job.get("uses").and_then(Value::as_str) {
+            if is_referenced_by_branch(uses) {
+                add(CiRisk::ActionByBranch, uses);
+            }
+        }
+
+        let steps = job
+            .get("steps")
+            .and_then(Value::as_sequence)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for step in steps {
+            if let Some(uses) = step.get("uses").and_then(Value::as_str) {
+                if is_referenced_by_branch(uses) {
+                    add(CiRisk::ActionByBranch, uses);
+                }
+                let checkout_ref = step
+                    .get("with")
+                    .and_then(|inputs| inputs.get("ref"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                if pull_request_target
+                    && uses.starts_with("actions/checkout@")
+                    && refers_to_pull_request_head(checkout_ref)
+                {
+                    add(
+                        CiRisk::PullRequestTargetCheckout,
+                        &format!("job {job_name}: {uses} with ref {checkout_ref}"),
+                    );
+                }
+            }
+
+            let run = step.get("run").and_then(Value::as_str).unwrap_or_default();
+            for line in run.lines().map(str::trim) {
+                if pull_request_target
+                    && (line.contains("gh pr checkout")
+                        || line.starts_with("git ") && refers_to_pull_request_head(line))
+                {
+                    add(
+                        CiRisk::PullRequestTargetCheckout,
+                        &format!("job {job_name}: {line}"),
+                    );
+                }
+                if is_curl_pipe_shell(line) {
+                    add(CiRisk::CurlPipeShell, line);
+                }
+                if is_secret_printed(line) {
+                    add(CiRisk::SecretInRun, line);
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+fn is_write_all(permissions: Option<&Value>) -> bool {
+    permissions
+        .and_then(Value::as_str)
+        .is_some_and(|permission| permission == "write-all")
+}
+
+fn refers_to_pull_request_head(text: &str) -> bool {
+    text.contains("github.event.pull_request.head")
+        || text.contains("github.head_ref")
+        || text.contains("refs/pull/")
+}
+
+/// A branch is anything that is neither a commit SHA nor looks like a version tag such as `v4` or `1.2.3`.
+fn is_referenced_by_branch(uses: &str) -> bool {
+    static VERSION_TAG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^v?\d+(?:\.\d+)*(?:[-+.][0-9A-Za-z.-]+)?$").unwrap());
+
+    if uses.starts_with("./") || uses.starts_with("docker://") {
+        return false;
+    }
+    uses.split_once('@')
+        .is_some_and(|(_, reference)| !is_commit_sha(reference) && !VERSION_TAG.is_match(reference))
+}
+
+fn is_curl_pipe_shell(line: &str) -> bool {
+    static CURL_PIPE_SHELL: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"\b(?:curl|wget)\b[^|]*\|\s*(?:sudo\s+)?(?:-E\s+)?(?:ba|z|da)?sh\b").unwrap()
+    });
+    CURL_PIPE_SHELL.is_match(line)
+}
+
+fn is_secret_printed(line: &str) -> bool {
+    static SECRET_PRINTED: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\b(?:echo|printf)\b.*\$\{\{\s*secrets\.").unwrap());
+    SECRET_PRINTED.is_match(line)
+}
+
+/// Strings and numbers, YAML reads an unquoted toolchain such as `1.70` as the float `1.7`.
+/// The zero is lost, so we pad a single digit minor version back: Rust 1.1 to 1.9 predate GitHub Actions.
+fn scalar(value: &Value) -> Option<String> {
+    if let Some(text) = value.as_str() {
+        return Some(text.to_owned());
+    }
+    let number = value.as_f64()?.to_string();
+    match number.split_once('.') {
+        Some((_, minor)) if minor.len() == 1 => Some(format!("{number}0")),
+        _ => Some(number),
+    }
+}
+
+/// A single value or a list of values.
+fn scalars(value: &Value) -> Vec<String> {
+    value.as_sequence().map_or_else(
+        || scalar(value).into_iter().collect(),
+        |items| items.iter().filter_map(scalar).collect(),
+    )
+}
+
+/// The values of a matrix key including the ones added with `include`.
+fn matrix_values(matrix: &Mapping, key: &str) -> Vec<String> {
+    let mut values = matrix.get(key).map_or_else(Vec::new, scalars);
+    if let Some(includes) = matrix.get("include").and_then(Value::as_sequence) {
+        values.extend(
+            includes
+                .iter()
+                .filter_map(|include| include.get(key))
+                .filter_map(scalar),
+        );
+    }
+    values
+}
+
+/// Replace a `${{ matrix.name }}` expression by the values in the matrix.
+/// We cannot resolve other expressions, but one mentioning the MSRV is good enough to know it is tested.
+fn resolve(value: &str, matrix: Option<&Mapping>) -> Vec<String> {
+    static MATRIX_EXPRESSION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\$\{\{\s*matrix\.([A-Za-z0-9_-]+)\s*\}\}$").unwrap());
+
+    if !value.contains("${{") {
+        return vec![value.to_owned()];
+    }
+    if let Some(captures) = MATRIX_EXPRESSION.captures(value.trim()) {
+        if let (Some(entries), Some(key)) = (matrix, captures.get(1)) {
+            return matrix_values(entries, key.as_str());
+        }
+    }
+    if value.to_lowercase().contains("msrv") {
+        return vec![String::from("msrv")];
+    }
+    vec![]
+}
+
+/// `stable`, `beta`, `nightly` and versions, dropping dates and targets, e.g. `nightly-2024-01-01` => `nightly`.
+fn normalize_toolchain(text: &str) -> Option<String> {
+    let toolchain = text.trim().trim_matches(['"', '\'']).to_lowercase();
+    for channel in ["stable", "beta", "nightly"] {
+        if toolchain.starts_with(channel) {
+            return Some(channel.to_owned());
+        }
+    }
+    if toolchain == "msrv" || is_rust_version(&toolchain) {
+        return Some(toolchain);
+    }
+    None
+}
+
+fn is_rust_version(text: &str) -> bool {
+    static RUST_VERSION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^1\.\d+(?:\.\d+)?$").unwrap());
+    RUST_VERSION.is_match(text)
+}
+
+fn is_commit_sha(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|chr| chr.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_add_workflow() {
+        let workflow = "
+name: CI
+on:
+  push:
+  pull_request:
+  schedule:
+    - cron: '0 0 * * 0'
+jobs:
+  test:
+    strategy:
+      matrix:
+        os: [ubuntu-latest, windows-latest]
+        rust: [stable, 1.70.0]
+        include:
+          - os: macos-latest
+            rust: nightly
+    runs-on: ${{ matrix.os }}
+    steps:
+      - uses: actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683
+      - uses: dtolnay/rust-toolchain@master
+        with:
+          toolchain: ${{ matrix.rust }}
+      - run: cargo test --all-features
+      - run: cargo +nightly miri test
+  lint:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: dtolnay/rust-toolchain@beta
+      - run: |
+          cargo fmt --all -- --check
+          cargo clippy -- -D warnings
+      - uses: taiki-e/install-action@cargo-llvm-cov
+      - run: cargo llvm-cov --lcov --output-path lcov.info
+";
+        let mut details = CiDetails::new();
+        details.add_workflow("ci.yml", workflow).unwrap();
+
+        let set = |items: &[&str]| {
+            items
+                .iter()
+                .map(|item| (*item).to_owned())
+                .collect::<BTreeSet<String>>()
+        };
+        assert_eq!(details.triggers, set(&["pull_request", "push", "schedule"]));
+        assert!(details.runs_tests);
+        assert!(details.runs_clippy);
+        assert!(details.runs_fmt_check);
+        assert!(!details.runs_doc);
+        assert!(details.runs_miri);
+        assert!(details.runs_coverage);
+        assert_eq!(
+            details.toolchains,
+            set(&["1.70.0", "beta", "nightly", "stable"])
+        );
+        assert!(details.tests_msrv());
+        assert_eq!(
+            details.operating_systems,
+            set(&["macos-latest", "ubuntu-latest", "windows-latest"])
+        );
+        assert!(details.runs_on_windows());
+        assert!(details.runs_on_macos());
+        assert_eq!(
+            details.pinned_actions,
+            set(&["actions/checkout@11bd71901bbe5b1630ceea73d27597364c9af683"])
+        );
+        assert_eq!(
+            details.floating_actions,
+            set(&[
+                "actions/checkout@v4",
+                "dtolnay/rust-toolchain@beta",
+                "dtolnay/rust-toolchain@master",
+                "taiki-e/install-action@cargo-llvm-cov",
+            ])
+        );
+    }
+
+    #[test]
+    fn check_add_triggers() {
+        let mut details = CiDetails::new();
+        details
+            .add_workflow("a.yml", "on: push\njobs: {}\n")
+            .unwrap();
+        details
+            .add_workflow("b.yml", "on: [pull_request, workflow_dispatch]\n")
+            .unwrap();
+        assert_eq!(details.triggers.len(), 3);
+        assert!(details.add_workflow("c.yml", "on: [push").is_err());
+    }
+
+    #[test]
+    fn check_find_risks() {
+        let workflow = "
+on: pull_request_target
+permissions: write-all
+jobs:
+  build:
+    permissions: write-all
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          ref: ${{ github.event.pull_request.head.sha }}
+      - uses: dtolnay/rust-toolchain@stable
+      - uses: Swatinem/rust-cache@v2.7.3
+      - uses: ./local-action
+      - run: |
+          curl --proto '=https' -sSf https://sh.rustup.rs | sh -s -- -y
+          echo \"token: ${{ secrets.TOKEN }}\"
+          cargo test
+        env:
+          TOKEN: ${{ secrets.TOKEN }}
+";
+        let findings = find_risks("ci.yml", &serde_yaml::from_str(workflow).unwrap())
+            .into_iter()
+            .map(|finding| (finding.risk, finding.details))
+            .collect::<Vec<(CiRisk, String)>>();
+        assert_eq!(
+            findings,
+            vec![
+                (CiRisk::WriteAllPermissions, String::from("workflow")),
+                (CiRisk::WriteAllPermissions, String::from("job build")),
+                (
+                    CiRisk::PullRequestTargetCheckout,
+                    String::from(
+                        "job build: actions/checkout@v4 with ref ${{ github.event.pull_request.head.sha }}"
+                    )
+                ),
+                (
+                    CiRisk::ActionByBranch,
+                    String::from("dtolnay/rust-toolchain@stable")
+                ),
+                (
+                    CiRisk::CurlPipeShell,
+                    String::from("curl --proto '=https' -sSf https://sh.rustup.rs | sh -s -- -y")
+                ),
+                (
+                    CiRisk::SecretInRun,
+                    String::from("echo \"token: ${{ secrets.TOKEN }}\"")
+                ),
+            ]
+        );
+
+        assert!(find_risks(
+            "ci.yml",
+            &serde_yaml::from_str("on: push\njobs: {}\n").unwrap()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn check_risk_name() {
+        for risk in [
+            CiRisk::PullRequestTargetCheckout,
+            CiRisk::ActionByBranch,
+            CiRisk::CurlPipeShell,
+            CiRisk::SecretInRun,
+            CiRisk::WriteAllPermissions,
+        ] {
+            assert_eq!(
+                serde_json::to_string(&risk).unwrap(),
+                format!("\"{}\"", risk.name())
+            );
+        }
+    }
+
+    #[test]
+    fn check_unquoted_toolchain_versions() {
+        let workflow = "
+on: push
+jobs:
+  test:
+    strategy:
+      matrix:
+        rust: [1.70, 1.80, 1.65]
+    steps:
+      - uses: dtolnay/rust-toolchain@master
+        with:
+          toolchain: ${{ matrix.rust }}
+  msrv:
+    steps:
+      - uses: dtolnay/rust-toolchain@master
+        with:
+          toolchain: 1.70
+";
+        let mut details = CiDetails::new();
+        details.add_workflow("ci.yml", workflow).unwrap();
+        assert_eq!(
+            details.toolchains,
+            ["1.65", "1.70", "1.80"]
+                .iter()
+                .map(|item| (*item).to_owned())
+                .collect::<BTreeSet<String>>()
+        );
+    }
+
+    #[test]
+    fn check_normalize_toolchain() {
+        assert_eq!(
+            normalize_toolchain("nightly-2024-01-01"),
+            Some(String::from("nightly"))
+        );
+        assert_eq!(normalize_toolchain("'1.70'"), Some(String::from("1.70")));
+        assert_eq!(
+            normalize_toolchain("stable-x86_64-pc-windows-gnu"),
+            Some(String::from("stable"))
+        );
+        assert_eq!(normalize_toolchain("master"), None);
+    }
+}
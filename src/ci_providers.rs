@@ -0,0 +1,284 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+/// The kinds of `include` in `.gitlab-ci.yml`, see <https://docs.gitlab.com/ee/ci/yaml/#include>.
+const GITLAB_INCLUDE_KINDS: [&str; 5] = ["local", "project", "remote", "template", "component"];
+
+/// A CI system and the files configuring it as listed in `ci_providers.yaml`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CiProvider {
+    /// The name we store in `VCSDetails::ci_providers`, also used in the URLs, e.g. `github-actions`.
+    pub name: String,
+
+    pub display: String,
+
+    /// Paths relative to the root of the repository. A path ending with `/` is a directory,
+    /// a `*` in the last part of the path matches any file name, e.g. `.github/workflows/*.yml`.
+    pub paths: Vec<String>,
+}
+
+/// The boolean fields we had in `VCSDetails` before we had `ci_providers`.
+/// We only read them from the old JSON files and convert them with `providers`.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct LegacyCiFlags {
+    #[serde(default)]
+    has_github_action: bool,
+    #[serde(default)]
+    has_gitlab_pipeline: bool,
+    #[serde(default)]
+    has_circle_ci: bool,
+    #[serde(default)]
+    has_cirrus_ci: bool,
+    #[serde(default)]
+    has_travis_ci: bool,
+    #[serde(default)]
+    has_jenkins: bool,
+    #[serde(default)]
+    has_appveyor: bool,
+    #[serde(default)]
+    has_azure_pipeline: bool,
+    #[serde(default)]
+    has_bitbucket_pipeline: bool,
+}
+
+impl LegacyCiFlags {
+    pub const fn new() -> Self {
+        Self {
+            has_github_action: false,
+            has_gitlab_pipeline: false,
+            has_circle_ci: false,
+            has_cirrus_ci: false,
+            has_travis_ci: false,
+            has_jenkins: false,
+            has_appveyor: false,
+            has_azure_pipeline: false,
+            has_bitbucket_pipeline: false,
+        }
+    }
+
+    /// The names of the providers in `ci_providers.yaml` matching the flags that are set.
+    pub fn providers(&self) -> Vec<String> {
+        [
+            (self.has_github_action, "github-actions"),
+            (self.has_gitlab_pipeline, "gitlab-pipeline"),
+            (self.has_circle_ci, "circle-ci"),
+            (self.has_cirrus_ci, "cirrus-ci"),
+            (self.has_travis_ci, "travis-ci"),
+            (self.has_jenkins, "jenkins"),
+            (self.has_appveyor, "appveyor"),
+            (self.has_azure_pipeline, "azure-pipelines"),
+            (self.has_bitbucket_pipeline, "bitbucket-pipelines"),
+        ]
+        .into_iter()
+        .filter(|flag| flag.0)
+        .map(|flag| flag.1.to_owned())
+        .collect()
+    }
+}
+
+/// The CI systems we know about.
+pub fn ci_providers() -> Vec<CiProvider> {
+    let text = include_str!("../ci_providers.yaml");
+
+    let providers: Vec<CiProvider> = serde_yaml::from_str(text).unwrap();
+    providers
+}
+
+/// The names of the CI systems configured in the repository.
+pub fn detect_ci_providers(repo_path: &Path) -> Vec<String> {
+    ci_providers()
+        .into_iter()
+        .filter(|provider| {
+            provider
+                .paths
+                .iter()
+                .any(|path| path_exists(repo_path, path))
+        })
+        .map(|provider| provider.name)
+        .collect()
+}
+
+fn path_exists(repo_path: &Path, path: &str) -> bool {
+    if let Some(dir) = path.strip_suffix('/') {
+        return repo_path.join(dir).is_dir();
+    }
+
+    let (dir, file_pattern) = path.rsplit_once('/').unwrap_or(("", path));
+    if !file_pattern.contains('*') {
+        return repo_path.join(path).is_file();
+    }
+
+    let Ok(entries) = repo_path.join(dir).read_dir() else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        !entry.path().is_dir() && wildcard_match(file_pattern, &entry.file_name().to_string_lossy())
+    })
+}
+
+/// The files and templates included by the `.gitlab-ci.yml` file of the repository, e.g. `local: ci/test.yml`.
+pub fn gitlab_ci_includes(repo_path: &Path) -> Vec<String> {
+    let Some(content) = [".gitlab-ci.yml", ".gitlab-ci.yaml"]
+        .iter()
+        .find_map(|name| fs::read_to_string(repo_path.join(name)).ok())
+    else {
+        return vec![];
+    };
+    match serde_yaml::from_str::<Value>(&content) {
+        Ok(pipeline) => pipeline
+            .get("include")
+            .map(parse_gitlab_include)
+            .unwrap_or_default(),
+        Err(err) => {
+            log::warn!("Could not parse .gitlab-ci.yml: {err}");
+            vec![]
+        }
+    }
+}
+
+/// `include` is a single entry or a list of entries. An entry is either a path (local or a URL)
+/// or a mapping with one of `GITLAB_INCLUDE_KINDS`, a `project` lists its files in `file`.
+fn parse_gitlab_include(include: &Value) -> Vec<String> {
+    if let Some(entries) = include.as_sequence() {
+        return entries.iter().flat_map(parse_gitlab_include).collect();
+    }
+    if let Some(path) = include.as_str() {
+        let kind = if path.starts_with("https://") || path.starts_with("http://") {
+            "remote"
+        } else {
+            "local"
+        };
+        return vec![format!("{kind}: {path}")];
+    }
+
+    let Some(kind) = GITLAB_INCLUDE_KINDS
+        .iter()
+        .find(|kind| include.get(**kind).is_some())
+    else {
+        return vec![];
+    };
+    let target = include
+        .get(*kind)
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if *kind != "project" {
+        return vec![format!("{kind}: {target}")];
+    }
+    let files = include.get("file").map_or_else(Vec::new, |file| {
+        file.as_sequence().map_or_else(
+            || file.as_str().into_iter().collect(),
+            |names| names.iter().filter_map(Value::as_str).collect(),
+        )
+    });
+    files
+        .into_iter()
+        .map(|file| format!("project: {target} {file}"))
+        .collect()
+}
+
+/// Match a file name against a pattern where `*` matches any number of characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*').collect::<Vec<&str>>();
+    let first = parts.remove(0);
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let Some(last) = parts.pop() else {
+        // There was no `*` in the pattern.
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = rest.get(index + part.len()..).unwrap_or_default(),
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_ci_providers() {
+        let providers = ci_providers();
+        let mut names = providers
+            .iter()
+            .map(|provider| provider.name.as_str())
+            .collect::<Vec<&str>>();
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), count, "provider names must be unique");
+        assert!(providers.iter().all(|provider| !provider.paths.is_empty()));
+
+        // The legacy flags must map to providers in the registry.
+        let legacy: LegacyCiFlags = serde_json::from_str(
+            r#"{"has_github_action": true, "has_gitlab_pipeline": true, "has_circle_ci": true, "has_cirrus_ci": true,
+            "has_travis_ci": true, "has_jenkins": true, "has_appveyor": true, "has_azure_pipeline": true, "has_bitbucket_pipeline": true}"#,
+        )
+        .unwrap();
+        for name in legacy.providers() {
+            assert!(
+                names.contains(&name.as_str()),
+                "{name} is not in the registry"
+            );
+        }
+        assert!(LegacyCiFlags::new().providers().is_empty());
+    }
+
+    #[test]
+    fn check_gitlab_ci_includes() {
+        let pipeline = "
+include:
+  - local: /ci/test.yml
+  - project: group/ci-templates
+    ref: main
+    file:
+      - /rust.yml
+      - /lint.yml
+  - template: Rust.gitlab-ci.yml
+  - remote: https://example.com/ci.yml
+  - ci/build.yml
+stages: [test]
+";
+        let tmp_dir = tempdir::TempDir::new("gitlab").unwrap();
+        assert!(gitlab_ci_includes(tmp_dir.path()).is_empty());
+        fs::write(tmp_dir.path().join(".gitlab-ci.yml"), pipeline).unwrap();
+        assert_eq!(
+            gitlab_ci_includes(tmp_dir.path()),
+            vec![
+                "local: /ci/test.yml",
+                "project: group/ci-templates /rust.yml",
+                "project: group/ci-templates /lint.yml",
+                "template: Rust.gitlab-ci.yml",
+                "remote: https://example.com/ci.yml",
+                "local: ci/build.yml",
+            ]
+        );
+
+        let single: Value = serde_yaml::from_str("https://example.com/ci.yml").unwrap();
+        assert_eq!(
+            parse_gitlab_include(&single),
+            vec!["remote: https://example.com/ci.yml"]
+        );
+    }
+
+    #[test]
+    fn check_wildcard_match() {
+        assert!(wildcard_match("*.yml", "ci.yml"));
+        assert!(!wildcard_match("*.yml", "ci.yaml"));
+        assert!(wildcard_match("*.gitlab-ci.yml", "test.gitlab-ci.yml"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("a*b*c", "axxc"));
+        assert!(wildcard_match("Jenkinsfile", "Jenkinsfile"));
+        assert!(!wildcard_match("Jenkinsfile", "Jenkinsfile.old"));
+    }
+}
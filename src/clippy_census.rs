@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use clap::Parser;
+
+use rust_digger::{
+    crates_root, download_clippy_lints, load_cargo_toml_simplified, load_clippy_lint_groups,
+    load_clippy_report, save_clippy_report, ClippyReport, ElapsedTimer, Runner,
+};
+
+/// How many lines of the error output we keep if clippy could not run.
+const ERROR_LINES: usize = 5;
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Limit the number of crates we process."
+    )]
+    limit: usize,
+}
+
+/// For each released crate we have locally
+///     skip it if we already ran clippy on the same version
+///     run `cargo clippy --message-format=json` in the container
+///     count the lints by lint group
+///     save the result.
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("Start running clippy on the released crates.");
+    let start_time = std::time::Instant::now();
+
+    if let Err(err) = run() {
+        log::error!("Error: {err}");
+    }
+
+    log::info!("Elapsed time: {} sec.", start_time.elapsed().as_secs());
+    log::info!("End running clippy");
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("clippy_census");
+
+    let args = Cli::parse();
+    log::info!("Limit: {}", args.limit);
+
+    if let Err(err) = download_clippy_lints() {
+        log::warn!("Could not download the list of clippy lints: {err}");
+    }
+    let lint_groups = load_clippy_lint_groups();
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let clippy_version = runner.version("cargo-clippy");
+
+    let mut count: usize = 0;
+    for entry in crates_root().read_dir()? {
+        if 0 < args.limit && args.limit <= count {
+            break;
+        }
+        let crate_path = entry?.path();
+
+        let (name, version) = match load_cargo_toml_simplified(&crate_path.join("Cargo.toml")) {
+            Ok(name_version) => name_version,
+            Err(err) => {
+                log::warn!(
+                    "Could not read Cargo.toml in {:?}: {err}",
+                    crate_path.display()
+                );
+                continue;
+            }
+        };
+        if load_clippy_report(&name).version == version {
+            continue;
+        }
+
+        log::info!("clippy ({count}/{}) {name} {version}", args.limit);
+        let mut report = run_clippy(&runner, &crate_path, &lint_groups);
+        report.version = version;
+        report.clippy_version.clone_from(&clippy_version);
+        save_clippy_report(&name, &report)?;
+
+        count += 1;
+    }
+
+    log::info!("Ran clippy on {count} crates");
+
+    Ok(())
+}
+
+fn run_clippy(
+    runner: &Runner,
+    crate_path: &Path,
+    lint_groups: &HashMap<String, String>,
+) -> ClippyReport {
+    let mut report = ClippyReport::new();
+    match runner.run("clippy", crate_path) {
+        Ok(result) => {
+            let succeeded = result.succeeded();
+            report.checked_at = result.started_at;
+            report.exit_code = result.exit_code;
+            report.parse_messages(&result.stdout, lint_groups);
+            if report.error.is_empty() && !succeeded {
+                report.error = result
+                    .stderr
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .take(ERROR_LINES)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
+        }
+        Err(err) => {
+            log::error!("Could not run clippy: {err}");
+            report.error = err.to_string();
+        }
+    }
+    report
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cargo_messages::compiler_messages;
+use crate::{clippy_root, get_data_folder};
+
+/// The list of the lints of clippy with their group, published with the documentation of clippy.
+pub const CLIPPY_LINTS_URL: &str = "https://rust-lang.github.io/rust-clippy/stable/lints.json";
+
+/// The group we use for the lints of the compiler, e.g. `unused_variables`.
+const RUSTC_GROUP: &str = "rustc";
+
+/// The group of the clippy lints we did not find in `lints.json`.
+const UNKNOWN_GROUP: &str = "unknown";
+
+/// How many times a lint was triggered in a crate.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LintCount {
+    /// e.g. `style`, `pedantic` or `rustc` for the lints of the compiler.
+    pub group: String,
+    pub count: usize,
+}
+
+/// The result of running `cargo clippy` on a released crate.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ClippyReport {
+    /// The version of the crate we checked, empty if we have not run clippy on the crate.
+    pub version: String,
+
+    pub checked_at: String,
+
+    /// The output of `cargo clippy --version` in the container.
+    pub clippy_version: String,
+
+    /// `None` if clippy timed out or could not be run at all.
+    pub exit_code: Option<i32>,
+
+    /// The code of each lint, e.g. `clippy::needless_return`, and how many times it was triggered.
+    pub lints: BTreeMap<String, LintCount>,
+
+    /// The number of warnings (and lints denied by default) in each lint group.
+    pub groups: BTreeMap<String, usize>,
+
+    /// The number of compiler errors, e.g. because a dependency was not available.
+    pub errors: usize,
+
+    /// The first compiler error or the first lines of the error output if clippy did not run.
+    pub error: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClippyLint {
+    id: String,
+    group: String,
+}
+
+impl ClippyReport {
+    pub const fn new() -> Self {
+        Self {
+            version: String::new(),
+            checked_at: String::new(),
+            clippy_version: String::new(),
+            exit_code: None,
+            lints: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            errors: 0,
+            error: String::new(),
+        }
+    }
+
+    /// Clippy ran and the crate compiled.
+    pub fn compiled(&self) -> bool {
+        !self.version.is_empty() && self.exit_code == Some(0)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.groups.values().sum()
+    }
+
+    /// Count the lints in the output of `cargo clippy --message-format=json`.
+    /// `lint_groups` maps the name of each clippy lint (without the `clippy::` prefix) to its group.
+    pub fn parse_messages(&mut self, stdout: &str, lint_groups: &HashMap<String, String>) {
+        for diagnostic in compiler_messages(stdout) {
+            let code = diagnostic.code().to_owned();
+            if code.is_empty() || code.starts_with('E') {
+                // e.g. "3 warnings emitted" has no code either
+                if diagnostic.is_compiler_error() {
+                    self.errors += 1;
+                    if self.error.is_empty() {
+                        self.error = diagnostic.message;
+                    }
+                }
+                continue;
+            }
+
+            let group = code.strip_prefix("clippy::").map_or(RUSTC_GROUP, |name| {
+                lint_groups.get(name).map_or(UNKNOWN_GROUP, String::as_str)
+            });
+            *self.groups.entry(group.to_owned()).or_default() += 1;
+            self.lints
+                .entry(code)
+                .or_insert_with(|| LintCount {
+                    group: group.to_owned(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+    }
+}
+
+impl Default for ClippyReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The part of the name of the lint we use in the URL, e.g. `needless_return` for `clippy::needless_return`.
+pub fn lint_slug(code: &str) -> &str {
+    code.strip_prefix("clippy::").unwrap_or(code)
+}
+
+fn clippy_lints_path() -> PathBuf {
+    get_data_folder().join("clippy-lints.json")
+}
+
+/// Download the list of clippy lints unless we already have it.
+pub fn download_clippy_lints() -> Result<(), Box<dyn Error>> {
+    let filepath = clippy_lints_path();
+    if filepath.exists() {
+        return Ok(());
+    }
+    log::info!("Downloading {CLIPPY_LINTS_URL}");
+    let content = reqwest::blocking::get(CLIPPY_LINTS_URL)?
+        .error_for_status()?
+        .text()?;
+    fs::write(filepath, content)?;
+    Ok(())
+}
+
+/// The name of each clippy lint and its group, e.g. `needless_return` => `style`.
+pub fn load_clippy_lint_groups() -> HashMap<String, String> {
+    let filepath = clippy_lints_path();
+    let lints = fs::read_to_string(&filepath)
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<Vec<ClippyLint>>(&content).map_err(|err| err.to_string())
+        })
+        .unwrap_or_else(|err| {
+            log::warn!("Could not load {:?}: {err}", filepath.display());
+            vec![]
+        });
+    lints
+        .into_iter()
+        .map(|lint| (lint.id, lint.group))
+        .collect()
+}
+
+fn clippy_report_path(name: &str) -> PathBuf {
+    clippy_root().join(format!("{name}.json"))
+}
+
+pub fn load_clippy_report(name: &str) -> ClippyReport {
+    let filepath = clippy_report_path(name);
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return ClippyReport::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        ClippyReport::new()
+    })
+}
+
+pub fn save_clippy_report(name: &str, report: &ClippyReport) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(clippy_root())?;
+    let content = serde_json::to_string(report)?;
+    fs::write(clippy_report_path(name), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_messages() {
+        let stdout = [
+            r#"{"reason":"compiler-artifact","package_id":"foo 0.1.0"}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return","explanation":null}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"unneeded `return` statement","level":"warning","code":{"code":"clippy::needless_return","explanation":null}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"this comparison involving the minimum or maximum element","level":"error","code":{"code":"clippy::absurd_extreme_comparisons","explanation":null}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"unused variable: `x`","level":"warning","code":{"code":"unused_variables","explanation":null}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"mismatched types","level":"error","code":{"code":"E0308","explanation":"..."}}}"#,
+            r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"4 warnings emitted","level":"warning","code":null}}"#,
+            "Checking foo v0.1.0",
+        ]
+        .join("\n");
+        let lint_groups = HashMap::from([
+            (String::from("needless_return"), String::from("style")),
+            (
+                String::from("absurd_extreme_comparisons"),
+                String::from("correctness"),
+            ),
+        ]);
+
+        let mut report = ClippyReport::new();
+        report.parse_messages(&stdout, &lint_groups);
+        assert_eq!(
+            report.groups.into_iter().collect::<Vec<_>>(),
+            vec![
+                (String::from("correctness"), 1),
+                (String::from("rustc"), 1),
+                (String::from("style"), 2),
+            ]
+        );
+        assert_eq!(
+            report.lints["clippy::needless_return"],
+            LintCount {
+                group: String::from("style"),
+                count: 2
+            }
+        );
+        assert_eq!(report.lints.len(), 3);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.error, "mismatched types");
+    }
+
+    #[test]
+    fn check_lint_slug() {
+        assert_eq!(lint_slug("clippy::needless_return"), "needless_return");
+        assert_eq!(lint_slug("unused_variables"), "unused_variables");
+    }
+}
@@ -1,13 +1,37 @@
-use std::collections::HashMap;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration as StdDuration;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
+use std::fs;
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
 
 use clap::Parser;
+use walkdir::WalkDir;
 
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 
 use git_digger::Repository;
 
-use rust_digger::{get_repos_folder, load_vcs_details, read_crates, Crate, ElapsedTimer};
+use rust_digger::{
+    canonical_repository_url, disk_usage, get_repos_folder, git_lines, load_repository_redirects,
+    load_vcs_details, parse_retry_after, read_crates, resolve_repository_url, save_details,
+    save_disk_usage, save_repository_redirects, CloneFailure, CloneMode, Crate, DiskUsage,
+    ElapsedTimer,
+};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How long we wait for the web page of the repository.
+const CHECK_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+
+/// Bytes in a gigabyte, the unit of the disk budget.
+const GB: u64 = 1024 * 1024 * 1024;
+
+const POLL_INTERVAL: StdDuration = StdDuration::from_millis(200);
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -32,29 +56,76 @@ struct Cli {
     #[arg(
         long,
         default_value_t = false,
-        help = "Try to clone even if it already failed once."
+        help = "Try to clone even if it already failed and it is not time yet to try again."
     )]
     force: bool,
+
+    #[arg(
+        long,
+        default_value_t = 4,
+        help = "The number of repositories we clone or pull in parallel."
+    )]
+    jobs: usize,
+
+    #[arg(
+        long,
+        default_value_t = 12,
+        help = "Skip the repositories we tried to update in the last `min-hours` hours, e.g. in an interrupted run."
+    )]
+    min_hours: u32,
+
+    #[arg(
+        long,
+        default_value_t = 1800,
+        help = "Stop git clone or git pull after this many seconds."
+    )]
+    timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "blobless",
+        help = "How much of the repositories we clone: full, blobless (all the commits, files only when needed) or shallow (only the last commit)."
+    )]
+    mode: CloneMode,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Remove the least recently used clones of inactive crates if the clones take more than this many GB. 0 means no limit."
+    )]
+    disk_budget: u64,
+
+    #[arg(
+        long,
+        default_value_t = 365,
+        help = "Crates without a release in this many days are inactive, only their clones are removed to stay within the disk budget."
+    )]
+    inactive_days: u32,
 }
 
-/// for each crate
+/// for each crate, the most recently released first
 ///     get the url and type of the VCS
 ///     load the details of vcs
 ///
+///     if we tried to update the repository in the last few hours (e.g. in an interrupted run) then go to next
+///
+///     if the last attempt failed and according to the kind of the failure it is not time yet to try again then go to next
+///
+///     check the web page of the repository, if it was moved use the new URL
+///
+///     if we removed the clone to save space and there was no release since then go to next
+///
 ///     if there is no clone yet:
-///         if we have evidence that the cloning has already failed then got to next
-///         else try to clone
-///             if failed
-///                 save in the details
-///             else
-///                 collect data from repo and save that in the details
+///         try to clone in the selected mode (full, blobless or shallow)
 ///
 ///     if there is already a clone
-///         if the crate was released recently then run git pull
-///             if there are new commits
-///                  collect data from repo and save that in the details
+///         run git pull
+///
+///     save the result of the attempt and the kind of the failure in the `clone_state` of the details
 ///
-///     (if the data collection takes too long we might need to separate it from the cloning)
+/// The repositories are cloned and pulled by a pool of `jobs` worker threads.
+/// At the end we remove old clones if they take more than the disk budget and report the space they use.
+/// The data is collected from the repositories by `analyze-vcs`.
 fn main() {
     simple_logger::init_with_level(log::Level::Info).unwrap();
     let start_time = std::time::Instant::now();
@@ -74,54 +145,52 @@ fn run() -> Result<(), Box<dyn Error>> {
     log::info!("Starting the clone process for max {} crates.", args.limit);
 
     let crates: Vec<Crate> = read_crates(0)?;
-    update_repositories(&crates, args.limit, args.recent, args.force, args.clone)?;
+    let repositories = select_repositories(&crates, &args)?;
+    update_repositories(&repositories, args.jobs, args.timeout, args.mode)?;
+    manage_disk_space(&crates, &args)?;
 
     Ok(())
 }
 
-fn update_repositories(
-    crates: &Vec<Crate>,
-    limit: u32,
-    recent: u32,
-    force: bool,
-    clone: bool,
-) -> Result<(), Box<dyn Error>> {
-    log::info!("start update repositories");
+/// The repositories we need to clone or pull, the ones of the most recently released crates first.
+fn select_repositories(
+    crates: &[Crate],
+    args: &Cli,
+) -> Result<Vec<(String, Repository)>, Box<dyn Error>> {
+    log::info!("start selecting repositories");
 
-    std::env::set_var("GIT_TERMINAL_PROMPT", "0");
-
-    let mut repo_reuse: HashMap<String, i32> = HashMap::new(); // number of times each repository is used for crates (monorepo)
+    let mut seen: HashSet<String> = HashSet::new(); // several crates might use the same repository (monorepo)
     let now: DateTime<Utc> = Utc::now();
     let before: DateTime<Utc> = now
-        - Duration::try_days(recent as i64)
+        - Duration::try_days(i64::from(args.recent))
             .ok_or_else(|| Box::<dyn Error>::from("Could not convert recent"))?;
     log::info!("before: {before}");
+    let attempted_after: DateTime<Utc> = now
+        - Duration::try_hours(i64::from(args.min_hours))
+            .ok_or_else(|| Box::<dyn Error>::from("Could not convert min-hours"))?;
 
-    let mut count: u32 = 0;
-    for krate in crates {
-        if 0 < limit && limit <= count {
+    // The timestamps look like this: 2023-09-18 01:44:10.299066 so we can compare them as strings.
+    let mut by_release = crates.iter().collect::<Vec<_>>();
+    by_release.sort_by(|first, second| second.updated_at.cmp(&first.updated_at));
+
+    let mut repositories = vec![];
+    for krate in by_release {
+        if 0 < args.limit && args.limit as usize <= repositories.len() {
             break;
         }
-        //log::info!("update_at {}", krate.updated_at); // 2023-09-18 01:44:10.299066
         log::info!("Crate {} updated_at: {}", krate.name, krate.updated_at);
-        if 0 < recent && crate_too_old(krate, before) {
+        if 0 < args.recent && crate_too_old(krate, before) {
             continue;
         }
 
-        let repository_url = get_repository_url(krate);
+        let Some(repository_url) = get_repository_url(krate) else {
+            continue;
+        };
 
-        if repository_url.is_empty() {
+        if !seen.insert(repository_url.clone()) {
             continue;
         }
 
-        match repo_reuse.get(&repository_url as &str) {
-            Some(value) => {
-                repo_reuse.insert(repository_url.clone(), value + 1);
-                continue;
-            }
-            None => repo_reuse.insert(repository_url.clone(), 1),
-        };
-
         let repo = match Repository::from_url(&repository_url) {
             Ok(repo) => repo,
             Err(err) => {
@@ -134,82 +203,688 @@ fn update_repositories(
             }
         };
 
-        let details = load_vcs_details(&repository_url);
-        if !details.git_clone_error.is_empty() && !force {
+        let has_clone = repo.path(&get_repos_folder()).exists();
+        if has_clone && args.clone {
             continue;
         }
 
-        log::info!("update ({count}/{limit}) repository '{}'", &repository_url);
-
-        let status = check_url(&repository_url);
-        if status != 200 {
-            log::error!(
-                "Error accessing the repository '{}' status: {}",
-                &repository_url,
-                status
+        let state = load_vcs_details(&repository_url).clone_state;
+        if !args.force && !state.is_due(now) {
+            continue;
+        }
+        // We removed the clone to save space, we clone it again only if there is a new release.
+        if !args.force && !state.evicted.is_empty() && !released_since(krate, &state.evicted) {
+            continue;
+        }
+        if state.attempted_since(attempted_after) {
+            log::info!(
+                "Repository '{repository_url}' was updated at {}",
+                state.last_attempt
             );
             continue;
         }
-        repo.update_repository(&get_repos_folder(), clone, None)?;
 
-        count += 1;
+        repositories.push((repository_url, repo));
     }
 
-    Ok(())
+    log::info!(
+        "Repositories: {}, to update: {}",
+        seen.len(),
+        repositories.len()
+    );
+
+    Ok(repositories)
 }
 
-fn get_repository_url(krate: &Crate) -> String {
-    if !krate.repository.is_empty() {
-        return krate.repository.to_lowercase();
+/// The link we use as the repository of the crate, the homepage if there is no repository link.
+fn repository_link(krate: &Crate) -> &str {
+    if krate.repository.is_empty() {
+        &krate.homepage
+    } else {
+        &krate.repository
     }
+}
 
-    if !krate.homepage.is_empty() {
+/// The canonical URL of the repository of the crate, after following the redirects we already know about.
+fn get_repository_url(krate: &Crate) -> Option<String> {
+    let url = repository_link(krate);
+    if url.is_empty() {
+        return None;
+    }
+    if krate.repository.is_empty() {
         log::info!(
             "Trying to use homepage field '{}' as a repository link to clone the project",
             krate.homepage
         );
-        return krate.homepage.to_lowercase();
     }
 
-    String::new()
+    let repository_url = resolve_repository_url(url);
+    if repository_url.is_none() {
+        log::warn!("Invalid repository url '{url}' in crate {}", krate.name);
+    }
+    repository_url
 }
 
-fn crate_too_old(krate: &Crate, before: DateTime<Utc>) -> bool {
+/// When the crate was last released.
+fn crate_updated_at(krate: &Crate) -> Result<DateTime<Utc>, chrono::ParseError> {
     // 2025-03-23 01:14:51.877032+00
     //let updated_at = match NaiveDateTime::parse_from_str(&krate.updated_at, "%Y-%m-%d %H:%M:%S.%f")
-    let updated_at =
-        match NaiveDateTime::parse_from_str(&krate.updated_at, "%Y-%m-%d %H:%M:%S.%f%Z") {
-            Ok(ts) => ts,
-            Err(err) => {
-                // TODO there are some crates, eg. one called cargo-script where the
-                // updated_at field has no microseconds and it looks like this: 2023-09-18 01:44:10
-                log::error!(
-                    "Error parsing timestamp '{}' of the crate {} ({})",
-                    &krate.updated_at,
-                    &krate.name,
-                    err
-                );
-                //std::process::exit(1);
-                return true;
-            }
-        };
-    if updated_at < before.naive_utc() {
+    NaiveDateTime::parse_from_str(&krate.updated_at, "%Y-%m-%d %H:%M:%S.%f%Z")
+        .map(|updated_at| updated_at.and_utc())
+}
+
+fn crate_too_old(krate: &Crate, before: DateTime<Utc>) -> bool {
+    match crate_updated_at(krate) {
+        Ok(updated_at) => updated_at < before,
+        Err(err) => {
+            // TODO there are some crates, eg. one called cargo-script where the
+            // updated_at field has no microseconds and it looks like this: 2023-09-18 01:44:10
+            log::error!(
+                "Error parsing timestamp '{}' of the crate {} ({})",
+                &krate.updated_at,
+                &krate.name,
+                err
+            );
+            //std::process::exit(1);
+            true
+        }
+    }
+}
+
+/// The crate was released after the given time.
+fn released_since(krate: &Crate, since: &str) -> bool {
+    let Ok(since_time) = DateTime::parse_from_rfc3339(since) else {
         return true;
+    };
+    crate_updated_at(krate).is_ok_and(|updated_at| since_time < updated_at)
+}
+
+/// Clone or pull the repositories using `jobs` threads.
+/// Each thread takes the next repository from the list until none is left.
+fn update_repositories(
+    repositories: &[(String, Repository)],
+    jobs: usize,
+    timeout: u64,
+    mode: CloneMode,
+) -> Result<(), Box<dyn Error>> {
+    log::info!("start update repositories");
+
+    std::env::set_var("GIT_TERMINAL_PROMPT", "0");
+
+    // We don't follow the redirects so we can tell if a repository was moved.
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(CHECK_TIMEOUT)
+        .user_agent(format!(
+            "Rust Digger {VERSION} https://rust-digger.code-maven.com/"
+        ))
+        .build()?;
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let redirects = Mutex::new(BTreeMap::new());
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            scope.spawn(|| {
+                while let Some(repository) = repositories.get(next.fetch_add(1, Ordering::Relaxed))
+                {
+                    log::info!(
+                        "update ({}/{}) repository '{}'",
+                        next.load(Ordering::Relaxed),
+                        repositories.len(),
+                        repository.0
+                    );
+                    if !update_repository(
+                        &client,
+                        &repository.0,
+                        &repository.1,
+                        timeout,
+                        mode,
+                        &redirects,
+                    ) {
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    log::info!(
+        "Updated {} repositories, failed: {}",
+        repositories.len(),
+        failed.load(Ordering::Relaxed)
+    );
+
+    let new_redirects = redirects.into_inner().unwrap_or_default();
+    if !new_redirects.is_empty() {
+        log::info!("Found {} moved repositories", new_redirects.len());
+        let mut all_redirects = load_repository_redirects();
+        all_redirects.extend(new_redirects);
+        save_repository_redirects(&all_redirects)?;
+    }
+
+    Ok(())
+}
+
+/// Clone or pull the repository and save the result in its details.
+/// If the repository was moved we record the redirect and from now on we use the new URL.
+/// Returns false if the update failed.
+fn update_repository(
+    client: &reqwest::blocking::Client,
+    repository_url: &str,
+    repo: &Repository,
+    timeout: u64,
+    mode: CloneMode,
+    redirects: &Mutex<BTreeMap<String, String>>,
+) -> bool {
+    let started_at = Utc::now().to_rfc3339();
+    let start_time = std::time::Instant::now();
+    let previous_mode = load_vcs_details(repository_url).clone_state.mode;
+    let mut retry_after = None;
+    let (cloned_url, result) = match check_url(client, repository_url) {
+        Ok(None) => (
+            repository_url.to_owned(),
+            clone_or_pull(repo, mode, previous_mode, timeout),
+        ),
+        Ok(Some(location)) => match moved_repository(repository_url, &location) {
+            Some((new_url, new_repo)) => {
+                log::warn!("Repository '{repository_url}' was moved to '{new_url}'");
+                if let Ok(mut known) = redirects.lock() {
+                    known.insert(repository_url.to_owned(), new_url.clone());
+                }
+                let result = move_clone(repo, &new_repo, timeout)
+                    .and_then(|()| clone_or_pull(&new_repo, mode, previous_mode, timeout));
+                (new_url, result)
+            }
+            None => (
+                repository_url.to_owned(),
+                Err((
+                    CloneFailure::Renamed,
+                    format!("The repository redirects to '{location}'"),
+                )),
+            ),
+        },
+        Err((failure, err, host_retry_after)) => {
+            retry_after = host_retry_after;
+            (repository_url.to_owned(), Err((failure, err)))
+        }
+    };
+    let duration_secs = start_time.elapsed().as_secs();
+
+    let mut details = load_vcs_details(&cloned_url);
+    let succeeded = match result {
+        Ok(update) => {
+            details.clone_state.record_success(
+                &started_at,
+                duration_secs,
+                &update.head,
+                update.mode,
+                update.disk_size,
+            );
+            true
+        }
+        Err((failure, err)) => {
+            log::error!(
+                "Error updating the repository '{cloned_url}' ({}): {err}",
+                failure.name()
+            );
+            details
+                .clone_state
+                .record_failure(&started_at, duration_secs, failure, &err);
+            if let Some(next_attempt) = retry_after {
+                details.clone_state.record_retry_after(next_attempt);
+            }
+            false
+        }
+    };
+    if let Err(err) = save_details(&cloned_url, &details) {
+        log::error!("Could not save the details of '{cloned_url}': {err}");
     }
 
-    false
+    succeeded
 }
 
-fn check_url(url: &str) -> reqwest::StatusCode {
+/// Why the web page of the repository is not available and when the host asked us to try again.
+type UrlFailure = (CloneFailure, String, Option<DateTime<Utc>>);
+
+/// Check the web page of the repository using a HEAD request.
+/// Returns the new URL if the repository was moved.
+/// If it failed, also the time the host asked us to try again, see `CloneFailure::Unavailable`.
+fn check_url(client: &reqwest::blocking::Client, url: &str) -> Result<Option<String>, UrlFailure> {
     log::info!("Checking url {url}");
 
-    let res = match reqwest::blocking::get(url) {
-        Ok(res) => res,
-        Err(err) => {
-            log::error!("Could not get '{url}': {err}");
-            return reqwest::StatusCode::INTERNAL_SERVER_ERROR;
+    let response = client.head(url).send().map_err(|err| {
+        let failure = if err.is_timeout() {
+            CloneFailure::Timeout
+        } else if err.is_builder() {
+            CloneFailure::InvalidUrl
+        } else {
+            CloneFailure::Other
+        };
+        (failure, format!("Could not get '{url}': {err}"), None)
+    })?;
+    let status = response.status();
+    log::info!("Status: {status}");
+
+    let location = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    match CloneFailure::from_status(status.as_u16(), &location) {
+        None => Ok(None),
+        Some(CloneFailure::Renamed) if location.starts_with("https://") => Ok(Some(location)),
+        Some(failure) => Err((
+            failure,
+            format!("status {status} when accessing the repository {location}")
+                .trim_end()
+                .to_owned(),
+            response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|retry_after| retry_after.to_str().ok())
+                .and_then(|retry_after| parse_retry_after(retry_after, Utc::now())),
+        )),
+    }
+}
+
+/// The canonical URL and the repository the web page of the repository redirects to.
+/// Returns `None` if the redirect does not lead to another repository, e.g. to a login page.
+fn moved_repository(repository_url: &str, location: &str) -> Option<(String, Repository)> {
+    let new_url = canonical_repository_url(location)?.url;
+    if new_url == repository_url {
+        return None;
+    }
+    let new_repo = Repository::from_url(&new_url).ok()?;
+    Some((new_url, new_repo))
+}
+
+/// Move the clone of a moved repository to the folder of its new URL so we can pull it instead of cloning it again.
+fn move_clone(
+    old_repo: &Repository,
+    new_repo: &Repository,
+    timeout: u64,
+) -> Result<(), (CloneFailure, String)> {
+    let root = get_repos_folder();
+    let old_path = old_repo.path(&root);
+    let new_path = new_repo.path(&root);
+    if !old_path.exists() || new_path.exists() {
+        return Ok(());
+    }
+
+    log::info!(
+        "Moving {:?} to {:?}",
+        old_path.display(),
+        new_path.display()
+    );
+    fs::create_dir_all(new_repo.owner_path(&root))
+        .and_then(|()| fs::rename(&old_path, &new_path))
+        .map_err(|err| {
+            (
+                CloneFailure::Other,
+                format!("Could not move {:?}: {err}", old_path.display()),
+            )
+        })?;
+    run_git(
+        &new_path,
+        &["remote", "set-url", "origin", &new_repo.url()],
+        timeout,
+    )
+}
+
+/// The clone after a successful update.
+struct Update {
+    head: String,
+    mode: CloneMode,
+    disk_size: u64,
+}
+
+/// Clone or pull the repository in the given mode, `previous_mode` is the mode of the existing clone.
+fn clone_or_pull(
+    repo: &Repository,
+    mode: CloneMode,
+    previous_mode: CloneMode,
+    timeout: u64,
+) -> Result<Update, (CloneFailure, String)> {
+    let repo_path = repo.path(&get_repos_folder());
+    let new_mode = if repo_path.exists() {
+        if mode == CloneMode::Shallow {
+            run_git(&repo_path, &["pull", "--depth", "1"], timeout)?;
+        } else {
+            if previous_mode == CloneMode::Shallow {
+                run_git(&repo_path, &["fetch", "--unshallow"], timeout)?;
+            }
+            run_git(&repo_path, &["pull"], timeout)?;
         }
+        previous_mode.after_pull(mode)
+    } else {
+        let url = repo.url();
+        let target = repo_path.display().to_string();
+        let mut args = vec!["clone"];
+        args.extend(mode.clone_args());
+        args.extend([url.as_str(), target.as_str()]);
+        run_git(&get_repos_folder(), &args, timeout)?;
+        mode
     };
-    log::info!("Status: {}", res.status());
-    res.status()
+
+    Ok(Update {
+        head: git_lines(&repo_path, &["rev-parse", "HEAD"])
+            .into_iter()
+            .next()
+            .unwrap_or_default(),
+        mode: new_mode,
+        disk_size: disk_usage(&repo_path),
+    })
+}
+
+/// Unlike `git_digger` we don't change the current directory of the process as we run several git commands in parallel.
+/// We stop git after `timeout` seconds.
+fn run_git(folder: &Path, args: &[&str], timeout: u64) -> Result<(), (CloneFailure, String)> {
+    let start = std::time::Instant::now();
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(folder)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            (
+                CloneFailure::Other,
+                format!("Could not run git {args:?}: {err}"),
+            )
+        })?;
+    let mut stderr_pipe = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut stderr = String::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            if let Err(err) = pipe.read_to_string(&mut stderr) {
+                log::warn!("Could not read the output of git: {err}");
+            }
+        }
+        stderr
+    });
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {}
+            Err(err) => return Err((CloneFailure::Other, err.to_string())),
+        }
+        if StdDuration::from_secs(timeout) <= start.elapsed() {
+            if let Err(err) = child.kill() {
+                log::warn!("Could not kill git: {err}");
+            }
+            let _status = child.wait();
+            return Err((
+                CloneFailure::Timeout,
+                format!("git {args:?} timed out after {timeout} seconds"),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if status.success() {
+        return Ok(());
+    }
+    Err((
+        CloneFailure::from_git_error(&stderr),
+        stderr
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .map_or_else(|| format!("git {args:?} failed"), ToOwned::to_owned),
+    ))
+}
+
+/// A clone in the repos folder.
+struct LocalClone {
+    url: String,
+    host: String,
+    owner: String,
+    path: PathBuf,
+    size: u64,
+
+    /// When we last updated the clone, empty if we don't know.
+    last_used: String,
+
+    /// The details of the URL belong to this clone, this is not the clone of a repository that was moved since.
+    has_details: bool,
+}
+
+/// Remove the least recently used clones of inactive crates if the clones take more space than the disk budget,
+/// then save a report of the space used by each host and owner.
+fn manage_disk_space(crates: &[Crate], args: &Cli) -> Result<(), Box<dyn Error>> {
+    log::info!("start managing disk space");
+    let now = Utc::now();
+
+    let mut clones = list_clones();
+    if 0 < args.disk_budget {
+        let inactive_before = now
+            - Duration::try_days(i64::from(args.inactive_days)).ok_or("Invalid number of days")?;
+        evict_clones(
+            &mut clones,
+            crates,
+            args.disk_budget.saturating_mul(GB),
+            inactive_before,
+            &now.to_rfc3339(),
+        );
+    }
+
+    let usage = DiskUsage::new(
+        &now.to_rfc3339(),
+        &clones
+            .into_iter()
+            .map(|clone| (clone.host, clone.owner, clone.size))
+            .collect::<Vec<_>>(),
+    );
+    log::info!("{} clones take {} bytes", usage.clones, usage.size);
+    for owner in usage.owners.iter().take(10) {
+        log::info!(
+            "{}: {} clones, {} bytes",
+            owner.name,
+            owner.clones,
+            owner.size
+        );
+    }
+    save_disk_usage(&usage)?;
+
+    Ok(())
+}
+
+/// The clones in the repos folder, in the `host/owner/name` folders.
+fn list_clones() -> Vec<LocalClone> {
+    let root = get_repos_folder();
+    WalkDir::new(&root)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_dir())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(&root).ok()?;
+            let mut parts = relative
+                .iter()
+                .map(|part| part.to_string_lossy().into_owned());
+            let (host, owner, name) = (parts.next()?, parts.next()?, parts.next()?);
+            let url = format!("https://{host}/{owner}/{name}");
+            let has_details = resolve_repository_url(&url).as_ref() == Some(&url);
+
+            let mut details = load_vcs_details(&url);
+            let mut size = details.clone_state.disk_size;
+            if !has_details || size == 0 {
+                size = disk_usage(entry.path());
+            }
+            if has_details && details.clone_state.disk_size == 0 {
+                details.clone_state.disk_size = size;
+                if let Err(err) = save_details(&url, &details) {
+                    log::error!("Could not save the details of '{url}': {err}");
+                }
+            }
+
+            Some(LocalClone {
+                url,
+                host,
+                owner,
+                path: entry.path().to_path_buf(),
+                size,
+                last_used: if has_details {
+                    details.clone_state.last_success
+                } else {
+                    String::new()
+                },
+                has_details,
+            })
+        })
+        .collect()
+}
+
+/// Remove clones until they fit in the budget.
+/// We only remove clones of repositories without a crate released after `inactive_before`
+/// and clones no crate links to any more, the least recently updated first.
+/// A crate with an `updated_at` we cannot parse counts as active.
+fn evict_clones(
+    clones: &mut Vec<LocalClone>,
+    crates: &[Crate],
+    budget: u64,
+    inactive_before: DateTime<Utc>,
+    evicted_at: &str,
+) {
+    let mut total: u64 = clones.iter().map(|clone| clone.size).sum();
+    if total <= budget {
+        return;
+    }
+
+    let mut last_release: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for krate in crates {
+        let Some(url) = resolve_repository_url(repository_link(krate)) else {
+            continue;
+        };
+        // if we cannot tell when the crate was released we keep its clone
+        let updated_at = crate_updated_at(krate).unwrap_or(DateTime::<Utc>::MAX_UTC);
+        last_release
+            .entry(url)
+            .and_modify(|latest| *latest = (*latest).max(updated_at))
+            .or_insert(updated_at);
+    }
+
+    let mut candidates = clones
+        .iter()
+        .filter(|clone| {
+            last_release
+                .get(&clone.url)
+                .is_none_or(|updated_at| *updated_at < inactive_before)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|first, second| first.last_used.cmp(&second.last_used));
+
+    let mut evicted = HashSet::new();
+    for clone in candidates {
+        if total <= budget {
+            break;
+        }
+        log::info!(
+            "Removing the clone of '{}' last updated at '{}' ({} bytes)",
+            clone.url,
+            clone.last_used,
+            clone.size
+        );
+        if let Err(err) = fs::remove_dir_all(&clone.path) {
+            log::error!("Could not remove {:?}: {err}", clone.path.display());
+            continue;
+        }
+        total = total.saturating_sub(clone.size);
+        evicted.insert(clone.url.clone());
+
+        if clone.has_details {
+            let mut details = load_vcs_details(&clone.url);
+            details.clone_state.record_eviction(evicted_at);
+            if let Err(err) = save_details(&clone.url, &details) {
+                log::error!("Could not save the details of '{}': {err}", clone.url);
+            }
+        }
+    }
+
+    clones.retain(|clone| !evicted.contains(&clone.url));
+    if budget < total {
+        log::warn!(
+            "The clones take {total} bytes even after removing {} clones, more than the budget of {budget} bytes",
+            evicted.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_clone(root: &Path, name: &str, last_used: &str) -> LocalClone {
+        let path = root.join("github.com/foo").join(name);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("Cargo.toml"), "").unwrap();
+        LocalClone {
+            url: format!("https://github.com/foo/{name}"),
+            host: String::from("github.com"),
+            owner: String::from("foo"),
+            path,
+            size: 10,
+            last_used: last_used.to_owned(),
+            has_details: false,
+        }
+    }
+
+    fn released(name: &str, updated_at: &str) -> Crate {
+        let mut krate = Crate::new();
+        krate.name = name.to_owned();
+        krate.repository = format!("https://github.com/foo/{name}");
+        krate.updated_at = updated_at.to_owned();
+        krate
+    }
+
+    fn names(clones: &[LocalClone]) -> Vec<&str> {
+        clones
+            .iter()
+            .map(|clone| clone.url.trim_start_matches("https://github.com/foo/"))
+            .collect()
+    }
+
+    #[test]
+    fn check_evict_clones() {
+        let tmp_dir = tempdir::TempDir::new("repos").unwrap();
+        let mut clones = vec![
+            local_clone(tmp_dir.path(), "active", "2024-01-01T00:00:00+00:00"),
+            local_clone(tmp_dir.path(), "unparsed", "2024-01-01T00:00:00+00:00"),
+            local_clone(tmp_dir.path(), "old", "2024-01-02T00:00:00+00:00"),
+            local_clone(tmp_dir.path(), "older", "2024-01-01T00:00:00+00:00"),
+            local_clone(tmp_dir.path(), "orphan", "2024-01-03T00:00:00+00:00"),
+        ];
+        let crates = vec![
+            released("active", "2025-03-23 01:14:51.877032+00"),
+            released("unparsed", "2023-09-18 01:44:10"),
+            released("old", "2023-01-01 10:00:00.000000+00"),
+            released("older", "2022-01-01 10:00:00.000000+00"),
+        ];
+        let inactive_before = DateTime::parse_from_rfc3339("2024-06-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let evicted_at = "2025-01-01T00:00:00+00:00";
+
+        evict_clones(&mut clones, &crates, 50, inactive_before, evicted_at);
+        assert_eq!(clones.len(), 5);
+
+        // the least recently updated inactive clone goes first
+        evict_clones(&mut clones, &crates, 40, inactive_before, evicted_at);
+        assert_eq!(names(&clones), vec!["active", "unparsed", "old", "orphan"]);
+        assert!(!tmp_dir.path().join("github.com/foo/older").exists());
+
+        evict_clones(&mut clones, &crates, 30, inactive_before, evicted_at);
+        assert_eq!(names(&clones), vec!["active", "unparsed", "orphan"]);
+
+        // the clones of active crates and of crates with an unknown release date are kept even over the budget
+        evict_clones(&mut clones, &crates, 0, inactive_before, evicted_at);
+        assert_eq!(names(&clones), vec!["active", "unparsed"]);
+        assert!(tmp_dir.path().join("github.com/foo/active").exists());
+        assert!(tmp_dir.path().join("github.com/foo/unparsed").exists());
+        assert!(!tmp_dir.path().join("github.com/foo/orphan").exists());
+    }
 }
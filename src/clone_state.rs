@@ -0,0 +1,579 @@
+use core::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Parts of the error output of `git clone` and `git pull` and the kind of failure they indicate.
+/// A full disk, a conflict while pulling or a local file we cannot write are our problems, not the ones of the repository,
+/// so they are not in this list and count as `Other`. The patterns are specific enough not to match such local errors,
+/// e.g. `fatal: Remote branch main not found` or a local `Permission denied`.
+const GIT_ERRORS: [(&str, CloneFailure); 12] = [
+    ("repository not found", CloneFailure::NotFound),
+    // fatal: repository 'https://example.com/foo/bar/' not found
+    ("' not found", CloneFailure::NotFound),
+    (
+        "the requested url returned error: 404",
+        CloneFailure::NotFound,
+    ),
+    ("authentication failed", CloneFailure::AuthRequired),
+    ("could not read username", CloneFailure::AuthRequired),
+    ("terminal prompts disabled", CloneFailure::AuthRequired),
+    ("permission denied (publickey)", CloneFailure::AuthRequired),
+    ("timed out", CloneFailure::Timeout),
+    ("pack exceeds maximum allowed size", CloneFailure::TooLarge),
+    (
+        "does not appear to be a git repository",
+        CloneFailure::InvalidUrl,
+    ),
+    ("is not a valid repository name", CloneFailure::InvalidUrl),
+    ("unable to access", CloneFailure::Other),
+];
+
+/// Why we could not clone or pull a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum CloneFailure {
+    /// The repository was deleted or never existed. GitHub also says this about private repositories.
+    NotFound,
+    /// The repository is private or the host wants us to log in.
+    AuthRequired,
+    /// The repository was moved. Once we know the new URL we clone that, but the link in the crate is outdated.
+    Renamed,
+    Timeout,
+    /// The repository is too big to clone.
+    TooLarge,
+    /// The URL does not point to a git repository.
+    InvalidUrl,
+    /// The host is rate limiting us (429) or has a temporary error (5xx), the link is not broken.
+    Unavailable,
+    Other,
+}
+
+impl CloneFailure {
+    pub const ALL: [Self; 8] = [
+        Self::NotFound,
+        Self::AuthRequired,
+        Self::Renamed,
+        Self::Timeout,
+        Self::TooLarge,
+        Self::InvalidUrl,
+        Self::Unavailable,
+        Self::Other,
+    ];
+
+    /// The name we use in the URLs and the templates, e.g. `not-found`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NotFound => "not-found",
+            Self::AuthRequired => "auth-required",
+            Self::Renamed => "renamed",
+            Self::Timeout => "timeout",
+            Self::TooLarge => "too-large",
+            Self::InvalidUrl => "invalid-url",
+            Self::Unavailable => "unavailable",
+            Self::Other => "other",
+        }
+    }
+
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::NotFound => "Repository not found or deleted",
+            Self::AuthRequired => "Private repository or login required",
+            Self::Renamed => "Repository was renamed or moved",
+            Self::Timeout => "Timed out",
+            Self::TooLarge => "Repository too large",
+            Self::InvalidUrl => "Not a git repository",
+            Self::Unavailable => "Host temporarily unavailable",
+            Self::Other => "Other error",
+        }
+    }
+
+    /// The failure says nothing about the repository, we just try again later.
+    pub const fn is_transient(self) -> bool {
+        matches!(self, Self::Unavailable)
+    }
+
+    /// The failure is about the repository the crate links to, so the link is broken.
+    /// Timeouts, unavailable hosts and other errors (e.g. a full disk on our side) say nothing about the link.
+    pub const fn is_about_repository(self) -> bool {
+        match self {
+            Self::NotFound
+            | Self::AuthRequired
+            | Self::Renamed
+            | Self::TooLarge
+            | Self::InvalidUrl => true,
+            Self::Timeout | Self::Unavailable | Self::Other => false,
+        }
+    }
+
+    /// How many hours to wait after the first failure and the longest we wait, the wait doubles after each failure.
+    /// `None` means we don't try again unless we are forced to.
+    const fn backoff_hours(self) -> Option<(i64, i64)> {
+        match self {
+            Self::Unavailable => Some((1, 24)),
+            Self::Timeout => Some((1, 7 * 24)),
+            Self::Other => Some((24, 30 * 24)),
+            Self::Renamed => Some((24, 24)),
+            Self::NotFound => Some((7 * 24, 90 * 24)),
+            Self::AuthRequired | Self::TooLarge => Some((30 * 24, 180 * 24)),
+            Self::InvalidUrl => None,
+        }
+    }
+
+    /// How long to wait before the next attempt after the given number of consecutive failures.
+    pub fn retry_delay(self, failures: u32) -> Option<Duration> {
+        let (first, longest) = self.backoff_hours()?;
+        let doubling: i64 = 2;
+        let factor = doubling.saturating_pow(failures.saturating_sub(1).min(16));
+        Duration::try_hours(first.saturating_mul(factor).min(longest))
+    }
+
+    /// Classify the HTTP status of the web page of the repository, `location` is the value of the `Location` header of redirects.
+    /// Returns `None` if the page is available.
+    pub fn from_status(status: u16, location: &str) -> Option<Self> {
+        match status {
+            200..=299 => None,
+            301 | 302 | 303 | 307 | 308 => {
+                if location.contains("/sign_in") || location.contains("/login") {
+                    Some(Self::AuthRequired)
+                } else {
+                    Some(Self::Renamed)
+                }
+            }
+            401 | 403 => Some(Self::AuthRequired),
+            404 | 410 => Some(Self::NotFound),
+            408 | 504 => Some(Self::Timeout),
+            429 | 500..=599 => Some(Self::Unavailable),
+            _ => Some(Self::Other),
+        }
+    }
+
+    /// Classify the error output of `git clone` or `git pull`.
+    pub fn from_git_error(stderr: &str) -> Self {
+        let lower = stderr.to_lowercase();
+        GIT_ERRORS
+            .iter()
+            .find(|error| lower.contains(error.0))
+            .map_or(Self::Other, |error| error.1)
+    }
+}
+
+/// The time in the `Retry-After` header, either a number of seconds or a date, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`.
+pub fn parse_retry_after(header: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(seconds) = header.trim().parse::<i64>() {
+        return Duration::try_seconds(seconds).map(|delay| now + delay);
+    }
+    DateTime::parse_from_rfc2822(header.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// How much of the repository we clone.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum CloneMode {
+    /// All the commits with all the files.
+    Full,
+    /// All the commits and trees, but only the files of the checked out commit (`--filter=blob:none`).
+    /// Enough for the analysis of the history and the tags, git fetches other files when they are needed.
+    Blobless,
+    /// Only the last commit (`--depth 1`). Enough to format, build and test the code, but there is no history and no tags.
+    Shallow,
+}
+
+impl CloneMode {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Blobless => "blobless",
+            Self::Shallow => "shallow",
+        }
+    }
+
+    /// The extra arguments of `git clone`.
+    pub const fn clone_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Full => &[],
+            Self::Blobless => &["--filter=blob:none"],
+            Self::Shallow => &["--depth", "1"],
+        }
+    }
+
+    /// The clone has the commits and the tags of the repository.
+    pub const fn has_history(self) -> bool {
+        match self {
+            Self::Full | Self::Blobless => true,
+            Self::Shallow => false,
+        }
+    }
+
+    /// The mode of an existing clone of the given mode after we pulled it in this mode.
+    /// Pulling a shallow clone in another mode fetches all the history, pulling in shallow mode keeps only the last commit.
+    #[must_use]
+    pub const fn after_pull(self, requested: Self) -> Self {
+        match (self, requested) {
+            (Self::Shallow, Self::Full | Self::Blobless) => Self::Full,
+            (_, Self::Shallow) => Self::Shallow,
+            (Self::Full | Self::Blobless, Self::Full | Self::Blobless) => self,
+        }
+    }
+}
+
+impl FromStr for CloneMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [Self::Full, Self::Blobless, Self::Shallow]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| format!("Invalid clone mode '{name}', use full, blobless or shallow"))
+    }
+}
+
+/// Clones made before we had clone modes are full clones.
+const fn full_clone() -> CloneMode {
+    CloneMode::Full
+}
+
+/// What happened when we last tried to clone or pull a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CloneState {
+    /// When we last tried to clone or pull, e.g. `2024-01-01T12:00:00+00:00`, empty if we never tried.
+    pub last_attempt: String,
+
+    /// When the last clone or pull succeeded.
+    pub last_success: String,
+
+    /// The commit of the clone after the last successful update (`git rev-parse HEAD`).
+    pub head: String,
+
+    /// The number of failed attempts since the last successful one.
+    pub consecutive_failures: u32,
+
+    /// How long the last attempt took.
+    pub duration_secs: u64,
+
+    /// The error of the last attempt, empty if it succeeded.
+    pub error: String,
+
+    /// What is wrong with the link to the repository, `None` if nothing.
+    #[serde(default)]
+    pub failure: Option<CloneFailure>,
+
+    /// How much of the repository we have in the clone.
+    #[serde(default = "full_clone")]
+    pub mode: CloneMode,
+
+    /// The size of the clone after the last successful update in bytes.
+    #[serde(default)]
+    pub disk_size: u64,
+
+    /// When we removed the clone to stay within the disk budget, empty if we have the clone.
+    #[serde(default)]
+    pub evicted: String,
+
+    /// The host asked us in the `Retry-After` header not to try again before this time, empty if it did not.
+    #[serde(default)]
+    pub retry_after: String,
+}
+
+impl CloneState {
+    pub const fn new() -> Self {
+        Self {
+            last_attempt: String::new(),
+            last_success: String::new(),
+            head: String::new(),
+            consecutive_failures: 0,
+            duration_secs: 0,
+            error: String::new(),
+            failure: None,
+            mode: CloneMode::Full,
+            disk_size: 0,
+            evicted: String::new(),
+            retry_after: String::new(),
+        }
+    }
+
+    pub fn record_success(
+        &mut self,
+        started_at: &str,
+        duration_secs: u64,
+        head: &str,
+        mode: CloneMode,
+        disk_size: u64,
+    ) {
+        started_at.clone_into(&mut self.last_attempt);
+        started_at.clone_into(&mut self.last_success);
+        head.clone_into(&mut self.head);
+        self.consecutive_failures = 0;
+        self.duration_secs = duration_secs;
+        self.error = String::new();
+        self.failure = None;
+        self.mode = mode;
+        self.disk_size = disk_size;
+        self.evicted = String::new();
+        self.retry_after = String::new();
+    }
+
+    /// We removed the clone to free disk space.
+    pub fn record_eviction(&mut self, evicted_at: &str) {
+        evicted_at.clone_into(&mut self.evicted);
+        self.disk_size = 0;
+    }
+
+    pub fn record_failure(
+        &mut self,
+        started_at: &str,
+        duration_secs: u64,
+        failure: CloneFailure,
+        error: &str,
+    ) {
+        started_at.clone_into(&mut self.last_attempt);
+        self.consecutive_failures += 1;
+        self.duration_secs = duration_secs;
+        self.failure = Some(failure);
+        error.clone_into(&mut self.error);
+        self.retry_after = String::new();
+    }
+
+    /// The host told us when to try again, we wait for that instead of the backoff of the failure.
+    pub fn record_retry_after(&mut self, retry_after: DateTime<Utc>) {
+        self.retry_after = retry_after.to_rfc3339();
+    }
+
+    /// It is time to try again to update the repository according to the backoff of the last failure.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        if !self.has_failed() {
+            return true;
+        }
+        if let Ok(retry_after) = DateTime::parse_from_rfc3339(&self.retry_after) {
+            return retry_after <= now;
+        }
+        let Some(delay) = self
+            .failure
+            .unwrap_or(CloneFailure::Other)
+            .retry_delay(self.consecutive_failures)
+        else {
+            return false;
+        };
+        DateTime::parse_from_rfc3339(&self.last_attempt)
+            .map_or(true, |attempt| attempt + delay <= now)
+    }
+
+    /// The last attempt failed.
+    pub const fn has_failed(&self) -> bool {
+        0 < self.consecutive_failures
+    }
+
+    /// We already tried to update the repository after the given time, e.g. in an interrupted run.
+    pub fn attempted_since(&self, since: DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.last_attempt).is_ok_and(|attempt| since <= attempt)
+    }
+}
+
+impl Default for CloneState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_record() {
+        let mut state = CloneState::new();
+        assert!(!state.has_failed());
+
+        state.record_failure(
+            "2024-01-01T10:00:00+00:00",
+            3,
+            CloneFailure::NotFound,
+            "status 404",
+        );
+        state.record_failure(
+            "2024-01-02T10:00:00+00:00",
+            4,
+            CloneFailure::NotFound,
+            "status 404",
+        );
+        assert!(state.has_failed());
+        assert_eq!(state.consecutive_failures, 2);
+        assert_eq!(state.last_success, "");
+
+        state.record_eviction("2024-01-02T12:00:00+00:00");
+        state.record_success(
+            "2024-01-03T10:00:00+00:00",
+            5,
+            "0123abcd",
+            CloneMode::Blobless,
+            1024,
+        );
+        assert!(!state.has_failed());
+        assert_eq!(state.mode, CloneMode::Blobless);
+        assert_eq!(state.disk_size, 1024);
+        assert_eq!(state.evicted, "");
+        assert_eq!(state.failure, None);
+        assert_eq!(state.last_success, "2024-01-03T10:00:00+00:00");
+        assert_eq!(state.head, "0123abcd");
+        assert_eq!(state.error, "");
+
+        let since = DateTime::parse_from_rfc3339("2024-01-03T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(state.attempted_since(since));
+        assert!(!CloneState::new().attempted_since(since));
+        state.record_failure(
+            "2024-01-02T23:00:00+00:00",
+            1,
+            CloneFailure::Timeout,
+            "timeout",
+        );
+        assert!(!state.attempted_since(since));
+    }
+
+    #[test]
+    fn check_classify() {
+        assert_eq!(CloneFailure::from_status(200, ""), None);
+        assert_eq!(
+            CloneFailure::from_status(301, "https://github.com/new/name"),
+            Some(CloneFailure::Renamed)
+        );
+        assert_eq!(
+            CloneFailure::from_status(302, "https://gitlab.com/users/sign_in"),
+            Some(CloneFailure::AuthRequired)
+        );
+        assert_eq!(
+            CloneFailure::from_status(404, ""),
+            Some(CloneFailure::NotFound)
+        );
+        assert_eq!(
+            CloneFailure::from_status(429, ""),
+            Some(CloneFailure::Unavailable)
+        );
+        assert_eq!(
+            CloneFailure::from_status(503, ""),
+            Some(CloneFailure::Unavailable)
+        );
+        assert!(CloneFailure::Unavailable.is_transient());
+        assert!(!CloneFailure::NotFound.is_transient());
+        assert_eq!(
+            CloneFailure::from_status(418, ""),
+            Some(CloneFailure::Other)
+        );
+        assert_eq!(
+            CloneFailure::from_git_error("fatal: write error: No space left on device"),
+            CloneFailure::Other
+        );
+
+        assert_eq!(
+            CloneFailure::from_git_error("remote: Repository not found.\nfatal: repository 'https://github.com/foo/bar/' not found"),
+            CloneFailure::NotFound
+        );
+        assert_eq!(
+            CloneFailure::from_git_error("fatal: could not read Username for 'https://github.com': terminal prompts disabled"),
+            CloneFailure::AuthRequired
+        );
+        assert_eq!(
+            CloneFailure::from_git_error("error: RPC failed; curl 56\nfatal: early EOF"),
+            CloneFailure::Other
+        );
+        assert_eq!(
+            CloneFailure::from_git_error(
+                "fatal: repository 'https://git.example.com/foo/bar/' not found"
+            ),
+            CloneFailure::NotFound
+        );
+        assert_eq!(
+            CloneFailure::from_git_error("fatal: unable to access 'https://example.com/foo/bar/': The requested URL returned error: 404"),
+            CloneFailure::NotFound
+        );
+        assert_eq!(
+            CloneFailure::from_git_error("git@github.com: Permission denied (publickey).\nfatal: Could not read from remote repository."),
+            CloneFailure::AuthRequired
+        );
+
+        // our own problems are not about the repository
+        for stderr in [
+            "fatal: Remote branch main not found in upstream origin",
+            "error: cannot open .git/FETCH_HEAD: Permission denied",
+            "fatal: write error: No space left on device",
+            "error: Your local changes to the following files would be overwritten by merge:\n\tCargo.lock\nAborting",
+            "CONFLICT (content): Merge conflict in src/lib.rs\nAutomatic merge failed; fix conflicts and then commit the result.",
+        ] {
+            assert_eq!(CloneFailure::from_git_error(stderr), CloneFailure::Other, "{stderr}");
+        }
+        assert!(!CloneFailure::Timeout.is_about_repository());
+        assert!(!CloneFailure::Unavailable.is_about_repository());
+        assert!(!CloneFailure::Other.is_about_repository());
+        assert!(CloneFailure::NotFound.is_about_repository());
+        assert!(CloneFailure::Renamed.is_about_repository());
+    }
+
+    #[test]
+    fn check_backoff() {
+        assert_eq!(CloneFailure::Timeout.retry_delay(1), Duration::try_hours(1));
+        assert_eq!(CloneFailure::Timeout.retry_delay(3), Duration::try_hours(4));
+        assert_eq!(
+            CloneFailure::Timeout.retry_delay(100),
+            Duration::try_hours(7 * 24)
+        );
+        assert_eq!(CloneFailure::InvalidUrl.retry_delay(1), None);
+
+        let now = DateTime::parse_from_rfc3339("2024-01-10T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut state = CloneState::new();
+        assert!(state.is_due(now));
+        state.record_failure("2024-01-09T12:00:00+00:00", 1, CloneFailure::Timeout, "");
+        assert!(state.is_due(now));
+        state.record_failure("2024-01-09T12:00:00+00:00", 1, CloneFailure::NotFound, "");
+        assert!(!state.is_due(now));
+        state.record_failure("2024-01-01T12:00:00+00:00", 1, CloneFailure::InvalidUrl, "");
+        assert!(!state.is_due(now));
+
+        state.record_failure(
+            "2024-01-09T23:00:00+00:00",
+            1,
+            CloneFailure::Unavailable,
+            "",
+        );
+        assert!(!state.is_due(now));
+        state.record_retry_after(parse_retry_after("1800", now - Duration::hours(1)).unwrap());
+        assert!(state.is_due(now));
+        state.record_retry_after(parse_retry_after("Wed, 10 Jan 2024 06:00:00 GMT", now).unwrap());
+        assert!(!state.is_due(now));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn check_clone_mode() {
+        assert_eq!("blobless".parse(), Ok(CloneMode::Blobless));
+        assert_eq!(
+            "partial".parse::<CloneMode>(),
+            Err(String::from(
+                "Invalid clone mode 'partial', use full, blobless or shallow"
+            ))
+        );
+        assert!(!CloneMode::Shallow.has_history());
+        assert_eq!(
+            CloneMode::Shallow.after_pull(CloneMode::Blobless),
+            CloneMode::Full
+        );
+        assert_eq!(
+            CloneMode::Full.after_pull(CloneMode::Shallow),
+            CloneMode::Shallow
+        );
+        assert_eq!(
+            CloneMode::Full.after_pull(CloneMode::Blobless),
+            CloneMode::Full
+        );
+        assert_eq!(
+            CloneMode::Blobless.after_pull(CloneMode::Full),
+            CloneMode::Blobless
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The community health files we look for and where they can be, relative to the root of the repository.
+/// The names are compared case-insensitively, as `Changelog.md` and `CHANGELOG.md` are both common.
+pub const COMMUNITY_FILES: [(&str, &[&str]); 7] = [
+    (
+        "security-policy",
+        &["SECURITY.md", ".github/SECURITY.md", "docs/SECURITY.md"],
+    ),
+    (
+        "contributing",
+        &[
+            "CONTRIBUTING.md",
+            ".github/CONTRIBUTING.md",
+            "docs/CONTRIBUTING.md",
+        ],
+    ),
+    (
+        "code-of-conduct",
+        &[
+            "CODE_OF_CONDUCT.md",
+            ".github/CODE_OF_CONDUCT.md",
+            "docs/CODE_OF_CONDUCT.md",
+        ],
+    ),
+    ("changelog", &CHANGELOG_FILES),
+    (
+        "issue-templates",
+        &[
+            ".github/ISSUE_TEMPLATE",
+            ".github/ISSUE_TEMPLATE.md",
+            ".gitlab/issue_templates",
+        ],
+    ),
+    (
+        "codeowners",
+        &[
+            "CODEOWNERS",
+            ".github/CODEOWNERS",
+            "docs/CODEOWNERS",
+            ".gitlab/CODEOWNERS",
+        ],
+    ),
+    ("funding", &[".github/FUNDING.yml", ".github/FUNDING.yaml"]),
+];
+
+/// The names of the changelog, both in the repository and in the released crate.
+const CHANGELOG_FILES: [&str; 5] = [
+    "CHANGELOG.md",
+    "CHANGELOG",
+    "CHANGES.md",
+    "HISTORY.md",
+    "RELEASES.md",
+];
+
+/// The name of each community file we found (see `COMMUNITY_FILES`) and its path relative to `root`.
+pub fn find_community_files(root: &Path) -> BTreeMap<String, String> {
+    COMMUNITY_FILES
+        .iter()
+        .filter_map(|file| {
+            let (name, candidates) = *file;
+            let path = candidates
+                .iter()
+                .find_map(|candidate| find_ignoring_case(root, candidate))?;
+            log::info!("community file: {name} {path}");
+            Some((name.to_owned(), path))
+        })
+        .collect()
+}
+
+/// The path of the changelog in the given folder relative to the folder.
+pub fn find_changelog(root: &Path) -> Option<String> {
+    CHANGELOG_FILES
+        .iter()
+        .find_map(|candidate| find_ignoring_case(root, candidate))
+}
+
+/// Check if the changelog has an entry for the given version, e.g. `## [1.2.3]` or `v1.2.3 - 2024-01-01`.
+/// `1.2.3` is not found in `1.2.30`, `11.2.3` or `1.2.3-beta.1`.
+pub fn changelog_mentions_version(content: &str, version: &str) -> bool {
+    if version.is_empty() {
+        return false;
+    }
+
+    content.match_indices(version).any(|(start, _)| {
+        let before = content
+            .get(..start)
+            .and_then(|text| text.chars().next_back());
+        let mut after = content
+            .get(start + version.len()..)
+            .unwrap_or_default()
+            .chars();
+        let next = after.next();
+        let following = after.next();
+
+        let starts_alone = before
+            .is_none_or(|chr| matches!(chr, 'v' | 'V') || !(chr.is_alphanumeric() || chr == '.'));
+        let ends_alone = match next {
+            None => true,
+            Some('.' | '-' | '+') => !following.is_some_and(|chr| chr.is_ascii_alphanumeric()),
+            Some(chr) => !chr.is_alphanumeric(),
+        };
+        starts_alone && ends_alone
+    })
+}
+
+/// Read the changelog in the given folder and check if it mentions the version.
+pub fn changelog_in_folder_mentions_version(root: &Path, changelog: &str, version: &str) -> bool {
+    fs::read_to_string(root.join(changelog))
+        .is_ok_and(|content| changelog_mentions_version(&content, version))
+}
+
+/// Find a file or folder where each part of the path is compared case-insensitively.
+fn find_ignoring_case(root: &Path, candidate: &str) -> Option<String> {
+    let mut found = String::new();
+    for part in candidate.split('/') {
+        let folder = root.join(&found);
+        let entry = fs::read_dir(folder).ok()?.flatten().find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(part))
+        })?;
+        if !found.is_empty() {
+            found.push('/');
+        }
+        found.push_str(&entry.file_name().to_string_lossy());
+    }
+    Some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_changelog_mentions_version() {
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n## [1.2.3] - 2024-01-01\n\n- Fix\n\n## v1.2.0\n\nFirst release of 0.9.x\n";
+        assert!(changelog_mentions_version(changelog, "1.2.3"));
+        assert!(changelog_mentions_version(changelog, "1.2.0"));
+        assert!(!changelog_mentions_version(changelog, "1.2"));
+        assert!(!changelog_mentions_version(changelog, "2.3"));
+        assert!(!changelog_mentions_version(changelog, "0.9"));
+        assert!(!changelog_mentions_version(changelog, ""));
+
+        assert!(!changelog_mentions_version("## 1.2.30", "1.2.3"));
+        assert!(!changelog_mentions_version("## 11.2.3", "1.2.3"));
+        assert!(!changelog_mentions_version("## 1.2.3-beta.1", "1.2.3"));
+        assert!(changelog_mentions_version("Version 1.2.3.", "1.2.3"));
+        assert!(changelog_mentions_version(
+            "## 1.2.3-beta.1",
+            "1.2.3-beta.1"
+        ));
+    }
+
+    #[test]
+    fn check_find_community_files() {
+        use tempdir::TempDir;
+        let tmp_dir = TempDir::new("community").unwrap();
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join(".github/ISSUE_TEMPLATE")).unwrap();
+        fs::write(root.join(".github/ISSUE_TEMPLATE/bug.md"), "").unwrap();
+        fs::write(root.join(".github/security.md"), "").unwrap();
+        fs::write(root.join("Changelog.md"), "").unwrap();
+
+        let files = find_community_files(root);
+        assert_eq!(
+            files.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("changelog".to_owned(), "Changelog.md".to_owned()),
+                (
+                    "issue-templates".to_owned(),
+                    ".github/ISSUE_TEMPLATE".to_owned()
+                ),
+                (
+                    "security-policy".to_owned(),
+                    ".github/security.md".to_owned()
+                ),
+            ]
+        );
+        assert_eq!(find_changelog(root), Some("Changelog.md".to_owned()));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use clap::Parser;
+
+use rust_digger::{
+    crates_root, get_repos_folder, load_cargo_toml_simplified, load_vcs_details,
+    parse_repository_url, read_crates, save_release_comparison, Crate, ElapsedTimer,
+    ReleaseComparison,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Limit the number of crates we process."
+    )]
+    limit: usize,
+}
+
+/// For each released crate we have locally
+///     find the clone of its repository, skip it if it is a shallow clone without history
+///     find the commit it was published from (`.cargo_vcs_info.json` or a tag matching the version)
+///     compare the files of the release with the files of that commit
+///     save the result.
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("Start comparing releases with the repositories.");
+    let start_time = std::time::Instant::now();
+
+    if let Err(err) = run() {
+        log::error!("Error: {err}");
+    }
+
+    log::info!("Elapsed time: {} sec.", start_time.elapsed().as_secs());
+    log::info!("End comparing releases");
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("compare_releases");
+
+    let args = Cli::parse();
+    log::info!("Limit: {}", args.limit);
+
+    let crates: Vec<Crate> = read_crates(0)?;
+    let repository_by_name = crates
+        .iter()
+        .map(|krate| (krate.name.as_str(), krate.repository.as_str()))
+        .collect::<HashMap<&str, &str>>();
+
+    compare_releases(&repository_by_name, args.limit)?;
+
+    Ok(())
+}
+
+fn compare_releases(
+    repository_by_name: &HashMap<&str, &str>,
+    limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut count: usize = 0;
+    let mut with_problems: usize = 0;
+    for entry in crates_root().read_dir()? {
+        if 0 < limit && limit <= count {
+            break;
+        }
+        let crate_path = entry?.path();
+
+        let (name, version) = match load_cargo_toml_simplified(&crate_path.join("Cargo.toml")) {
+            Ok(name_version) => name_version,
+            Err(err) => {
+                log::warn!(
+                    "Could not read Cargo.toml in {:?}: {err}",
+                    crate_path.display()
+                );
+                continue;
+            }
+        };
+
+        let Some(repository_url) = repository_by_name
+            .get(name.as_str())
+            .filter(|url| !url.is_empty())
+        else {
+            continue;
+        };
+        let repository = match parse_repository_url(repository_url) {
+            Ok(repository) => repository,
+            Err(err) => {
+                log::error!("Error: {err} when parsing the repository url of {name}");
+                continue;
+            }
+        };
+        let repo_path = repository.path(&get_repos_folder());
+        if !repo_path.exists() {
+            continue;
+        }
+
+        let vcs_details = load_vcs_details(repository_url);
+        // A shallow clone only has the last commit, the commit of the release would look missing.
+        // We keep the result of an earlier comparison with a clone that had the history.
+        if !vcs_details.clone_state.mode.has_history() {
+            log::info!("skipping {name} {version}, the clone of {repository_url} has no history");
+            continue;
+        }
+
+        log::info!("compare ({count}/{limit}) {name} {version} with {repository_url}");
+        let comparison = ReleaseComparison::compare(
+            &crate_path,
+            &repo_path,
+            &name,
+            &version,
+            vcs_details.packages.get(&name).map(String::as_str),
+        );
+        if comparison.has_problems() {
+            with_problems += 1;
+        }
+        save_release_comparison(&name, &version, &comparison)?;
+
+        count += 1;
+    }
+
+    log::info!("Compared {count} releases, {with_problems} of them differ from the repository");
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::path::Path;
+
+use clap::Parser;
+
+use rust_digger::{
+    for_each_cloned_repository, load_vcs_details, read_crates, save_details, CoverageReport, Crate,
+    ElapsedTimer, Runner,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of git repositories to measure the test coverage of."
+    )]
+    limit: u32,
+}
+
+/// For each repo load the details (if they already exist)
+///    If we have not measured the coverage of the current commit of the given repo then
+///          run `cargo llvm-cov` in the container
+///          save the line and function coverage of the repository and of each of its crates back to the details.
+fn main() {
+    let args = Cli::parse();
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("start measuring test coverage");
+
+    match measure_coverage(args.limit) {
+        Ok(()) => {}
+        Err(err) => log::error!("Error: {err}"),
+    }
+}
+
+fn measure_coverage(limit: u32) -> Result<(), Box<dyn Error>> {
+    log::info!("start measuring the test coverage of the repositories. limit {limit}.");
+    let _a = ElapsedTimer::new("coverage.rs");
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let rustc_version = runner.version("rustc");
+    log::info!("{rustc_version}");
+
+    let crates: Vec<Crate> = read_crates(0)?;
+    for_each_cloned_repository(&crates, limit, |repo| {
+        let mut details = load_vcs_details(&repo.krate.repository);
+        if details.coverage.is_up_to_date(&repo.commit) {
+            log::info!(
+                "coverage already measured on {} of {}",
+                repo.commit,
+                repo.krate.repository
+            );
+            return Ok(false);
+        }
+
+        log::info!("repo: {:?}", repo.path.display());
+        let mut report = match runner.run("coverage", &repo.path) {
+            Ok(result) => CoverageReport::from_job_result(&result, &details.packages),
+            Err(err) => {
+                log::error!("Could not run cargo llvm-cov: {err}");
+                let mut report = CoverageReport::new();
+                report.error = err.to_string();
+                report
+            }
+        };
+        log::info!(
+            "exit code: {:?} lines: {}% functions: {}%",
+            report.exit_code,
+            report.line_coverage,
+            report.function_coverage
+        );
+        report.commit.clone_from(&repo.commit);
+        report.rustc_version.clone_from(&rustc_version);
+        details.coverage = report;
+        save_details(&repo.krate.repository, &details)?;
+        Ok(true)
+    })
+}
@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cargo_messages::{compiler_messages, Diagnostic};
+use crate::{percentage, JobResult, SOURCE_MOUNT, WORK_MOUNT};
+
+/// How many lines of the error output we keep if we could not collect the coverage.
+const ERROR_LINES: usize = 5;
+
+/// The test coverage of a repository according to `cargo llvm-cov`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The commit we measured (`git rev-parse HEAD`), empty if we have not measured the coverage yet.
+    pub commit: String,
+
+    pub checked_at: String,
+
+    /// The output of `rustc --version` in the container.
+    pub rustc_version: String,
+
+    /// `None` if the tests timed out or could not be run at all.
+    pub exit_code: Option<i32>,
+
+    /// We found the coverage summary in the output, even if some of the tests failed.
+    pub measured: bool,
+
+    pub lines: usize,
+    pub covered_lines: usize,
+    /// The percentage of the covered lines, e.g. `76.54`.
+    pub line_coverage: String,
+
+    pub functions: usize,
+    pub covered_functions: usize,
+    pub function_coverage: String,
+
+    pub duration_secs: u64,
+
+    /// The first compiler error or the first lines of the error output if there is no coverage summary.
+    pub error: String,
+
+    /// The coverage of each crate of the repository, the sum of the files in the folder of the crate.
+    /// The key is the name of the crate, see `VCSDetails::packages`.
+    #[serde(default = "BTreeMap::new")]
+    pub crates: BTreeMap<String, CrateCoverage>,
+}
+
+/// The test coverage of one crate of a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CrateCoverage {
+    pub lines: usize,
+    pub covered_lines: usize,
+    /// The percentage of the covered lines, e.g. `76.54`.
+    pub line_coverage: String,
+
+    pub functions: usize,
+    pub covered_functions: usize,
+    pub function_coverage: String,
+}
+
+/// The part of the output of `cargo llvm-cov --json` we use.
+#[derive(Debug, Deserialize)]
+struct LlvmCovExport {
+    data: Vec<LlvmCovData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlvmCovData {
+    #[serde(default)]
+    files: Vec<LlvmCovFile>,
+    totals: LlvmCovTotals,
+}
+
+/// The coverage of a single source file, the `filename` is the absolute path in the container.
+#[derive(Debug, Deserialize)]
+struct LlvmCovFile {
+    filename: String,
+    summary: LlvmCovTotals,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlvmCovTotals {
+    lines: LlvmCovSummary,
+    functions: LlvmCovSummary,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlvmCovSummary {
+    count: usize,
+    covered: usize,
+}
+
+impl CoverageReport {
+    pub const fn new() -> Self {
+        Self {
+            commit: String::new(),
+            checked_at: String::new(),
+            rustc_version: String::new(),
+            exit_code: None,
+            measured: false,
+            lines: 0,
+            covered_lines: 0,
+            line_coverage: String::new(),
+            functions: 0,
+            covered_functions: 0,
+            function_coverage: String::new(),
+            duration_secs: 0,
+            error: String::new(),
+            crates: BTreeMap::new(),
+        }
+    }
+
+    /// We have already measured this commit.
+    pub fn is_up_to_date(&self, commit: &str) -> bool {
+        !commit.is_empty() && self.commit == commit
+    }
+
+    /// Collect the coverage from the result of the `coverage` job, see `runner.yaml`.
+    /// `packages` maps the name of each crate in the repository to its folder, see `VCSDetails::packages`.
+    pub fn from_job_result(result: &JobResult, packages: &BTreeMap<String, String>) -> Self {
+        let mut report = Self::new();
+        report.checked_at.clone_from(&result.started_at);
+        report.exit_code = result.exit_code;
+        report.duration_secs = result.duration_secs;
+        report.parse_summary(&result.stdout, packages);
+
+        if !report.measured {
+            report.error = compiler_messages(&result.stdout)
+                .into_iter()
+                .find(Diagnostic::is_compiler_error)
+                .map_or_else(
+                    || {
+                        result
+                            .stderr
+                            .lines()
+                            .filter(|line| !line.trim().is_empty())
+                            .take(ERROR_LINES)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    },
+                    |diagnostic| diagnostic.message,
+                );
+        }
+        report
+    }
+
+    /// Find the JSON export of llvm-cov among the other lines of the output (e.g. the output of the tests)
+    /// and take the totals of the repository and the sum of the files of each crate from it.
+    pub fn parse_summary(&mut self, stdout: &str, packages: &BTreeMap<String, String>) {
+        let Some(data) = stdout
+            .lines()
+            .filter(|line| line.starts_with('{'))
+            .filter_map(|line| serde_json::from_str::<LlvmCovExport>(line).ok())
+            .find_map(|export| export.data.into_iter().next())
+        else {
+            return;
+        };
+
+        self.measured = true;
+        self.lines = data.totals.lines.count;
+        self.covered_lines = data.totals.lines.covered;
+        self.line_coverage = coverage_percentage(self.covered_lines, self.lines);
+        self.functions = data.totals.functions.count;
+        self.covered_functions = data.totals.functions.covered;
+        self.function_coverage = coverage_percentage(self.covered_functions, self.functions);
+
+        self.crates.clear();
+        for file in &data.files {
+            let Some(name) = crate_of_file(&file.filename, packages) else {
+                continue;
+            };
+            let krate = self.crates.entry(name.to_owned()).or_default();
+            krate.lines += file.summary.lines.count;
+            krate.covered_lines += file.summary.lines.covered;
+            krate.functions += file.summary.functions.count;
+            krate.covered_functions += file.summary.functions.covered;
+        }
+        for krate in self.crates.values_mut() {
+            krate.line_coverage = coverage_percentage(krate.covered_lines, krate.lines);
+            krate.function_coverage = coverage_percentage(krate.covered_functions, krate.functions);
+        }
+    }
+}
+
+impl CrateCoverage {
+    pub const fn new() -> Self {
+        Self {
+            lines: 0,
+            covered_lines: 0,
+            line_coverage: String::new(),
+            functions: 0,
+            covered_functions: 0,
+            function_coverage: String::new(),
+        }
+    }
+}
+
+impl Default for CrateCoverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The crate whose folder contains the file, the innermost one if the folders are nested, e.g. in a workspace
+/// with a crate in the root. The files outside of the repository (e.g. in the registry) belong to no crate.
+fn crate_of_file<'packages>(
+    filename: &str,
+    packages: &'packages BTreeMap<String, String>,
+) -> Option<&'packages str> {
+    let relative = [WORK_MOUNT, SOURCE_MOUNT]
+        .into_iter()
+        .find_map(|mount| filename.strip_prefix(&format!("{mount}/")))?;
+    packages
+        .iter()
+        .filter(|&(_, folder)| folder == "." || relative.starts_with(&format!("{folder}/")))
+        .max_by_key(|&(_, folder)| if folder == "." { 0 } else { folder.len() })
+        .map(|(name, _)| name.as_str())
+}
+
+impl Default for CoverageReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn coverage_percentage(covered: usize, count: usize) -> String {
+    if count == 0 {
+        return String::from("0");
+    }
+    percentage(covered, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_summary() {
+        let stdout = [
+            r#"{"reason":"compiler-artifact","package_id":"foo 0.1.0"}"#,
+            "running 2 tests",
+            "test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s",
+            r#"{"data":[{"files":[],"totals":{"branches":{"count":0,"covered":0,"notcovered":0,"percent":0},"functions":{"count":8,"covered":6,"percent":75},"instantiations":{"count":8,"covered":6,"percent":75},"lines":{"count":200,"covered":153,"percent":76.5},"regions":{"count":120,"covered":90,"notcovered":30,"percent":75}}}],"type":"llvm.coverage.json.export","version":"2.0.1"}"#,
+        ]
+        .join("\n");
+
+        let mut report = CoverageReport::new();
+        report.parse_summary(&stdout, &BTreeMap::new());
+        assert!(report.measured);
+        assert_eq!(report.lines, 200);
+        assert_eq!(report.covered_lines, 153);
+        assert_eq!(report.line_coverage, "76.5");
+        assert_eq!(report.functions, 8);
+        assert_eq!(report.function_coverage, "75");
+
+        let mut empty = CoverageReport::new();
+        empty.parse_summary("error: could not compile `foo`", &BTreeMap::new());
+        assert!(!empty.measured);
+        assert_eq!(empty.line_coverage, "");
+    }
+
+    #[test]
+    fn check_parse_summary_per_crate() {
+        let file = |filename: &str, lines: usize, covered: usize| {
+            format!(
+                r#"{{"filename":"{filename}","summary":{{"functions":{{"count":2,"covered":1,"percent":50}},"lines":{{"count":{lines},"covered":{covered},"percent":0}}}}}}"#
+            )
+        };
+        let files = [
+            file("/work/src/main.rs", 100, 90),
+            file("/work/crates/foo/src/lib.rs", 60, 30),
+            file("/work/crates/foo/src/parser.rs", 40, 10),
+            file("/work/crates/foobar/src/lib.rs", 10, 10),
+            file("/registry/serde-1.0.0/src/lib.rs", 1000, 0),
+        ]
+        .join(",");
+        let stdout = format!(
+            r#"{{"data":[{{"files":[{files}],"totals":{{"functions":{{"count":10,"covered":5,"percent":50}},"lines":{{"count":1210,"covered":140,"percent":11.57}}}}}}],"type":"llvm.coverage.json.export","version":"2.0.1"}}"#
+        );
+        let packages = BTreeMap::from([
+            (String::from("app"), String::from(".")),
+            (String::from("foo"), String::from("crates/foo")),
+            (String::from("foobar"), String::from("crates/foobar")),
+        ]);
+
+        let mut report = CoverageReport::new();
+        report.parse_summary(&stdout, &packages);
+        assert!(report.measured);
+        assert_eq!(report.lines, 1210);
+        assert_eq!(report.crates.len(), 3);
+
+        let app = &report.crates["app"];
+        assert_eq!((app.lines, app.covered_lines), (100, 90));
+        assert_eq!(app.line_coverage, "90");
+
+        let foo = &report.crates["foo"];
+        assert_eq!((foo.lines, foo.covered_lines), (100, 40));
+        assert_eq!(foo.line_coverage, "40");
+        assert_eq!((foo.functions, foo.covered_functions), (4, 2));
+        assert_eq!(foo.function_coverage, "50");
+
+        assert_eq!(report.crates["foobar"].line_coverage, "100");
+    }
+}
@@ -0,0 +1,132 @@
+use core::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::get_data_folder;
+
+/// The space used by the clones of a host or of an owner.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SpaceUsed {
+    /// The host, e.g. `github.com`, or the host and the owner, e.g. `github.com/foo`.
+    pub name: String,
+    pub size: u64,
+    pub clones: usize,
+}
+
+/// The space used by the clones of the repositories, the largest hosts and owners first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DiskUsage {
+    pub generated_at: String,
+    pub size: u64,
+    pub clones: usize,
+    pub hosts: Vec<SpaceUsed>,
+    pub owners: Vec<SpaceUsed>,
+}
+
+impl DiskUsage {
+    /// Summarize the sizes of the clones given as (host, owner, size).
+    pub fn new(generated_at: &str, clones: &[(String, String, u64)]) -> Self {
+        let mut hosts: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+        let mut owners: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+        for clone in clones {
+            let host = hosts.entry(clone.0.clone()).or_default();
+            host.0 += clone.2;
+            host.1 += 1;
+            let owner = owners
+                .entry(format!("{}/{}", clone.0, clone.1))
+                .or_default();
+            owner.0 += clone.2;
+            owner.1 += 1;
+        }
+
+        Self {
+            generated_at: generated_at.to_owned(),
+            size: clones.iter().map(|clone| clone.2).sum(),
+            clones: clones.len(),
+            hosts: largest_first(hosts),
+            owners: largest_first(owners),
+        }
+    }
+}
+
+fn largest_first(sizes: BTreeMap<String, (u64, usize)>) -> Vec<SpaceUsed> {
+    let mut used = sizes
+        .into_iter()
+        .map(|(name, (size, clones))| SpaceUsed { name, size, clones })
+        .collect::<Vec<_>>();
+    used.sort_by_key(|space| Reverse(space.size));
+    used
+}
+
+fn disk_usage_path() -> PathBuf {
+    get_data_folder().join("repos-disk-usage.json")
+}
+
+pub fn load_disk_usage() -> DiskUsage {
+    let filepath = disk_usage_path();
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return DiskUsage::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        DiskUsage::default()
+    })
+}
+
+pub fn save_disk_usage(usage: &DiskUsage) -> Result<(), Box<dyn Error>> {
+    let content = serde_json::to_string_pretty(usage)?;
+    fs::write(disk_usage_path(), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_disk_usage() {
+        let clone = |host: &str, owner: &str, size: u64| (host.to_owned(), owner.to_owned(), size);
+        let usage = DiskUsage::new(
+            "2024-01-01T00:00:00+00:00",
+            &[
+                clone("github.com", "foo", 10),
+                clone("github.com", "bar", 30),
+                clone("github.com", "foo", 5),
+                clone("gitlab.com", "foo", 1),
+            ],
+        );
+        assert_eq!(usage.size, 46);
+        assert_eq!(usage.clones, 4);
+        assert_eq!(
+            usage.hosts,
+            vec![
+                SpaceUsed {
+                    name: String::from("github.com"),
+                    size: 45,
+                    clones: 3
+                },
+                SpaceUsed {
+                    name: String::from("gitlab.com"),
+                    size: 1,
+                    clones: 1
+                },
+            ]
+        );
+        assert_eq!(
+            usage
+                .owners
+                .iter()
+                .map(|owner| (owner.name.as_str(), owner.size, owner.clones))
+                .collect::<Vec<_>>(),
+            vec![
+                ("github.com/bar", 30, 1),
+                ("github.com/foo", 15, 2),
+                ("gitlab.com/foo", 1, 1)
+            ]
+        );
+    }
+}
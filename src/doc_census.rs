@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use rust_digger::{
+    analyzed_crates_root, crates_root, has_library, load_crate_details, CrateDetails, DocCoverage,
+    ElapsedTimer, Runner,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Limit the number of crates we process."
+    )]
+    limit: usize,
+}
+
+/// For each released crate we have locally that has a library
+///     skip it if we already ran rustdoc on it
+///     run `cargo +nightly rustdoc` with the JSON output in the container
+///     count the public items and how many of them are documented
+///     save the result in the details of the crate.
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("Start measuring the documentation of the released crates.");
+    let start_time = std::time::Instant::now();
+
+    if let Err(err) = run() {
+        log::error!("Error: {err}");
+    }
+
+    log::info!("Elapsed time: {} sec.", start_time.elapsed().as_secs());
+    log::info!("End measuring the documentation");
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("doc_census");
+
+    let args = Cli::parse();
+    log::info!("Limit: {}", args.limit);
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let rustdoc_version = runner.version("rustc +nightly");
+
+    let mut count: usize = 0;
+    for entry in crates_root().read_dir()? {
+        if 0 < args.limit && args.limit <= count {
+            break;
+        }
+        let crate_path = entry?.path();
+        if !has_library(&crate_path) {
+            continue;
+        }
+
+        let Some(crate_dirname) = crate_path.file_name() else {
+            continue;
+        };
+        // can't use set_extension as there are dots in the names and this would remove them
+        let filepath = PathBuf::from(format!(
+            "{}.json",
+            analyzed_crates_root().join(crate_dirname).display()
+        ));
+        let mut details = load_crate_details(&filepath).unwrap_or_else(|_| CrateDetails::new());
+        if !details.doc_coverage.rustdoc_version.is_empty() {
+            continue;
+        }
+
+        log::info!(
+            "rustdoc ({count}/{}) {}",
+            args.limit,
+            crate_dirname.display()
+        );
+        details.doc_coverage = match runner.run("rustdoc", &crate_path) {
+            Ok(result) => DocCoverage::from_job_result(&result),
+            Err(err) => {
+                log::error!("Could not run rustdoc: {err}");
+                let mut coverage = DocCoverage::new();
+                coverage.error = err.to_string();
+                coverage
+            }
+        };
+        details
+            .doc_coverage
+            .rustdoc_version
+            .clone_from(&rustdoc_version);
+        log::info!(
+            "public items: {} documented: {}%",
+            details.doc_coverage.public_items,
+            details.doc_coverage.doc_percentage
+        );
+        details.save(filepath)?;
+
+        count += 1;
+    }
+
+    log::info!("Ran rustdoc on {count} crates");
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::rustdoc_json::{id_key, item_kind};
+use crate::{percentage, JobResult};
+
+/// How many lines of the error output we keep if rustdoc failed.
+const ERROR_LINES: usize = 5;
+
+/// How many of the undocumented public items we list.
+const UNDOCUMENTED_ITEMS: usize = 10;
+
+/// Kinds of items that are public but are not part of the documentation on their own, e.g. re-exports.
+const SKIPPED_KINDS: [&str; 4] = ["use", "import", "impl", "extern_crate"];
+
+/// How well the public API of a released crate is documented, according to the JSON output of rustdoc.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DocCoverage {
+    pub checked_at: String,
+
+    /// The output of `rustc +nightly --version` in the container, empty if we have not run rustdoc.
+    pub rustdoc_version: String,
+
+    /// `None` if rustdoc timed out or could not be run at all.
+    pub exit_code: Option<i32>,
+
+    /// We could parse the JSON output of rustdoc.
+    pub measured: bool,
+
+    pub public_items: usize,
+    pub documented_items: usize,
+    /// The percentage of the documented public items, e.g. `87.5`.
+    pub doc_percentage: String,
+
+    /// The number of public items with a code block in their documentation.
+    pub items_with_examples: usize,
+
+    /// The crate itself has documentation (`//!` in `lib.rs`).
+    pub has_crate_docs: bool,
+
+    /// The first few undocumented public items, e.g. `function parse`.
+    pub undocumented: Vec<String>,
+
+    pub error: String,
+}
+
+impl DocCoverage {
+    pub const fn new() -> Self {
+        Self {
+            checked_at: String::new(),
+            rustdoc_version: String::new(),
+            exit_code: None,
+            measured: false,
+            public_items: 0,
+            documented_items: 0,
+            doc_percentage: String::new(),
+            items_with_examples: 0,
+            has_crate_docs: false,
+            undocumented: vec![],
+            error: String::new(),
+        }
+    }
+
+    /// Collect the documentation coverage from the result of the `rustdoc` job, see `runner.yaml`.
+    pub fn from_job_result(result: &JobResult) -> Self {
+        let mut coverage = Self::new();
+        coverage.checked_at.clone_from(&result.started_at);
+        coverage.exit_code = result.exit_code;
+
+        if result.succeeded() {
+            match serde_json::from_str::<Value>(&result.stdout) {
+                Ok(rustdoc) => coverage.count_items(&rustdoc),
+                Err(err) => {
+                    coverage.error = format!("Could not parse the output of rustdoc: {err}");
+                }
+            }
+        } else {
+            coverage.error = result
+                .stderr
+                .lines()
+                .filter(|line| line.trim_start().starts_with("error"))
+                .take(ERROR_LINES)
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        coverage
+    }
+
+    /// Count the public items of the crate and how many of them are documented.
+    /// We only rely on the fields of the rustdoc JSON format that have not changed in a long time:
+    /// `root`, `index` and the `crate_id`, `name`, `visibility`, `docs` and `inner` of the items.
+    pub fn count_items(&mut self, rustdoc: &Value) {
+        let Some(index) = rustdoc.get("index").and_then(Value::as_object) else {
+            self.error = String::from("No index in the output of rustdoc");
+            return;
+        };
+        let root = rustdoc.get("root").map(id_key).unwrap_or_default();
+
+        let mut items = index
+            .iter()
+            .filter(|entry| entry.1.get("crate_id").and_then(Value::as_u64) == Some(0))
+            .filter(|entry| entry.1.get("visibility").and_then(Value::as_str) == Some("public"))
+            .filter(|entry| item_kind(entry.1).is_some_and(|kind| !SKIPPED_KINDS.contains(&kind)))
+            .collect::<Vec<_>>();
+        // the order of the index is not stable
+        items.sort_by_key(|entry| entry.0);
+
+        self.measured = true;
+        self.public_items = items.len();
+        for (id, item) in items {
+            let docs = item
+                .get("docs")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .trim();
+            if docs.is_empty() {
+                if self.undocumented.len() < UNDOCUMENTED_ITEMS {
+                    let name = item.get("name").and_then(Value::as_str).unwrap_or("?");
+                    self.undocumented
+                        .push(format!("{} {name}", item_kind(item).unwrap_or_default()));
+                }
+                continue;
+            }
+            self.documented_items += 1;
+            if has_example(docs) {
+                self.items_with_examples += 1;
+            }
+            if *id == root {
+                self.has_crate_docs = true;
+            }
+        }
+        self.doc_percentage = if self.public_items == 0 {
+            String::from("0")
+        } else {
+            percentage(self.documented_items, self.public_items)
+        };
+    }
+}
+
+impl Default for DocCoverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The documentation has a code block, e.g. `` ```rust `` or `` ``` ``.
+fn has_example(docs: &str) -> bool {
+    static CODE_BLOCK: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)^\s*(```|~~~)").expect("valid regex"));
+    CODE_BLOCK.is_match(docs)
+}
+
+/// The level of the `missing_docs` lint set in `lib.rs` (e.g. `#![deny(missing_docs)]`)
+/// or in the `[lints.rust]` section of `Cargo.toml`.
+pub fn missing_docs_lint(lib_rs: &str, cargo_toml: &str) -> Option<String> {
+    static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"#!\[\s*(allow|warn|deny|forbid)\s*\([^)]*\bmissing_docs\b")
+            .expect("valid regex")
+    });
+    static LINTS: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?m)^\s*missing_docs\s*=\s*(?:"(\w+)"|\{[^}]*\blevel\s*=\s*"(\w+)")"#)
+            .expect("valid regex")
+    });
+
+    if let Some(level) = ATTRIBUTE
+        .captures(lib_rs)
+        .and_then(|captures| captures.get(1))
+    {
+        return Some(level.as_str().to_owned());
+    }
+    let captures = LINTS.captures(cargo_toml)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map(|level| level.as_str().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_count_items() {
+        let rustdoc = serde_json::json!({
+            "root": 0,
+            "crate_version": "0.1.0",
+            "index": {
+                "0": {"crate_id": 0, "name": "foo", "visibility": "public", "docs": "The foo crate.", "inner": {"module": {}}},
+                "1": {"crate_id": 0, "name": "parse", "visibility": "public", "docs": "Parse.\n\n```\nfoo::parse(\"\");\n```", "inner": {"function": {}}},
+                "2": {"crate_id": 0, "name": "Config", "visibility": "public", "docs": null, "inner": {"struct": {}}},
+                "3": {"crate_id": 0, "name": "helper", "visibility": "crate", "docs": null, "inner": {"function": {}}},
+                "4": {"crate_id": 0, "name": null, "visibility": "default", "docs": null, "inner": {"impl": {}}},
+                "5": {"crate_id": 0, "name": "Map", "visibility": "public", "docs": null, "inner": {"use": {}}},
+                "6": {"crate_id": 1, "name": "String", "visibility": "public", "docs": "A string.", "inner": {"struct": {}}},
+            },
+        });
+
+        let mut coverage = DocCoverage::new();
+        coverage.count_items(&rustdoc);
+        assert!(coverage.measured);
+        assert_eq!(coverage.public_items, 3);
+        assert_eq!(coverage.documented_items, 2);
+        assert_eq!(coverage.doc_percentage, "66.66");
+        assert_eq!(coverage.items_with_examples, 1);
+        assert!(coverage.has_crate_docs);
+        assert_eq!(coverage.undocumented, vec!["struct Config"]);
+    }
+
+    #[test]
+    fn check_missing_docs_lint() {
+        assert_eq!(
+            missing_docs_lint("//! Docs\n#![deny(missing_docs)]\n", ""),
+            Some(String::from("deny"))
+        );
+        assert_eq!(
+            missing_docs_lint("#![warn(unsafe_code, missing_docs)]", ""),
+            Some(String::from("warn"))
+        );
+        assert_eq!(
+            missing_docs_lint("", "[lints.rust]\nmissing_docs = \"forbid\"\n"),
+            Some(String::from("forbid"))
+        );
+        assert_eq!(
+            missing_docs_lint(
+                "",
+                "[lints.rust]\nmissing_docs = { level = \"deny\", priority = 1 }\n"
+            ),
+            Some(String::from("deny"))
+        );
+        assert_eq!(
+            missing_docs_lint("#![deny(missing_debug_implementations)]", ""),
+            None
+        );
+    }
+}
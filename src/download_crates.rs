@@ -4,6 +4,7 @@ use std::collections::HashSet;
 use std::error::Error;
 use std::ffi::OsString;
 use std::fs;
+use std::path::Path;
 
 use clap::Parser;
 use flate2::read::GzDecoder;
@@ -13,8 +14,9 @@ use tempdir::TempDir;
 use thousands::Separable as _;
 
 use rust_digger::{
-    crates_root, create_data_folders, get_temp_folder, read_crates, read_versions, Crate,
-    CrateVersion,
+    crates_root, create_data_folders, get_temp_folder, previous_crates_root,
+    quarantine_crates_without_checksum, read_crates, read_versions, Crate, CrateVersion,
+    CARGO_CHECKSUM_FILE,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -28,6 +30,13 @@ struct Cli {
         help = "Limit the number of crates to download."
     )]
     limit: u32,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Also download the release before the newest one so we can compare their public API."
+    )]
+    previous: bool,
 }
 
 fn main() {
@@ -54,6 +63,7 @@ fn run() -> Result<(), Box<dyn Error>> {
     }
 
     create_data_folders()?;
+    quarantine_crates_without_checksum()?;
 
     let crates: Vec<Crate> = read_crates(0)?;
     let versions: Vec<CrateVersion> = read_versions()?;
@@ -63,13 +73,16 @@ fn run() -> Result<(), Box<dyn Error>> {
         versions.len().separate_with_commas()
     );
 
-    let (newest_crates, downloaded_count, downloaded_total) =
-        download_crates(&crates, &versions, args.limit)?;
+    let (newest_crates, previous_crates, downloaded_count, downloaded_total) =
+        download_crates(&crates, &versions, args.limit, args.previous)?;
 
     // If the limit is not 0 we don't have all the crates in the newest_crates HashSet so we should not remove the old versions based on that.
     // TODO: have a set that contains all the newest crates and then remove the old versions based on that.
     if args.limit == 0 {
-        remove_old_versions_of_the_crates(&newest_crates)?;
+        remove_old_versions_of_the_crates(&crates_root(), &newest_crates)?;
+        if args.previous {
+            remove_old_versions_of_the_crates(&previous_crates_root(), &previous_crates)?;
+        }
     }
 
     let crate_folders = crates_root().read_dir()?.flatten().count();
@@ -88,15 +101,19 @@ fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-/// Go over the downloaded crates on disk.
-/// Check each one of them of it is in the `HashSet` of most recent crates.
+/// Go over the downloaded crates on disk in the given folder.
+/// Check each one of them of it is in the `HashSet` of the versions we keep.
 /// Remove the ones that are not there.
 fn remove_old_versions_of_the_crates(
+    root: &Path,
     newest_versions: &HashSet<OsString>,
 ) -> Result<(), Box<dyn Error>> {
-    log::info!("start remove_old_versions_of_the_crates");
+    log::info!(
+        "start remove_old_versions_of_the_crates in {:?}",
+        root.display()
+    );
 
-    for entry in crates_root().read_dir()?.flatten() {
+    for entry in root.read_dir()?.flatten() {
         log::info!("entry: {:?}", entry.file_name().display());
 
         if !newest_versions.contains(&entry.file_name()) {
@@ -113,16 +130,22 @@ fn remove_old_versions_of_the_crates(
     Ok(())
 }
 
+/// The names of the `name-version` folders we keep on disk.
+type KeptVersions = HashSet<OsString>;
+
 /// Download the crates from crates.io and extract them to the `crates_root` folder.
-/// Returns a tuple with the set of newest versions and the total size downloaded in bytes.
+/// If `previous` is set, also download the release before the newest one to the `previous_crates_root` folder.
+/// Returns a tuple with the set of newest versions, the set of previous versions, the number of downloaded files and the total size downloaded in bytes.
 fn download_crates(
     crates: &[Crate],
     versions: &[CrateVersion],
     limit: u32,
-) -> Result<(HashSet<OsString>, u32, u64), Box<dyn Error>> {
+    previous: bool,
+) -> Result<(KeptVersions, KeptVersions, u32, u64), Box<dyn Error>> {
     log::info!("start update repositories");
 
     let mut newest_versions: HashSet<OsString> = HashSet::new();
+    let mut previous_versions: HashSet<OsString> = HashSet::new();
 
     // TODO maybe we should not include the versions that are not in the standard format e.g. only accept  0.3.0 and not  0.3.0-beta-dev.30 ?
     let mut latest: HashMap<String, CrateVersion> = HashMap::new();
@@ -139,6 +162,12 @@ fn download_crates(
         }
     }
 
+    let before_latest = if previous {
+        releases_before_latest(versions, &latest)
+    } else {
+        HashMap::new()
+    };
+
     let mut count = 0;
     let mut total = 0;
     for krate in crates {
@@ -154,27 +183,24 @@ fn download_crates(
             krate.id
         );
 
-        let krate_name_version = format!("{}-{}", krate.name, latest[&krate.id].num);
-        newest_versions.insert(OsString::from(&krate_name_version));
-
-        let folder = crates_root().join(krate_name_version);
-        log::info!("Checking {:?}", folder.display());
-
-        if folder.exists() {
-            log::info!("{:?} already exists. Skipping download", folder.display());
-            continue;
+        let mut wanted = vec![(&latest[&krate.id], crates_root(), &mut newest_versions)];
+        if let Some(version) = before_latest.get(&krate.id) {
+            wanted.push((version, previous_crates_root(), &mut previous_versions));
         }
 
-        // "https://crates.io/api/v1/crates/serde/1.0.0/download
-        let url = format!(
-            "https://crates.io/api/v1/crates/{}/{}/download",
-            krate.name, latest[&krate.id].num
-        );
+        for (version, root, kept_versions) in wanted {
+            let krate_name_version = format!("{}-{}", krate.name, version.num);
+            kept_versions.insert(OsString::from(&krate_name_version));
 
-        log::info!("downloading url {url}");
+            let folder = root.join(krate_name_version);
+            log::info!("Checking {:?}", folder.display());
 
-        match download_crate(&url) {
-            Ok((downloaded_file, size)) => {
+            if folder.exists() {
+                log::info!("{:?} already exists. Skipping download", folder.display());
+                continue;
+            }
+
+            if let Some(size) = download_version(&krate.name, version, &root) {
                 count += 1;
                 total += size;
                 log::info!(
@@ -183,21 +209,67 @@ fn download_crates(
                     count.separate_with_commas(),
                     total.separate_with_commas()
                 );
-                match extract_file(&downloaded_file) {
-                    Ok(filename) => log::info!("extracted {:?}", filename.display()),
-                    Err(err) => log::error!("{err} {url}"),
-                }
-
-                match std::fs::remove_file(&downloaded_file) {
-                    Ok(()) => log::info!("file {:?} removed", downloaded_file.display()),
-                    Err(err) => log::error!("{err}"),
-                }
             }
-            Err(err) => log::error!("{err}"),
         }
     }
 
-    Ok((newest_versions, count, total))
+    Ok((newest_versions, previous_versions, count, total))
+}
+
+/// The newest release of each crate that was not yanked and was published before the newest one.
+fn releases_before_latest(
+    versions: &[CrateVersion],
+    latest: &HashMap<String, CrateVersion>,
+) -> HashMap<String, CrateVersion> {
+    let mut before_latest: HashMap<String, CrateVersion> = HashMap::new();
+    for version in versions {
+        if version.yanked == "t"
+            || version
+                .created_at
+                .cmp(&latest[&version.crate_id].created_at)
+                != Ordering::Less
+        {
+            continue;
+        }
+        if before_latest
+            .get(&version.crate_id)
+            .is_none_or(|current_version| current_version.created_at < version.created_at)
+        {
+            before_latest.insert(version.crate_id.clone(), version.clone());
+        }
+    }
+    before_latest
+}
+
+/// Download one release of a crate and extract it to the `root` folder.
+/// Returns the size of the downloaded file.
+fn download_version(name: &str, version: &CrateVersion, root: &Path) -> Option<u64> {
+    // "https://crates.io/api/v1/crates/serde/1.0.0/download
+    let url = format!(
+        "https://crates.io/api/v1/crates/{name}/{}/download",
+        version.num
+    );
+
+    log::info!("downloading url {url}");
+
+    match download_crate(&url) {
+        Ok((downloaded_file, size)) => {
+            match extract_file(&downloaded_file, root, &version.checksum) {
+                Ok(filename) => log::info!("extracted {:?}", filename.display()),
+                Err(err) => log::error!("{err} {url}"),
+            }
+
+            match std::fs::remove_file(&downloaded_file) {
+                Ok(()) => log::info!("file {:?} removed", downloaded_file.display()),
+                Err(err) => log::error!("{err}"),
+            }
+            Some(size)
+        }
+        Err(err) => {
+            log::error!("{err}");
+            None
+        }
+    }
 }
 
 fn download_crate(url: &str) -> Result<(std::path::PathBuf, u64), Box<dyn Error>> {
@@ -231,7 +303,13 @@ fn download_crate(url: &str) -> Result<(std::path::PathBuf, u64), Box<dyn Error>
     Ok((download_file, total))
 }
 
-fn extract_file(file: &std::path::PathBuf) -> Result<OsString, Box<dyn Error>> {
+/// Extract the crate to the `root` folder with the checksum file Cargo needs to use the folder as a vendored registry.
+/// We take the checksum of the crate file from the database dump.
+fn extract_file(
+    file: &std::path::PathBuf,
+    root: &Path,
+    checksum: &str,
+) -> Result<OsString, Box<dyn Error>> {
     let tar_gz = fs::File::open(file)?;
     let tar = GzDecoder::new(tar_gz);
     let tmp_dir = TempDir::new_in(get_temp_folder(), "example")?;
@@ -246,10 +324,12 @@ fn extract_file(file: &std::path::PathBuf) -> Result<OsString, Box<dyn Error>> {
     log::info!("extract dir: {extracted_dir:?}");
     log::info!("extract filename {:?}", extracted_dir.file_name().display());
 
-    fs::rename(
-        extracted_dir.path(),
-        crates_root().join(extracted_dir.file_name()),
+    let content = serde_json::json!({"files": {}, "package": checksum});
+    fs::write(
+        extracted_dir.path().join(CARGO_CHECKSUM_FILE),
+        content.to_string(),
     )?;
+    fs::rename(extracted_dir.path(), root.join(extracted_dir.file_name()))?;
 
     Ok(extracted_dir.file_name())
 }
@@ -1,10 +1,15 @@
-use std::env;
+use std::error::Error;
 use std::path::Path;
-use std::process::Command;
 
 use clap::Parser;
 
-use rust_digger::get_repos_folder;
+use rust_digger::{
+    for_each_cloned_repository, load_vcs_details, read_crates, save_details, Crate, ElapsedTimer,
+    FmtReport, Runner, SOURCE_MOUNT,
+};
+
+/// How many lines of the error output we keep if `cargo fmt` fails.
+const ERROR_LINES: usize = 5;
 
 #[derive(Parser, Debug)]
 #[command(version)]
@@ -17,116 +22,88 @@ struct Cli {
     limit: u32,
 }
 
-/// For each repo run cargo fmt
+/// For each repo run cargo fmt.
 ///
 /// For each repo load the details (if they already exist)
-///    If we have not ran fmt on the given repo then
+///    If we have not ran fmt on the current commit of the given repo then
 ///          run fmt
-///          save the results back to the details
+///          save the results back to the details.
 fn main() {
     let args = Cli::parse();
     simple_logger::init_with_level(log::Level::Info).unwrap();
-    log::info!("start updating git repositories");
-    run_cargo_fmt(args.limit);
-}
-
-fn run_cargo_fmt(limit: u32) {
-    log::info!("start update repositories. limit {limit}.");
-
-    build_docker_image();
-    let mut count: u32 = 0;
-    let path = get_repos_folder();
-    for host in path.read_dir().expect("read_dir call failed").flatten() {
-        log::info!("host: {:?}", host.path().display());
-        for user in host
-            .path()
-            .read_dir()
-            .expect("read_dir call failed")
-            .flatten()
-        {
-            log::info!("user: {:?}", user.path().display());
-            for repo in user.path().read_dir().expect("read_dir call failed") {
-                if 0 < limit && limit <= count {
-                    return;
-                }
+    log::info!("start running cargo fmt");
 
-                if let Ok(repository) = repo {
-                    count += 1;
-                    log::info!("repo {}: {:?}", count, repository.path().display());
-
-                    let root_dir = env::current_dir().unwrap();
-                    env::set_current_dir(repository.path()).unwrap();
-
-                    if !run_fmt_on(repository.path().to_str().unwrap()) {
-                        count -= 1;
-                    }
-
-                    env::set_current_dir(&root_dir).unwrap();
-                }
-            }
-        }
+    match run_cargo_fmt(args.limit) {
+        Ok(()) => {}
+        Err(err) => log::error!("Error: {err}"),
     }
 }
 
-fn run_fmt_on(_repo_path: &str) -> bool {
-    // TODO load details
-    if Path::new("Cargo.toml").exists() {
-        // TODO measure elapsed time
-        let stdout = run_cargo_in_docker();
-        log::info!("stdout: {stdout}");
-        // TODO save to details
-        return true;
-    }
-    false
-}
+fn run_cargo_fmt(limit: u32) -> Result<(), Box<dyn Error>> {
+    log::info!("start running cargo fmt on the repositories. limit {limit}.");
+    let _a = ElapsedTimer::new("fmt.rs");
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let rustfmt_version = runner.version("rustfmt");
+    let rustc_version = runner.version("rustc");
+    log::info!("{rustfmt_version} {rustc_version}");
+
+    let crates: Vec<Crate> = read_crates(0)?;
+    for_each_cloned_repository(&crates, limit, |repo| {
+        let mut details = load_vcs_details(&repo.krate.repository);
+        if details.cargo_fmt.is_up_to_date(&repo.commit) {
+            log::info!(
+                "cargo fmt already checked {} of {}",
+                repo.commit,
+                repo.krate.repository
+            );
+            return Ok(false);
+        }
 
-/// docker build -t rust-test .
-fn build_docker_image() {
-    log::info!("build_docker_image");
-    let result = Command::new("docker")
-        .arg("build")
-        .arg("-t")
-        .arg("rust-test")
-        .arg(".")
-        .output()
-        .expect("Could not run");
-    log::info!("build_docker_image {:?}", result.status.code());
-    if result.status.code() != Some(0) {
-        log::warn!("{}", core::str::from_utf8(&result.stdout).unwrap());
-        log::warn!("{}", core::str::from_utf8(&result.stderr).unwrap());
-    }
+        log::info!("repo: {:?}", repo.path.display());
+        details.cargo_fmt = run_fmt_on(&runner, &repo.path, repo.commit.clone());
+        details
+            .cargo_fmt
+            .rustfmt_version
+            .clone_from(&rustfmt_version);
+        details.cargo_fmt.rustc_version.clone_from(&rustc_version);
+        save_details(&repo.krate.repository, &details)?;
+        Ok(true)
+    })
 }
 
-/// docker run --rm --workdir /opt -v$(pwd):/opt -it --user tester rust-test cargo fmt --check -- --color=never
-fn run_cargo_in_docker() -> String {
-    log::info!("run_cargo_in_docker");
-    let cwd = env::current_dir().unwrap();
-    log::info!("cwd: {}", cwd.display());
-    let result = Command::new("docker")
-        .arg("run")
-        .arg("--rm")
-        .arg("--workdir")
-        .arg("/crate")
-        .arg(format!("-v{}:/crate", cwd.display()))
-        .arg("--user")
-        .arg("tester")
-        .arg("rust-test")
-        .arg("bash")
-        .arg("/opt/fmt.sh")
-        // .arg("cargo")
-        // .arg("fmt")
-        // .arg("--check")
-        // .arg("--")
-        // .arg("--color=never")
-        .output()
-        .expect("Could not run");
-    log::info!("run_cargo_in_docker {:?}", result.status.code());
-    if result.status.code() != Some(0) {
-        log::warn!("stdout: {}", core::str::from_utf8(&result.stdout).unwrap());
-        log::warn!("stderr: {}", core::str::from_utf8(&result.stderr).unwrap());
-    }
+fn run_fmt_on(runner: &Runner, repo_path: &Path, commit: String) -> FmtReport {
+    let mut report = FmtReport::new();
+    report.commit = commit;
 
-    core::str::from_utf8(&result.stdout).unwrap().to_owned()
+    let result = match runner.run("fmt", repo_path) {
+        Ok(result) => result,
+        Err(err) => {
+            log::error!("Could not run cargo fmt: {err}");
+            report.error = err.to_string();
+            return report;
+        }
+    };
+    report.checked_at = result.started_at;
+    report.exit_code = result.exit_code;
+    report.parse_check_output(&result.stdout, SOURCE_MOUNT);
+    if !matches!(report.exit_code, Some(0 | 1)) {
+        report.error = result
+            .stderr
+            .lines()
+            .take(ERROR_LINES)
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    log::info!(
+        "exit code: {:?} unformatted files: {} diff lines: {}",
+        report.exit_code,
+        report.unformatted_files.len(),
+        report.diff_lines
+    );
+
+    report
 }
 
 //git status --porcelain
@@ -0,0 +1,142 @@
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The result of running `cargo fmt --check` on a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FmtReport {
+    /// The commit we checked (`git rev-parse HEAD`), empty if we have not run `cargo fmt` yet.
+    pub commit: String,
+
+    /// When we ran `cargo fmt`, e.g. `2024-01-01T12:00:00+00:00`.
+    pub checked_at: String,
+
+    /// The output of `rustfmt --version` in the container.
+    pub rustfmt_version: String,
+
+    /// The output of `rustc --version` in the container.
+    pub rustc_version: String,
+
+    /// 0 if the code is formatted, 1 if it is not. `None` if `cargo fmt` could not be run at all.
+    pub exit_code: Option<i32>,
+
+    /// The files `cargo fmt` would change, relative to the root of the repository.
+    pub unformatted_files: Vec<String>,
+
+    /// The number of lines `cargo fmt` would add or remove.
+    pub diff_lines: usize,
+
+    /// The first few lines of the error output if `cargo fmt` failed, e.g. because of a syntax error.
+    pub error: String,
+}
+
+impl FmtReport {
+    pub const fn new() -> Self {
+        Self {
+            commit: String::new(),
+            checked_at: String::new(),
+            rustfmt_version: String::new(),
+            rustc_version: String::new(),
+            exit_code: None,
+            unformatted_files: vec![],
+            diff_lines: 0,
+            error: String::new(),
+        }
+    }
+
+    /// We have already checked this commit.
+    pub fn is_up_to_date(&self, commit: &str) -> bool {
+        !commit.is_empty() && self.commit == commit
+    }
+
+    /// `cargo fmt --check` ran and found nothing to change.
+    pub fn is_formatted(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Collect the unformatted files and the size of the diff from the output of `cargo fmt --check`.
+    /// `root` is the folder of the repository inside the container, e.g. `/crate`.
+    pub fn parse_check_output(&mut self, stdout: &str, root: &str) {
+        static DIFF_IN: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^Diff in (.+?)(?: at line \d+|:\d+)?:$").expect("valid regex")
+        });
+
+        let mut files = BTreeSet::new();
+        let mut diff_lines = 0;
+        for line in stdout.lines() {
+            if let Some(captures) = DIFF_IN.captures(line) {
+                let file = captures.get(1).map_or("", |file| file.as_str());
+                let relative = file
+                    .strip_prefix(root)
+                    .map_or(file, |path| path.trim_start_matches('/'));
+                files.insert(relative.to_owned());
+            } else if line.starts_with('+') || line.starts_with('-') {
+                diff_lines += 1;
+            } else {
+                // context line of the diff
+            }
+        }
+
+        self.unformatted_files = files.into_iter().collect();
+        self.diff_lines = diff_lines;
+    }
+}
+
+impl Default for FmtReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Before we had the `FmtReport` the `cargo_fmt` field was a string that was always empty.
+pub fn fmt_report_or_legacy_string<'de, D>(deserializer: D) -> Result<FmtReport, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Report(FmtReport),
+        Legacy(String),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Report(report) => report,
+        Stored::Legacy(text) => {
+            if !text.is_empty() {
+                log::warn!("Dropping the old cargo_fmt value: {text}");
+            }
+            FmtReport::new()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_check_output() {
+        let stdout = "Diff in /crate/src/main.rs at line 1:\n fn main() {\n-    println!(\"hello\")\n+    println!(\"hello\");\n }\nDiff in /crate/src/lib.rs:12:\n-use b;\n-use a;\n+use a;\n+use b;\nDiff in /crate/src/main.rs at line 20:\n-  x\n+    x\n";
+        let mut report = FmtReport::new();
+        report.parse_check_output(stdout, "/crate");
+        assert_eq!(report.unformatted_files, vec!["src/lib.rs", "src/main.rs"]);
+        assert_eq!(report.diff_lines, 8);
+
+        report.parse_check_output("", "/crate");
+        assert!(report.unformatted_files.is_empty());
+        assert_eq!(report.diff_lines, 0);
+    }
+
+    #[test]
+    fn check_is_up_to_date() {
+        let mut report = FmtReport::new();
+        assert!(!report.is_up_to_date(""));
+        assert!(!report.is_up_to_date("abc"));
+        report.commit = String::from("abc");
+        assert!(report.is_up_to_date("abc"));
+        assert!(!report.is_up_to_date("def"));
+    }
+}
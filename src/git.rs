@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::release_tags::{tag_name, TAG_CONVENTIONS};
+
+/// Run a git command in the given repository and return its standard output if it succeeded.
+/// Unlike `git_digger` this does not change the current working directory of the process.
+pub fn git_output(repo_path: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let result = match Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+    {
+        Ok(result) => result,
+        Err(err) => {
+            log::error!(
+                "Could not run git {args:?} in {:?}: {err}",
+                repo_path.display()
+            );
+            return None;
+        }
+    };
+
+    if result.status.success() {
+        Some(result.stdout)
+    } else {
+        log::warn!(
+            "git {args:?} failed in {:?}: {}",
+            repo_path.display(),
+            String::from_utf8_lossy(&result.stderr).trim_end()
+        );
+        None
+    }
+}
+
+/// Run a git command and return the lines of its output.
+pub fn git_lines(repo_path: &Path, args: &[&str]) -> Vec<String> {
+    git_output(repo_path, args).map_or_else(Vec::new, |stdout| {
+        String::from_utf8_lossy(&stdout)
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect()
+    })
+}
+
+/// Check if the given sha exists in the repository and points to a commit.
+pub fn git_commit_exists(repo_path: &Path, sha: &str) -> bool {
+    git_output(repo_path, &["cat-file", "-e", &format!("{sha}^{{commit}}")]).is_some()
+}
+
+/// The list of tags in the repository.
+pub fn git_tags(repo_path: &Path) -> Vec<String> {
+    git_lines(repo_path, &["tag", "--list"])
+}
+
+/// The path and the blob id of every file in the given revision. The paths are relative to the root of the repository.
+pub fn git_tree_blobs(repo_path: &Path, revision: &str) -> HashMap<String, String> {
+    let Some(stdout) = git_output(repo_path, &["ls-tree", "-r", "-z", "--full-tree", revision])
+    else {
+        return HashMap::new();
+    };
+
+    // Each entry looks like this: `100644 blob 0123abcd...\tsrc/lib.rs`
+    String::from_utf8_lossy(&stdout)
+        .split('\0')
+        .filter_map(|entry| {
+            let (meta, path) = entry.split_once('\t')?;
+            let mut parts = meta.split_whitespace();
+            let (_mode, kind, blob) = (parts.next()?, parts.next()?, parts.next()?);
+            (kind == "blob").then(|| (path.to_owned(), blob.to_owned()))
+        })
+        .collect()
+}
+
+/// The blob id git would assign to each one of the given files, in the same order.
+/// The files are hashed as they are, without applying any line-ending conversion.
+pub fn git_hash_files(repo_path: &Path, files: &[PathBuf]) -> Option<Vec<String>> {
+    let mut child = match Command::new("git")
+        .args(["hash-object", "--no-filters", "--stdin-paths"])
+        .current_dir(repo_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            log::error!("Could not run git hash-object: {err}");
+            return None;
+        }
+    };
+
+    // Write from a separate thread so a full stdout pipe can't block us.
+    let mut stdin = child.stdin.take()?;
+    let mut input = files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<String>>()
+        .join("\n");
+    input.push('\n');
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let result = child.wait_with_output().ok()?;
+    if !writer.join().is_ok_and(|written| written.is_ok()) || !result.status.success() {
+        log::warn!(
+            "git hash-object failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim_end()
+        );
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&result.stdout)
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect(),
+    )
+}
+
+/// The tag names commonly used for releasing the given version of a crate, see `TAG_CONVENTIONS`.
+pub fn version_tag_candidates(name: &str, version: &str) -> Vec<String> {
+    TAG_CONVENTIONS
+        .iter()
+        .map(|convention| tag_name(convention, name, version))
+        .collect()
+}
+
+/// Find the tag that belongs to the given version of the crate.
+pub fn find_version_tag(tags: &[String], name: &str, version: &str) -> Option<String> {
+    version_tag_candidates(name, version)
+        .into_iter()
+        .find(|candidate| tags.contains(candidate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_find_version_tag() {
+        let tags = vec![
+            String::from("v0.1.0"),
+            String::from("foo-v0.2.0"),
+            String::from("bar/1.0.0"),
+        ];
+        assert_eq!(
+            find_version_tag(&tags, "foo", "0.1.0"),
+            Some(String::from("v0.1.0"))
+        );
+        assert_eq!(
+            find_version_tag(&tags, "foo", "0.2.0"),
+            Some(String::from("foo-v0.2.0"))
+        );
+        assert_eq!(
+            find_version_tag(&tags, "bar", "1.0.0"),
+            Some(String::from("bar/1.0.0"))
+        );
+        assert_eq!(find_version_tag(&tags, "foo", "1.0.0"), None);
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::git::git_output;
+use crate::percentage;
+
+/// The domain of the email addresses GitHub uses when people hide their real email address.
+const GITHUB_NOREPLY_DOMAIN: &str = "@users.noreply.github.com";
+
+/// The number of authors with the most commits we keep.
+const TOP_AUTHORS: usize = 5;
+
+/// The format of the lines we ask from `git log`: committer date and author email separated by a TAB.
+const LOG_FORMAT: &str = "--format=%cI%x09%ae";
+
+/// Activity metrics of a repository collected from the git history.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitHistory {
+    /// The date of the oldest commit, e.g. `2020-01-31`.
+    pub first_commit_date: String,
+
+    /// The date of the newest commit.
+    pub last_commit_date: String,
+
+    /// The number of distinct authors (by email address).
+    pub author_count: usize,
+
+    /// The number of distinct authors in the 12 months before we collected the data.
+    pub recent_author_count: usize,
+
+    /// The number of commits in each month, e.g. `2020-01 => 12`.
+    pub commits_per_month: BTreeMap<String, u32>,
+
+    /// The date of the last commit touching the folder of each crate in the repository.
+    pub last_commit_by_crate: BTreeMap<String, String>,
+
+    /// The percentage of the commits made by the author with the most commits.
+    #[serde(default = "crate::empty_string")]
+    pub top_author_share: String,
+
+    /// The percentage of the commits in the last 12 months made by the author with the most commits in that period.
+    #[serde(default = "crate::empty_string")]
+    pub recent_top_author_share: String,
+
+    /// The smallest number of authors who made at least half of the commits.
+    #[serde(default = "crate::get_zero")]
+    pub bus_factor: usize,
+
+    /// The same for the commits of the last 12 months, 0 if there were no commits in that period.
+    #[serde(default = "crate::get_zero")]
+    pub recent_bus_factor: usize,
+
+    /// The authors with the most commits.
+    #[serde(default = "crate::no_entries")]
+    pub top_authors: Vec<Author>,
+}
+
+/// An author in the git history of a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Author {
+    pub email: String,
+
+    /// The GitHub login, if the email is a GitHub noreply address.
+    pub github_login: String,
+
+    pub commits: usize,
+}
+
+impl GitHistory {
+    pub const fn new() -> Self {
+        Self {
+            first_commit_date: String::new(),
+            last_commit_date: String::new(),
+            author_count: 0,
+            recent_author_count: 0,
+            commits_per_month: BTreeMap::new(),
+            last_commit_by_crate: BTreeMap::new(),
+            top_author_share: String::new(),
+            recent_top_author_share: String::new(),
+            bus_factor: 0,
+            recent_bus_factor: 0,
+            top_authors: vec![],
+        }
+    }
+
+    /// The number of days since the last commit.
+    pub fn days_since_last_commit(&self, now: DateTime<Utc>) -> Option<i64> {
+        let last = NaiveDate::parse_from_str(&self.last_commit_date, "%Y-%m-%d").ok()?;
+        Some((now.date_naive() - last).num_days())
+    }
+}
+
+impl Default for GitHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collect the history of the current branch of the repository.
+pub fn git_history(repo_path: &Path, now: DateTime<Utc>) -> GitHistory {
+    git_output(repo_path, &["log", LOG_FORMAT]).map_or_else(GitHistory::new, |stdout| {
+        parse_git_log(&String::from_utf8_lossy(&stdout), now)
+    })
+}
+
+/// The date of the last commit touching the given path, e.g. the folder of a crate in a workspace.
+pub fn git_last_commit_date(repo_path: &Path, path: &str) -> Option<String> {
+    let stdout = git_output(repo_path, &["log", "-1", LOG_FORMAT, "--", path])?;
+    String::from_utf8_lossy(&stdout)
+        .lines()
+        .next()
+        .and_then(|line| parse_log_line(line).map(|(date, _)| date.format("%Y-%m-%d").to_string()))
+}
+
+fn parse_log_line(line: &str) -> Option<(DateTime<Utc>, &str)> {
+    let (date_text, email) = line.split_once('\t')?;
+    let date = DateTime::parse_from_rfc3339(date_text).ok()?;
+    Some((date.with_timezone(&Utc), email))
+}
+
+/// Process the output of `git log` with `LOG_FORMAT`.
+fn parse_git_log(log: &str, now: DateTime<Utc>) -> GitHistory {
+    let year_ago = now - Duration::days(365);
+
+    let mut history = GitHistory::new();
+    let mut first: Option<DateTime<Utc>> = None;
+    let mut last: Option<DateTime<Utc>> = None;
+    let mut authors: HashMap<String, usize> = HashMap::new();
+    let mut recent_authors: HashMap<String, usize> = HashMap::new();
+
+    for (date, author) in log.lines().filter_map(parse_log_line) {
+        let email = author.to_lowercase();
+        // the commits of bots count as activity, but the bots are not authors who could maintain the crate
+        if !is_bot_email(&email) {
+            if year_ago <= date {
+                *recent_authors.entry(email.clone()).or_default() += 1;
+            }
+            *authors.entry(email).or_default() += 1;
+        }
+
+        *history
+            .commits_per_month
+            .entry(date.format("%Y-%m").to_string())
+            .or_default() += 1;
+
+        // The commit dates are not necessarily in order, e.g. after a rebase.
+        if first.is_none_or(|first_date| date < first_date) {
+            first = Some(date);
+        }
+        if last.is_none_or(|last_date| last_date < date) {
+            last = Some(date);
+        }
+    }
+
+    history.first_commit_date = first
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    history.last_commit_date = last
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    history.author_count = authors.len();
+    history.recent_author_count = recent_authors.len();
+
+    let commits = sorted_by_commits(authors);
+    let recent_commits = sorted_by_commits(recent_authors);
+    history.top_author_share = top_author_share(&commits);
+    history.recent_top_author_share = top_author_share(&recent_commits);
+    history.bus_factor = bus_factor(&commits);
+    history.recent_bus_factor = bus_factor(&recent_commits);
+    history.top_authors = commits
+        .into_iter()
+        .take(TOP_AUTHORS)
+        .map(|(email, count)| Author {
+            github_login: github_login_from_email(&email).unwrap_or_default(),
+            email,
+            commits: count,
+        })
+        .collect();
+
+    history
+}
+
+/// The authors and the number of their commits, the most active first.
+fn sorted_by_commits(authors: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut commits = authors.into_iter().collect::<Vec<(String, usize)>>();
+    #[expect(clippy::min_ident_chars)]
+    commits.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    commits
+}
+
+fn top_author_share(commits: &[(String, usize)]) -> String {
+    let total = commits.iter().map(|author| author.1).sum();
+    commits
+        .first()
+        .map(|top| percentage(top.1, total))
+        .unwrap_or_default()
+}
+
+/// The smallest number of authors who made at least half of the commits.
+/// `commits` must be sorted with the most active author first.
+fn bus_factor(commits: &[(String, usize)]) -> usize {
+    let total: usize = commits.iter().map(|author| author.1).sum();
+    let mut sum = 0;
+    for (index, author) in commits.iter().enumerate() {
+        sum += author.1;
+        if total <= 2 * sum {
+            return index + 1;
+        }
+    }
+    0
+}
+
+/// GitHub noreply addresses look like `login@users.noreply.github.com` or `12345+login@users.noreply.github.com`.
+pub fn github_login_from_email(email: &str) -> Option<String> {
+    let local = email
+        .to_lowercase()
+        .strip_suffix(GITHUB_NOREPLY_DOMAIN)?
+        .to_owned();
+    let login = local.split_once('+').map_or(local.as_str(), |pair| pair.1);
+    (!login.is_empty()).then(|| login.to_owned())
+}
+
+/// Bots like `49699333+dependabot[bot]@users.noreply.github.com` and the web-flow commits of GitHub (`noreply@github.com`).
+fn is_bot_email(email: &str) -> bool {
+    email == "noreply@github.com"
+        || github_login_from_email(email).is_some_and(|login| login.ends_with("[bot]"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_git_log() {
+        let now = DateTime::parse_from_rfc3339("2025-06-15T12:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let log = "\
+2025-05-02T10:00:00+02:00\tfoo@example.com
+2025-04-30T23:30:00-03:00\tBar@Example.com
+2023-01-15T08:00:00+00:00\tbar@example.com
+2022-12-31T10:00:00+00:00\tqux@example.com
+not a commit line
+";
+        let history = parse_git_log(log, now);
+        assert_eq!(history.first_commit_date, "2022-12-31");
+        assert_eq!(history.last_commit_date, "2025-05-02");
+        assert_eq!(history.author_count, 3);
+        assert_eq!(history.recent_author_count, 2);
+        assert_eq!(
+            history.commits_per_month,
+            BTreeMap::from([
+                (String::from("2022-12"), 1),
+                (String::from("2023-01"), 1),
+                (String::from("2025-05"), 2),
+            ])
+        );
+        assert_eq!(history.days_since_last_commit(now), Some(44));
+        assert_eq!(history.top_author_share, "50");
+        assert_eq!(history.recent_top_author_share, "50");
+        assert_eq!(history.bus_factor, 1);
+        assert_eq!(history.recent_bus_factor, 1);
+        assert_eq!(history.top_authors.len(), 3);
+        assert_eq!(
+            history.top_authors.first(),
+            Some(&Author {
+                email: String::from("bar@example.com"),
+                github_login: String::new(),
+                commits: 2,
+            })
+        );
+
+        let bots = "\
+2025-05-01T10:00:00+00:00\t49699333+dependabot[bot]@users.noreply.github.com
+2025-05-02T10:00:00+00:00\t49699333+dependabot[bot]@users.noreply.github.com
+2025-05-03T10:00:00+00:00\t41898282+github-actions[bot]@users.noreply.github.com
+2025-05-04T10:00:00+00:00\tnoreply@github.com
+2025-05-05T10:00:00+00:00\tfoo@example.com
+2025-05-06T10:00:00+00:00\t12345+bar@users.noreply.github.com
+";
+        let without_bots = parse_git_log(bots, now);
+        assert_eq!(without_bots.author_count, 2);
+        assert_eq!(without_bots.recent_author_count, 2);
+        assert_eq!(without_bots.bus_factor, 1);
+        assert_eq!(without_bots.top_author_share, "50");
+        assert_eq!(
+            without_bots.commits_per_month,
+            BTreeMap::from([(String::from("2025-05"), 6)])
+        );
+        assert!(without_bots
+            .top_authors
+            .iter()
+            .all(|author| !author.github_login.ends_with("[bot]")));
+
+        assert_eq!(parse_git_log("", now), GitHistory::new());
+        assert_eq!(GitHistory::new().days_since_last_commit(now), None);
+    }
+
+    #[test]
+    fn check_bus_factor() {
+        let commits = |counts: &[usize]| {
+            counts
+                .iter()
+                .map(|count| (String::new(), *count))
+                .collect::<Vec<(String, usize)>>()
+        };
+        assert_eq!(bus_factor(&commits(&[10])), 1);
+        assert_eq!(bus_factor(&commits(&[5, 5])), 1);
+        assert_eq!(bus_factor(&commits(&[4, 3, 3])), 2);
+        assert_eq!(bus_factor(&commits(&[1, 1, 1, 1, 1])), 3);
+        assert_eq!(bus_factor(&[]), 0);
+    }
+
+    #[test]
+    fn check_github_login_from_email() {
+        assert_eq!(
+            github_login_from_email("12345+SzabGab@users.noreply.github.com"),
+            Some(String::from("szabgab"))
+        );
+        assert_eq!(
+            github_login_from_email("szabgab@users.noreply.github.com"),
+            Some(String::from("szabgab"))
+        );
+        assert_eq!(github_login_from_email("gabor@szabgab.com"), None);
+    }
+}
@@ -14,21 +14,59 @@ use regex::Regex;
 use serde::Serialize;
 use thousands::Separable as _;
 
-use git_digger::Repository;
-
 use rust_digger::{
-    add_cargo_toml_to_crates, analyzed_crates_root, build_path, collected_data_root,
-    load_crate_details, load_release_errors, load_vcs_details, percentage, read_crates,
-    CargoTomlErrors, Crate, CrateErrors, CratesByOwner, ElapsedTimer, Owners, Repo, User,
+    add_cargo_toml_to_crates, analyzed_crates_root, build_path, canonical_repository_url,
+    ci_providers, collected_data_root, lint_slug, load_build_report, load_clippy_report,
+    load_crate_details, load_disk_usage, load_release_comparison, load_release_errors,
+    load_semver_history, load_vcs_details, minimum_rust_version_for_edition, parse_repository_url,
+    percentage, read_crates, read_recent_downloads, read_versions, resolve_repository_url,
+    BuildOutcome, CargoTomlErrors, CloneFailure, Crate, CrateErrors, CrateVersion, CratesByOwner,
+    DiskUsage, ElapsedTimer, Owners, Repo, RustVersion, User, TOOLS,
 };
 
 const URL: &str = "https://rust-digger.code-maven.com";
 
+/// How well the public items of a crate are documented: the slug of the page and its title.
+const DOC_QUALITY: [(&str, &str); 5] = [
+    (
+        "fully-documented",
+        "Crates with all the public items documented",
+    ),
+    (
+        "mostly-documented",
+        "Crates with at least 80% of the public items documented",
+    ),
+    (
+        "half-documented",
+        "Crates with at least 50% of the public items documented",
+    ),
+    (
+        "poorly-documented",
+        "Crates with less than 50% of the public items documented",
+    ),
+    ("undocumented", "Crates without any documented public item"),
+];
+
+/// The levels of the `missing_docs` lint we list, `none` is for crates that don't set it.
+const MISSING_DOCS_LEVELS: [&str; 5] = ["forbid", "deny", "warn", "allow", "none"];
+
+/// The buckets of the number of tests in a repository: the slug of the page, the label and the range.
+const TEST_COUNT_BUCKETS: [(&str, &str, usize, usize); 5] = [
+    ("no-tests", "0", 0, 0),
+    ("1-9", "1-9", 1, 9),
+    ("10-99", "10-99", 10, 99),
+    ("100-999", "100-999", 100, 999),
+    ("1000-or-more", "1,000+", 1000, usize::MAX),
+];
+
 pub type Partials = liquid::partials::EagerCompiler<liquid::partials::InMemorySource>;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const PAGE_SIZE: usize = 200;
 
+/// The number of owners with the largest clones we list on the page of the repositories.
+const TOP_OWNERS: usize = 50;
+
 struct CrateFilter {
     func: Box<dyn Fn(&&Crate) -> bool>,
 }
@@ -87,6 +125,39 @@ struct Cli {
     #[arg(long, default_value_t = false, help = "Generate the msrv pages")]
     msrv: bool,
 
+    #[arg(long, default_value_t = false, help = "Generate the tooling pages")]
+    tooling: bool,
+
+    #[arg(long, default_value_t = false, help = "Generate the clippy pages")]
+    clippy: bool,
+
+    #[arg(long, default_value_t = false, help = "Generate the build pages")]
+    builds: bool,
+
+    #[arg(long, default_value_t = false, help = "Generate the test pages")]
+    tests: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Generate the coverage pages and badges"
+    )]
+    coverage: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Generate the documentation quality pages"
+    )]
+    docs: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Generate the semver check pages"
+    )]
+    semver: bool,
+
     #[arg(long, default_value_t = false, help = "Generate the errors pages")]
     errors: bool,
 
@@ -95,6 +166,9 @@ struct Cli {
 
     #[arg(long, default_value_t = false, help = "Generate the top pages")]
     top: bool,
+
+    #[arg(long, default_value_t = false, help = "Generate the binaries pages")]
+    bins: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -124,6 +198,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     add_owners_to_crates(&mut crates, &users, &owner_by_crate_id);
     load_vcs_details_for_all_the_crates(&mut crates);
     load_crate_details_for_all_the_crates(&mut crates);
+    load_release_comparison_for_all_the_crates(&mut crates);
+    load_clippy_reports_for_all_the_crates(&mut crates);
+    load_build_reports_for_all_the_crates(&mut crates);
+    load_semver_histories_for_all_the_crates(&mut crates);
+    let versions = read_versions()?;
+    add_recent_downloads_to_crates(&mut crates, &read_recent_downloads(&versions)?);
+    let reverse_dependencies = count_reverse_dependencies(&crates);
     create_html_folders()?;
 
     if args.all || args.stats {
@@ -140,8 +221,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         generate_rustfmt_pages(&crates)?;
     }
     if args.all || args.msrv {
-        generate_msrv_pages(&crates)?;
+        generate_msrv_pages(&crates, &versions)?;
     }
+    if args.all || args.tooling {
+        generate_tooling_pages(&crates)?;
+    }
+    generate_job_pages(&args, &crates)?;
     if args.all {
         generate_interesting_homepages(&crates)?;
     }
@@ -155,7 +240,16 @@ fn main() -> Result<(), Box<dyn Error>> {
         render_static_pages()?;
     }
     if args.all {
-        generate_crate_pages(&crates, &released_cargo_toml_errors)?;
+        let user_logins = users
+            .iter()
+            .map(|user| user.gh_login.to_lowercase())
+            .collect::<HashSet<String>>();
+        generate_crate_pages(
+            &crates,
+            &released_cargo_toml_errors,
+            &user_logins,
+            &reverse_dependencies,
+        )?;
     }
     if args.all || args.users {
         generate_user_pages(
@@ -167,7 +261,11 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 
     if args.all || args.top {
-        generate_top_crates_lists(&mut crates)?;
+        generate_top_crates_lists(&mut crates, &reverse_dependencies)?;
+    }
+
+    if args.all || args.bins {
+        generate_binaries_pages(&crates)?;
     }
 
     if args.all {
@@ -200,6 +298,178 @@ fn load_crate_details_for_all_the_crates(crates: &mut [Crate]) {
     }
 }
 
+fn load_release_comparison_for_all_the_crates(crates: &mut [Crate]) {
+    let _a = ElapsedTimer::new("load_release_comparison_for_all_the_crates");
+
+    for krate in crates.iter_mut() {
+        krate.release_comparison =
+            load_release_comparison(&krate.cargo.package.name, &krate.cargo.package.version);
+    }
+}
+
+/// The pages of the results of the cargo commands we run in containers, see `runner.yaml`.
+fn generate_job_pages(args: &Cli, crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    if args.all || args.clippy {
+        generate_clippy_pages(crates)?;
+    }
+    if args.all || args.builds {
+        generate_build_pages(crates)?;
+    }
+    if args.all || args.tests {
+        generate_test_pages(crates)?;
+    }
+    if args.all || args.coverage {
+        generate_coverage_pages(crates)?;
+    }
+    if args.all || args.docs {
+        generate_documentation_pages(crates)?;
+    }
+    if args.all || args.semver {
+        generate_semver_pages(crates)?;
+    }
+
+    Ok(())
+}
+
+fn load_clippy_reports_for_all_the_crates(crates: &mut [Crate]) {
+    let _a = ElapsedTimer::new("load_clippy_reports_for_all_the_crates");
+
+    for krate in crates.iter_mut() {
+        let report = load_clippy_report(&krate.name);
+        // a report of an older release is misleading
+        if report.version == krate.cargo.package.version {
+            krate.clippy = report;
+        }
+    }
+}
+
+fn load_build_reports_for_all_the_crates(crates: &mut [Crate]) {
+    let _a = ElapsedTimer::new("load_build_reports_for_all_the_crates");
+
+    for krate in crates.iter_mut() {
+        let report = load_build_report(&krate.name);
+        if report.version == krate.cargo.package.version {
+            krate.build = report;
+        }
+    }
+}
+
+fn load_semver_histories_for_all_the_crates(crates: &mut [Crate]) {
+    let _a = ElapsedTimer::new("load_semver_histories_for_all_the_crates");
+
+    for krate in crates.iter_mut() {
+        krate.semver_history = load_semver_history(&krate.name);
+    }
+}
+
+/// The number of crates that depend on each crate according to the `[dependencies]` of their latest release.
+fn count_reverse_dependencies(crates: &[Crate]) -> HashMap<String, usize> {
+    let dependencies = crates
+        .iter()
+        .filter_map(|krate| krate.cargo.dependencies.as_ref())
+        .flat_map(|dependencies| {
+            dependencies.iter().map(|(name, value)| {
+                // Renamed dependencies: `alias = { package = "real-name", version = "1.0" }`
+                value
+                    .get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(name)
+            })
+        });
+
+    let mut reverse_dependencies: HashMap<String, usize> = HashMap::new();
+    for dependency in dependencies {
+        *reverse_dependencies
+            .entry(dependency.to_owned())
+            .or_default() += 1;
+    }
+    reverse_dependencies
+}
+
+fn add_recent_downloads_to_crates(crates: &mut [Crate], recent_downloads: &HashMap<String, usize>) {
+    for krate in crates.iter_mut() {
+        krate.recent_downloads = recent_downloads.get(&krate.id).copied().unwrap_or_default();
+    }
+}
+
+/// We consider a repository abandoned if it has no commit in this many days
+/// while the crate still has `ABANDONED_MIN_RECENT_DOWNLOADS` downloads in the period of the database dump.
+const ABANDONED_AFTER_DAYS: i64 = 2 * 365;
+
+const ABANDONED_MIN_RECENT_DOWNLOADS: usize = 1000;
+
+/// The repository of the crate seems to be abandoned while people still use the crate.
+fn is_abandoned(krate: &Crate, now: DateTime<Utc>) -> bool {
+    ABANDONED_MIN_RECENT_DOWNLOADS <= krate.recent_downloads
+        && krate
+            .vcs_details
+            .history
+            .days_since_last_commit(now)
+            .is_some_and(|days| ABANDONED_AFTER_DAYS <= days)
+}
+
+/// The checks of the crate page checklist: the key of the problem on the user page and the description of the check.
+const HEALTH_CHECKS: [(&str, &str); 12] = [
+    ("no_repository", "Repository link in Cargo.toml"),
+    ("vcs_with_http", "Repository link uses https"),
+    ("vcs_with_www", "Repository link without www"),
+    ("has_cargo_toml_errors", "Released Cargo.toml can be parsed"),
+    (
+        "both_rustfm_and_dot_rustfmt",
+        "Only one of rustfmt.toml and .rustfmt.toml",
+    ),
+    ("no_ci", "Continuous Integration"),
+    ("no_security_policy", "Security policy"),
+    ("no_contributing", "Contributing guide"),
+    ("no_code_of_conduct", "Code of conduct"),
+    ("no_issue_templates", "Issue templates"),
+    ("no_changelog", "Changelog"),
+    (
+        "changelog_without_latest_release",
+        "Changelog mentions the latest release",
+    ),
+];
+
+/// The keys of the `HEALTH_CHECKS` the crate fails.
+fn crate_problems(krate: &Crate, has_cargo_toml_errors: bool) -> Vec<&'static str> {
+    let vcs = &krate.vcs_details;
+    let has_changelog =
+        vcs.community_files.contains_key("changelog") || !krate.crate_details.changelog.is_empty();
+    let changelog_has_latest_release = vcs
+        .changelog_has_latest_release
+        .get(&krate.name)
+        .is_some_and(|mentioned| *mentioned)
+        || krate.crate_details.changelog_mentions_version;
+
+    HEALTH_CHECKS
+        .iter()
+        .map(|check| check.0)
+        .filter(|key| match *key {
+            "no_repository" => krate.repository.is_empty(),
+            "vcs_with_http" => krate.repository.starts_with("http://"),
+            "vcs_with_www" => krate.repository.starts_with("https://www.github.com"),
+            "has_cargo_toml_errors" => has_cargo_toml_errors,
+            "both_rustfm_and_dot_rustfmt" => vcs.has_rustfmt_toml && vcs.has_dot_rustfmt_toml,
+            "no_ci" => vcs.ci_providers.is_empty(),
+            "no_security_policy" => !vcs.community_files.contains_key("security-policy"),
+            "no_contributing" => !vcs.community_files.contains_key("contributing"),
+            "no_code_of_conduct" => !vcs.community_files.contains_key("code-of-conduct"),
+            "no_issue_templates" => !vcs.community_files.contains_key("issue-templates"),
+            "no_changelog" => !has_changelog,
+            "changelog_without_latest_release" => has_changelog && !changelog_has_latest_release,
+            _ => false,
+        })
+        .collect()
+}
+
+/// The percentage of the `HEALTH_CHECKS` the crate passes.
+fn health_score(problems: &[&str]) -> String {
+    percentage(
+        HEALTH_CHECKS.len().saturating_sub(problems.len()),
+        HEALTH_CHECKS.len(),
+    )
+}
+
 fn add_owners_to_crates(crates: &mut [Crate], users: &Vec<User>, owner_by_crate_id: &Owners) {
     let _a = ElapsedTimer::new("add_owners_to_crates");
     let mut mapping: HashMap<String, &User> = HashMap::new();
@@ -234,7 +504,7 @@ fn get_site_folder() -> PathBuf {
     PathBuf::from("_site")
 }
 
-pub fn render_list_of_repos(repos: &Vec<Repo>) {
+pub fn render_list_of_repos(repos: &Vec<Repo>, broken: &[liquid::Object], usage: &DiskUsage) {
     log::info!("render_list_of_repos start");
     let partials = load_templates().unwrap();
 
@@ -253,6 +523,14 @@ pub fn render_list_of_repos(repos: &Vec<Repo>) {
         "utc":     format!("{}", utc),
         "title":   String::from("Repositories"),
         "repos":    repos,
+        "broken":   broken,
+        "disk_usage": liquid::object!({
+            "generated_at": usage.generated_at,
+            "size": usage.size,
+            "clones": usage.clones,
+            "hosts": usage.hosts,
+            "owners": usage.owners.iter().take(TOP_OWNERS).collect::<Vec<_>>(),
+        }),
     });
     let html = template.render(&globals).unwrap();
     let mut file = File::create(filename).unwrap();
@@ -415,9 +693,11 @@ pub fn render_news_pages() {
     }
 }
 
-pub fn generate_crate_pages(
+fn generate_crate_pages(
     crates: &Vec<Crate>,
     released_cargo_toml_errors: &CrateErrors,
+    user_logins: &HashSet<String>,
+    reverse_dependencies: &HashMap<String, usize>,
 ) -> Result<(), Box<dyn Error>> {
     let _a = ElapsedTimer::new("generate_crate_pages");
 
@@ -438,6 +718,36 @@ pub fn generate_crate_pages(
 
         let def = String::new();
         let cargo_toml_error = released_cargo_toml_errors.get(&krate.name).unwrap_or(&def);
+        let problems = crate_problems(krate, !cargo_toml_error.is_empty());
+        let checklist = HEALTH_CHECKS
+            .iter()
+            .map(|check| {
+                liquid::object!({
+                    "label": check.1,
+                    "ok": !problems.contains(&check.0),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Link the authors with a GitHub noreply email address to their page if they are also Crates.io users.
+        let authors = krate
+            .vcs_details
+            .history
+            .top_authors
+            .iter()
+            .map(|author| {
+                let user = if user_logins.contains(&author.github_login) {
+                    author.github_login.as_str()
+                } else {
+                    ""
+                };
+                liquid::object!({
+                    "email": author.email,
+                    "commits": author.commits,
+                    "user": user,
+                })
+            })
+            .collect::<Vec<_>>();
 
         let globals = liquid::object!({
             "version": format!("{VERSION}"),
@@ -446,6 +756,18 @@ pub fn generate_crate_pages(
             "crate":   krate,
             "readme":  markdown2html(&krate.readme),
             "cargo_toml_error": cargo_toml_error,
+            "abandoned": is_abandoned(krate, utc),
+            "path_in_repository": krate.path_in_repository(),
+            "repository_sub_path": canonical_repository_url(&krate.repository).map(|repository| repository.sub_path).unwrap_or_default(),
+            "moved_repository": krate.moved_repository().unwrap_or_default(),
+            "release_tags": krate.release_tags(),
+            "last_commit_of_crate": krate.vcs_details.history.last_commit_by_crate.get(&krate.name),
+            "crate_coverage": krate.vcs_details.coverage.crates.get(&krate.name),
+            "authors": authors,
+            "reverse_dependencies": reverse_dependencies.get(&krate.name).copied().unwrap_or_default(),
+            "commits_per_month": krate.vcs_details.history.commits_per_month.iter().rev().take(12).collect::<Vec<_>>(),
+            "checklist": checklist,
+            "health_score": health_score(&problems),
         });
         let html = template.render(&globals)?;
         let mut file = File::create(filename)?;
@@ -470,6 +792,23 @@ fn markdown2html(content: &str) -> String {
     .unwrap_or_else(|err| format!("Error converting README {err}"))
 }
 
+/// The crates where each GitHub user is among the top authors of the repository
+/// and the number of their commits, based on the GitHub noreply email addresses.
+fn collect_contributions(crates: &[Crate]) -> HashMap<&str, Vec<(&Crate, usize)>> {
+    let mut contributions: HashMap<&str, Vec<(&Crate, usize)>> = HashMap::new();
+    for krate in crates {
+        for author in &krate.vcs_details.history.top_authors {
+            if !author.github_login.is_empty() {
+                contributions
+                    .entry(author.github_login.as_str())
+                    .or_default()
+                    .push((krate, author.commits));
+            }
+        }
+    }
+    contributions
+}
+
 pub fn generate_user_pages(
     crates: &Vec<Crate>,
     users: Vec<User>,
@@ -490,6 +829,7 @@ pub fn generate_user_pages(
     for krate in crates {
         crate_by_id.insert(&krate.id, krate);
     }
+    let contributions = collect_contributions(crates);
     //dbg!(&crate_by_id);
     //dbg!(&crate_by_id["81366"]);
 
@@ -521,46 +861,44 @@ pub fn generate_user_pages(
                     Some("html"),
                 );
 
-                let mut problems: HashMap<&str, Vec<&&Crate>> = HashMap::new();
-                problems.insert(
-                    "vcs_with_http",
-                    selected_crates
-                        .iter()
-                        .filter(|krate| {
-                            !krate.repository.is_empty() && krate.repository.starts_with("http://")
-                        })
-                        .collect::<Vec<_>>(),
-                );
-
-                problems.insert(
-                    "vcs_with_www",
-                    selected_crates
-                        .iter()
-                        .filter(|krate| {
-                            !krate.repository.is_empty()
-                                && krate.repository.starts_with("https://www.github.com")
-                        })
-                        .collect::<Vec<_>>(),
-                );
+                let mut problems: HashMap<&str, Vec<&&Crate>> = HEALTH_CHECKS
+                    .iter()
+                    .map(|check| (check.0, vec![]))
+                    .collect();
+                let mut health = vec![];
+                for krate in &selected_crates {
+                    let failed =
+                        crate_problems(krate, released_cargo_toml_errors.contains_key(&krate.name));
+                    for key in &failed {
+                        problems.entry(key).or_default().push(krate);
+                    }
+                    health.push(liquid::object!({
+                        "name": krate.name,
+                        "score": health_score(&failed),
+                        "low": HEALTH_CHECKS.len() < 2 * failed.len(),
+                        "missing": HEALTH_CHECKS
+                            .iter()
+                            .filter(|check| failed.contains(&check.0))
+                            .map(|check| check.1)
+                            .collect::<Vec<_>>(),
+                    }));
+                }
 
-                problems.insert(
-                    "both_rustfm_and_dot_rustfmt",
-                    selected_crates
-                        .iter()
-                        .filter(|krate| {
-                            krate.vcs_details.has_rustfmt_toml
-                                && krate.vcs_details.has_dot_rustfmt_toml
+                let broken_links = selected_crates
+                    .iter()
+                    .filter_map(|krate| {
+                        krate.broken_repository_link().map(|failure| {
+                            liquid::object!({
+                                "name": krate.name,
+                                "repository": krate.repository,
+                                "failure": failure.name(),
+                                "title": failure.title(),
+                                "redirect_url": krate.moved_repository().unwrap_or_default(),
+                                "last_attempt": krate.vcs_details.clone_state.last_attempt,
+                            })
                         })
-                        .collect::<Vec<_>>(),
-                );
-
-                problems.insert(
-                    "has_cargo_toml_errors",
-                    selected_crates
-                        .iter()
-                        .filter(|krate| released_cargo_toml_errors.contains_key(&krate.name))
-                        .collect::<Vec<_>>(),
-                );
+                    })
+                    .collect::<Vec<_>>();
 
                 let utc: DateTime<Utc> = Utc::now();
                 let globals = liquid::object!({
@@ -570,6 +908,9 @@ pub fn generate_user_pages(
                     "user":    user,
                     "crates":  selected_crates,
                     "problems": problems,
+                    "health": health,
+                    "broken_links": broken_links,
+                    "contributions": contributions.get(user.gh_login.to_lowercase().as_str()),
                 });
                 let html = template.render(&globals).unwrap();
                 let mut file = File::create(filename).unwrap();
@@ -697,7 +1038,10 @@ fn render_stats_page(stats: &HashMap<String, Vec<StatEntry>>) {
 fn create_html_folders() -> Result<(), Box<dyn Error>> {
     let _a = ElapsedTimer::new("create_html_folders");
     fs::create_dir_all(get_site_folder())?;
-    for folder in ["crates", "users", "news", "vcs", "rustfmt"] {
+    for folder in [
+        "crates", "users", "news", "vcs", "rustfmt", "clippy", "builds", "tests", "coverage",
+        "docs", "semver",
+    ] {
         fs::create_dir_all(get_site_folder().join(folder))?;
     }
 
@@ -827,7 +1171,25 @@ fn collect_repos(crates: &[Crate]) -> Result<usize, Box<dyn Error>> {
         (repob.count, repob.name.to_lowercase()).cmp(&(repoa.count, repoa.name.to_lowercase()))
     });
 
-    render_list_of_repos(&repos);
+    let mut broken = vec![];
+    for failure in CloneFailure::ALL
+        .into_iter()
+        .filter(|failure| failure.is_about_repository())
+    {
+        let count = render_filtered_crates(
+            &format!("vcs/broken-{}", failure.name()),
+            &format!("Crates with broken repository links: {}", failure.title()),
+            |krate| krate.broken_repository_link() == Some(failure),
+            crates,
+        )?;
+        broken.push(liquid::object!({
+            "name": failure.name(),
+            "title": failure.title(),
+            "count": count,
+        }));
+    }
+
+    render_list_of_repos(&repos, &broken, &load_disk_usage());
 
     log::info!("collect_repos end");
     Ok(no_repo_count)
@@ -937,7 +1299,7 @@ fn render_top_crates(
     fields: &[&str],
     krates: &[Thing],
 ) -> Result<(), Box<dyn Error>> {
-    log::info!("render_top_crates: {filename}",);
+    log::info!("render_top_crates: {filename}");
 
     let page_size = if krates.len() > PAGE_SIZE {
         PAGE_SIZE
@@ -984,7 +1346,10 @@ struct Thing<'local> {
     krate: &'local Crate,
     fields: Vec<String>,
 }
-pub fn generate_top_crates_lists(crates: &mut [Crate]) -> Result<(), Box<dyn Error>> {
+fn generate_top_crates_lists(
+    crates: &mut [Crate],
+    reverse_dependencies: &HashMap<String, usize>,
+) -> Result<(), Box<dyn Error>> {
     let _a = ElapsedTimer::new("generate_top_crates_lists");
 
     crates.sort_by_key(|krate| krate.crate_details.size);
@@ -1005,6 +1370,182 @@ pub fn generate_top_crates_lists(crates: &mut [Crate]) -> Result<(), Box<dyn Err
         &crates_and_fields,
     )?;
 
+    render_critical_crates_with_bus_factor_one(crates, reverse_dependencies)?;
+    render_crates_with_risky_ci(crates, reverse_dependencies)?;
+
+    Ok(())
+}
+
+/// Crates that many other crates depend on, while most of the commits in their repository were made by a single person.
+fn render_critical_crates_with_bus_factor_one(
+    crates: &[Crate],
+    reverse_dependencies: &HashMap<String, usize>,
+) -> Result<(), Box<dyn Error>> {
+    let mut critical = crates
+        .iter()
+        .filter(|krate| krate.vcs_details.history.bus_factor == 1)
+        .filter_map(|krate| {
+            reverse_dependencies
+                .get(&krate.name)
+                .map(|count| (krate, *count))
+        })
+        .collect::<Vec<(&Crate, usize)>>();
+    critical.sort_by_key(|entry| core::cmp::Reverse(entry.1));
+
+    let crates_and_fields = critical
+        .into_iter()
+        .map(|(krate, count)| Thing {
+            krate,
+            fields: vec![
+                count.separate_with_commas(),
+                format!("{}%", krate.vcs_details.history.top_author_share),
+                krate.vcs_details.history.author_count.to_string(),
+                krate.recent_downloads.separate_with_commas(),
+            ],
+        })
+        .collect::<Vec<Thing>>();
+
+    render_top_crates(
+        "critical-crates-bus-factor-1",
+        "Critical crates with bus factor 1",
+        &[
+            "Reverse dependencies",
+            "Commits by the top author",
+            "Authors",
+            "Downloads in the last 90 days",
+        ],
+        &crates_and_fields,
+    )
+}
+
+/// The crates other crates depend on, with risky patterns in their GitHub Actions workflows.
+fn render_crates_with_risky_ci(
+    crates: &[Crate],
+    reverse_dependencies: &HashMap<String, usize>,
+) -> Result<(), Box<dyn Error>> {
+    let mut risky = crates
+        .iter()
+        .filter(|krate| !krate.vcs_details.github_workflows.findings.is_empty())
+        .filter_map(|krate| {
+            reverse_dependencies
+                .get(&krate.name)
+                .map(|count| (krate, *count))
+        })
+        .collect::<Vec<(&Crate, usize)>>();
+    risky.sort_by_key(|entry| {
+        (
+            core::cmp::Reverse(entry.1),
+            core::cmp::Reverse(entry.0.recent_downloads),
+        )
+    });
+
+    let crates_and_fields = risky
+        .into_iter()
+        .map(|(krate, count)| Thing {
+            krate,
+            fields: vec![
+                count.separate_with_commas(),
+                krate.recent_downloads.separate_with_commas(),
+                krate
+                    .vcs_details
+                    .github_workflows
+                    .risks()
+                    .iter()
+                    .map(|risk| risk.name())
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+            ],
+        })
+        .collect::<Vec<Thing>>();
+
+    render_top_crates(
+        "risky-ci",
+        "Widely used crates with risky CI",
+        &[
+            "Reverse dependencies",
+            "Downloads in the last 90 days",
+            "Risks",
+        ],
+        &crates_and_fields,
+    )
+}
+
+/// Generate the catalogue of the crates that install at least one executable
+/// and the list of executable names installed by more than one crate.
+pub fn generate_binaries_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_binaries_pages");
+
+    let mut tools = crates
+        .iter()
+        .filter(|krate| !krate.crate_details.binaries.is_empty())
+        .collect::<Vec<&Crate>>();
+    #[expect(clippy::min_ident_chars)]
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut crates_by_binary: HashMap<&str, Vec<&str>> = HashMap::new();
+    for krate in &tools {
+        for binary in &krate.crate_details.binaries {
+            crates_by_binary
+                .entry(binary.name.as_str())
+                .or_default()
+                .push(krate.name.as_str());
+        }
+    }
+
+    let mut collisions = crates_by_binary
+        .into_iter()
+        .filter(|entry| entry.1.len() > 1)
+        .collect::<Vec<(&str, Vec<&str>)>>();
+    #[expect(clippy::min_ident_chars)]
+    collisions.sort_by(|a, b| (b.1.len(), a.0).cmp(&(a.1.len(), b.0)));
+
+    render_cli_tools(&tools)?;
+    render_binary_collisions(&collisions)?;
+
+    Ok(())
+}
+
+fn render_cli_tools(tools: &[&Crate]) -> Result<(), Box<dyn Error>> {
+    let partials = load_templates()?;
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/cli_tools.html")?;
+
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Installable CLI tools",
+        "tools":   tools,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(get_site_folder().join("cli-tools.html"))?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+fn render_binary_collisions(collisions: &[(&str, Vec<&str>)]) -> Result<(), Box<dyn Error>> {
+    let partials = load_templates()?;
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/binary_collisions.html")?;
+
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Binary name collisions",
+        "collisions": collisions,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(get_site_folder().join("binary-name-collisions.html"))?;
+    writeln!(&mut file, "{html}")?;
+
     Ok(())
 }
 
@@ -1158,6 +1699,11 @@ pub fn generate_stats_pages(
                 !krate.crate_details.has_cargo_lock && !krate.crate_details.has_main_rs
             }),
         ),
+        (
+            "crates-with-binaries",
+            "Crates with at least one binary target",
+            CrateFilter::new(|krate: &&Crate| !krate.crate_details.binaries.is_empty()),
+        ),
     ];
     process_cases(crates, crates_stats, crates_cases)?;
 
@@ -1202,6 +1748,82 @@ pub fn generate_stats_pages(
             "Has no Cargo.toml file in the root of the repository",
             CrateFilter::new(|krate: &&Crate| !krate.vcs_details.cargo_toml_in_root),
         ),
+        (
+            "has-workspace",
+            "Has a workspace in the root of the repository",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.is_workspace),
+        ),
+        (
+            "crate-in-subfolder-of-repository",
+            "The crate is in a subfolder of the repository",
+            CrateFilter::new(|krate: &&Crate| {
+                krate.path_in_repository().is_some_and(|path| path != ".")
+            }),
+        ),
+        (
+            "crate-missing-from-repository",
+            "The crate could not be found in the repository it links to",
+            CrateFilter::new(|krate: &&Crate| krate.missing_from_repository()),
+        ),
+        (
+            "abandoned",
+            "No commit in the repository for 2 years, but the crate is still downloaded",
+            CrateFilter::new(|krate: &&Crate| is_abandoned(krate, Utc::now())),
+        ),
+        (
+            "releases-without-tag",
+            "Some of the releases have no git tag",
+            CrateFilter::new(|krate: &&Crate| {
+                krate
+                    .release_tags()
+                    .is_some_and(|tags| !tags.untagged.is_empty())
+            }),
+        ),
+        (
+            "no-release-tags",
+            "None of the releases has a git tag",
+            CrateFilter::new(|krate: &&Crate| {
+                krate
+                    .release_tags()
+                    .is_some_and(|tags| 0 < tags.released && tags.untagged.len() == tags.released)
+            }),
+        ),
+        (
+            "tags-without-release",
+            "The repository has version tags that were not released",
+            CrateFilter::new(|krate: &&Crate| !krate.vcs_details.tags_without_release.is_empty()),
+        ),
+        (
+            "release-without-vcs-info",
+            "The released crate has no .cargo_vcs_info.json file",
+            CrateFilter::new(|krate: &&Crate| {
+                krate.release_comparison.compared && !krate.release_comparison.has_vcs_info
+            }),
+        ),
+        (
+            "release-commit-not-in-repository",
+            "The commit the crate was published from is not in the repository",
+            CrateFilter::new(|krate: &&Crate| {
+                !krate.release_comparison.sha1.is_empty() && !krate.release_comparison.commit_found
+            }),
+        ),
+        (
+            "release-dirty",
+            "The crate was published with uncommitted changes",
+            CrateFilter::new(|krate: &&Crate| krate.release_comparison.dirty),
+        ),
+        (
+            "release-files-only-in-tarball",
+            "The released crate has files that are not in the repository",
+            CrateFilter::new(|krate: &&Crate| !krate.release_comparison.only_in_tarball.is_empty()),
+        ),
+        (
+            "release-content-differs",
+            "The released crate has files that differ from the ones in the repository",
+            CrateFilter::new(|krate: &&Crate| {
+                !krate.release_comparison.different_content.is_empty()
+            }),
+        ),
         (
             "has-rustfmt-toml",
             "Has rustfmt.toml file",
@@ -1270,12 +1892,12 @@ fn on_github_has_github_action(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_github() {
                 return false;
             }
-            if !krate.vcs_details.has_github_action {
+            if !krate.vcs_details.has_ci("github-actions") {
                 return false;
             }
 
@@ -1290,12 +1912,12 @@ fn on_github_has_circle_ci(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_github() {
                 return false;
             }
-            if !krate.vcs_details.has_circle_ci {
+            if !krate.vcs_details.has_ci("circle-ci") {
                 return false;
             }
 
@@ -1310,12 +1932,12 @@ fn on_github_has_cirrus_ci(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_github() {
                 return false;
             }
-            if !krate.vcs_details.has_cirrus_ci {
+            if !krate.vcs_details.has_ci("cirrus-ci") {
                 return false;
             }
 
@@ -1330,15 +1952,12 @@ fn on_github_but_no_ci(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_github() {
                 return false;
             }
-            if krate.vcs_details.has_github_action
-                || krate.vcs_details.has_circle_ci
-                || krate.vcs_details.has_cirrus_ci
-            {
+            if !krate.vcs_details.ci_providers.is_empty() {
                 return false;
             }
 
@@ -1353,12 +1972,12 @@ fn on_gitlab_has_gitlab_pipeline(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_gitlab() {
                 return false;
             }
-            if !krate.vcs_details.has_gitlab_pipeline {
+            if !krate.vcs_details.has_ci("gitlab-pipeline") {
                 return false;
             }
 
@@ -1373,12 +1992,12 @@ fn on_gitlab_but_no_ci(krate: &Crate) -> bool {
         return false;
     }
 
-    match Repository::from_url(&krate.repository) {
+    match parse_repository_url(&krate.repository) {
         Ok(repo) => {
             if !repo.is_gitlab() {
                 return false;
             }
-            if krate.vcs_details.has_gitlab_pipeline {
+            if !krate.vcs_details.ci_providers.is_empty() {
                 return false;
             }
 
@@ -1429,37 +2048,44 @@ fn load_collected_rustfmt() -> Vec<(String, String, String)> {
 fn generate_ci_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
     log::info!("generate_ci_pages start");
 
-    let mut count: HashMap<&str, usize> = HashMap::new();
-
-    count.insert(
-        "has-github-actions",
-        render_filtered_crates(
-            "has-github-actions",
-            "Crates with GitHub Actions",
-            |krate| krate.vcs_details.has_github_action,
+    let registry = ci_providers();
+    let pages = registry
+        .iter()
+        .map(|provider| {
+            (
+                format!("has-{}", provider.name),
+                format!("Crates with {}", provider.display),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+    let mut providers: Vec<StatEntry> = vec![];
+    for (provider, page) in registry.iter().zip(&pages) {
+        let count = render_filtered_crates(
+            &page.0,
+            &page.1,
+            |krate| krate.vcs_details.has_ci(&provider.name),
             crates,
-        )?,
-    );
+        )?;
+        providers.push(StatEntry {
+            path: &page.0,
+            title: &page.1,
+            count,
+            percentage: percentage(count, crates.len()),
+        });
+    }
 
-    count.insert(
-        "has-gitlab-pipeline",
-        render_filtered_crates(
-            "has-gitlab-pipeline",
-            "Crates with GitLab Pipeline",
-            |krate| krate.vcs_details.has_gitlab_pipeline,
-            crates,
-        )?,
-    );
+    let mut workflows: Vec<StatEntry> = vec![];
+    process_cases(crates, &mut workflows, github_workflow_cases())?;
 
-    count.insert(
-        "has-cirrus-ci",
-        render_filtered_crates(
-            "has-cirrus-ci",
-            "Crates with Cirrus CI",
-            |krate| krate.vcs_details.has_cirrus_ci,
-            crates,
-        )?,
-    );
+    let triggers = count_crates_by(crates, |krate| {
+        krate
+            .vcs_details
+            .github_workflows
+            .triggers
+            .iter()
+            .cloned()
+            .collect()
+    });
 
     let partials = load_templates()?;
 
@@ -1475,7 +2101,9 @@ fn generate_ci_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
         "version": format!("{VERSION}"),
         "utc":     format!("{}", utc),
         "title":   "CI systems",
-        "count": count,
+        "providers": providers,
+        "workflows": workflows,
+        "triggers": triggers,
     });
     let html = template.render(&globals)?;
     let mut file = File::create(filename)?;
@@ -1485,14 +2113,744 @@ fn generate_ci_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn vectorize(editions: &HashMap<String, u32>) -> Vec<(String, String, u32)> {
-    let mut editions_vector = editions
-        .iter()
-        .map(|entry| {
-            (
-                entry.0.clone(),
-                if entry.0.is_empty() {
-                    String::from("na")
+/// The lists of crates by what their GitHub Actions workflows do.
+#[expect(clippy::too_many_lines)]
+fn github_workflow_cases() -> Vec<(&'static str, &'static str, CrateFilter)> {
+    vec![
+        (
+            "github-actions-run-tests",
+            "GitHub Actions running cargo test",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_tests),
+        ),
+        (
+            "github-actions-run-clippy",
+            "GitHub Actions running cargo clippy",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_clippy),
+        ),
+        (
+            "github-actions-check-fmt",
+            "GitHub Actions running cargo fmt --check",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_fmt_check),
+        ),
+        (
+            "github-actions-run-doc",
+            "GitHub Actions running cargo doc",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_doc),
+        ),
+        (
+            "github-actions-run-miri",
+            "GitHub Actions running Miri",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_miri),
+        ),
+        (
+            "github-actions-run-coverage",
+            "GitHub Actions measuring test coverage",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_coverage),
+        ),
+        (
+            "github-actions-test-beta",
+            "GitHub Actions using the beta toolchain",
+            CrateFilter::new(|krate: &&Crate| {
+                krate
+                    .vcs_details
+                    .github_workflows
+                    .toolchains
+                    .contains("beta")
+            }),
+        ),
+        (
+            "github-actions-test-nightly",
+            "GitHub Actions using the nightly toolchain",
+            CrateFilter::new(|krate: &&Crate| {
+                krate
+                    .vcs_details
+                    .github_workflows
+                    .toolchains
+                    .contains("nightly")
+            }),
+        ),
+        (
+            "github-actions-test-msrv",
+            "GitHub Actions using a specific Rust version (MSRV)",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.tests_msrv()),
+        ),
+        (
+            "github-actions-on-windows",
+            "GitHub Actions running on Windows",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_on_windows()),
+        ),
+        (
+            "github-actions-on-macos",
+            "GitHub Actions running on macOS",
+            CrateFilter::new(|krate: &&Crate| krate.vcs_details.github_workflows.runs_on_macos()),
+        ),
+        (
+            "github-actions-pinned",
+            "GitHub Actions with all the actions pinned to a commit",
+            CrateFilter::new(|krate: &&Crate| {
+                let ci = &krate.vcs_details.github_workflows;
+                !ci.pinned_actions.is_empty() && ci.floating_actions.is_empty()
+            }),
+        ),
+        (
+            "github-actions-floating",
+            "GitHub Actions using actions by branch or tag",
+            CrateFilter::new(|krate: &&Crate| {
+                !krate
+                    .vcs_details
+                    .github_workflows
+                    .floating_actions
+                    .is_empty()
+            }),
+        ),
+        (
+            "github-actions-risky",
+            "GitHub Actions with risky patterns",
+            CrateFilter::new(|krate: &&Crate| {
+                !krate.vcs_details.github_workflows.findings.is_empty()
+            }),
+        ),
+        (
+            "github-actions-invalid",
+            "GitHub Actions with workflows we could not parse",
+            CrateFilter::new(|krate: &&Crate| {
+                !krate
+                    .vcs_details
+                    .github_workflows
+                    .invalid_workflows
+                    .is_empty()
+            }),
+        ),
+    ]
+}
+
+/// Count the crates by the values `values` returns for each one, the most common value first.
+fn count_crates_by(
+    crates: &[Crate],
+    values: impl Fn(&Crate) -> Vec<String>,
+) -> Vec<(String, usize)> {
+    let mut count: HashMap<String, usize> = HashMap::new();
+    for value in crates.iter().flat_map(values) {
+        *count.entry(value).or_default() += 1;
+    }
+    let mut counted = count.into_iter().collect::<Vec<(String, usize)>>();
+    #[expect(clippy::min_ident_chars)]
+    counted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counted
+}
+
+/// The adoption of the tools in `TOOLS` and the most common settings of some of them.
+fn generate_tooling_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_tooling_pages");
+
+    let pages = TOOLS
+        .iter()
+        .map(|tool| {
+            (
+                format!("has-tool-{}", tool.0),
+                format!("Crates using {}", tool.0),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+    let mut tools: Vec<StatEntry> = vec![];
+    for (tool, page) in TOOLS.iter().zip(&pages) {
+        let count = render_filtered_crates(
+            &page.0,
+            &page.1,
+            |krate| krate.vcs_details.tooling.has(tool.0),
+            crates,
+        )?;
+        tools.push(StatEntry {
+            path: &page.0,
+            title: &page.1,
+            count,
+            percentage: percentage(count, crates.len()),
+        });
+    }
+
+    // nightly-2024-01-01 => nightly
+    let toolchain_channels = count_crates_by(crates, |krate| {
+        let channel = &krate.vcs_details.tooling.toolchain_channel;
+        if channel.is_empty() {
+            return vec![];
+        }
+        let name = if channel.starts_with("nightly") {
+            "nightly"
+        } else {
+            channel
+        };
+        vec![name.to_owned()]
+    });
+    let deny_checks = count_crates_by(crates, |krate| {
+        krate.vcs_details.tooling.deny_checks.clone()
+    });
+    let dependabot_updates = count_crates_by(crates, |krate| {
+        krate.vcs_details.tooling.dependabot_updates.clone()
+    });
+    let pre_commit_hooks = count_crates_by(crates, |krate| {
+        krate.vcs_details.tooling.pre_commit_hooks.clone()
+    });
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/tooling.html")?;
+
+    let filename = get_site_folder().join("tooling.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Tooling adoption",
+        "tools": tools,
+        "settings": [
+            ["Toolchain channel in rust-toolchain(.toml)", toolchain_channels],
+            ["cargo-deny checks", deny_checks],
+            ["Dependabot updates", dependabot_updates],
+            ["pre-commit hooks", pre_commit_hooks.into_iter().take(20).collect::<Vec<_>>()],
+        ],
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The lint groups and the most frequently triggered lints of `cargo clippy` on the released crates
+/// and a page for each lint listing the crates triggering it.
+fn generate_clippy_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    static RE_SLUG: LazyLock<Regex> = LazyLock::new(|| Regex::new("^[a-z0-9_]+$").unwrap());
+    let _a = ElapsedTimer::new("generate_clippy_pages");
+
+    let checked = crates
+        .iter()
+        .filter(|krate| !krate.clippy.version.is_empty())
+        .collect::<Vec<_>>();
+    let compiled = checked
+        .iter()
+        .filter(|krate| krate.clippy.compiled())
+        .count();
+
+    // group => (crates, warnings) and lint => (group, crates, warnings)
+    let mut count_by_group: HashMap<&str, (usize, usize)> = HashMap::new();
+    let mut count_by_lint: HashMap<&str, (&str, usize, usize)> = HashMap::new();
+    for krate in &checked {
+        for (group, count) in &krate.clippy.groups {
+            let entry = count_by_group.entry(group).or_default();
+            entry.0 += 1;
+            entry.1 += count;
+        }
+        for (code, lint) in &krate.clippy.lints {
+            let entry = count_by_lint.entry(code).or_insert((&lint.group, 0, 0));
+            entry.1 += 1;
+            entry.2 += lint.count;
+        }
+    }
+
+    let mut groups = count_by_group.into_iter().collect::<Vec<_>>();
+    #[expect(clippy::min_ident_chars)]
+    groups.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(b.0)));
+    let mut lints = count_by_lint.into_iter().collect::<Vec<_>>();
+    #[expect(clippy::min_ident_chars)]
+    lints.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(b.0)));
+
+    let mut lint_entries = vec![];
+    for (code, (group, crate_count, count)) in lints {
+        let slug = lint_slug(code);
+        if RE_SLUG.captures(slug).is_none() {
+            log::error!("Invalid lint: {code}");
+            continue;
+        }
+        render_filtered_crates(
+            &format!("clippy/{slug}"),
+            &format!("Crates triggering the {code} lint"),
+            |krate| krate.clippy.lints.contains_key(code),
+            crates,
+        )?;
+        lint_entries.push(liquid::object!({
+            "code": code,
+            "slug": slug,
+            "group": group,
+            "crates": crate_count,
+            "count": count,
+        }));
+    }
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/clippy.html")?;
+
+    let filename = get_site_folder().join("clippy/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Clippy Stats",
+        "checked": checked.len(),
+        "compiled": compiled,
+        "groups": groups,
+        "lints": lint_entries,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The outcome of `cargo check` on the released crates, a page for each outcome
+/// and a page for each of the most common compiler errors.
+fn generate_build_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    static RE_ERROR_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^E\d{4}$").unwrap());
+    let _a = ElapsedTimer::new("generate_build_pages");
+
+    let outcomes = [
+        (BuildOutcome::Success, "Crates that build on stable"),
+        (
+            BuildOutcome::CompileError,
+            "Crates that no longer build on stable",
+        ),
+        (
+            BuildOutcome::DependencyError,
+            "Crates with dependencies we could not resolve offline or compile",
+        ),
+        (BuildOutcome::Timeout, "Crates where cargo check timed out"),
+        (BuildOutcome::Failure, "Crates where cargo check failed"),
+    ];
+    let mut outcome_entries = vec![];
+    for (outcome, title) in outcomes {
+        let count = render_filtered_crates(
+            &format!("builds/{}", outcome.name()),
+            title,
+            |krate| krate.build.outcome == outcome,
+            crates,
+        )?;
+        outcome_entries.push(liquid::object!({
+            "name": outcome.name(),
+            "title": title,
+            "count": count,
+        }));
+    }
+
+    let errors = count_crates_by(crates, |krate| {
+        if krate.build.outcome == BuildOutcome::CompileError {
+            vec![krate.build.error_code.clone()]
+        } else {
+            vec![]
+        }
+    });
+    let mut error_entries = vec![];
+    for (code, count) in errors {
+        let has_page = RE_ERROR_CODE.is_match(&code);
+        if has_page {
+            render_filtered_crates(
+                &format!("builds/{code}"),
+                &format!("Crates failing to build with error {code}"),
+                |krate| {
+                    krate.build.outcome == BuildOutcome::CompileError
+                        && krate.build.error_code == code
+                },
+                crates,
+            )?;
+        }
+        error_entries.push(liquid::object!({
+            "code": code,
+            "has_page": has_page,
+            "count": count,
+        }));
+    }
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/builds.html")?;
+
+    let filename = get_site_folder().join("builds/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let checked = crates
+        .iter()
+        .filter(|krate| krate.build.outcome != BuildOutcome::NotChecked)
+        .count();
+    let rustc_versions = count_crates_by(crates, |krate| {
+        if krate.build.rustc_version.is_empty() {
+            vec![]
+        } else {
+            vec![krate.build.rustc_version.clone()]
+        }
+    });
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Build Stats",
+        "checked": checked,
+        "rustc_versions": rustc_versions,
+        "outcomes": outcome_entries,
+        "errors": error_entries,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The distribution of the number of tests in the repositories and the lists of repositories with failing tests.
+fn generate_test_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_test_pages");
+
+    // the test binaries ran, even if some of the tests failed
+    let ran = |krate: &&Crate| krate.vcs_details.cargo_test.suites > 0;
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let repositories = crates
+        .iter()
+        .filter(|krate| !krate.vcs_details.cargo_test.commit.is_empty())
+        .filter(|krate| seen.insert(resolve_repository_url(&krate.repository).unwrap_or_default()))
+        .collect::<Vec<_>>();
+    let passing = repositories
+        .iter()
+        .filter(|krate| krate.vcs_details.cargo_test.succeeded())
+        .count();
+    let failing = repositories
+        .iter()
+        .filter(|krate| krate.vcs_details.cargo_test.failed > 0)
+        .count();
+    let broken = repositories.iter().filter(|krate| !ran(krate)).count();
+
+    render_filtered_crates(
+        "tests/failing",
+        "Crates with failing tests",
+        |krate| krate.vcs_details.cargo_test.failed > 0,
+        crates,
+    )?;
+    render_filtered_crates(
+        "tests/broken",
+        "Crates where the tests could not be compiled or run",
+        |krate| !krate.vcs_details.cargo_test.commit.is_empty() && !ran(krate),
+        crates,
+    )?;
+
+    let mut buckets = vec![];
+    for (slug, label, min, max) in TEST_COUNT_BUCKETS {
+        let in_bucket = |krate: &&Crate| {
+            ran(krate) && (min..=max).contains(&krate.vcs_details.cargo_test.tests)
+        };
+        render_filtered_crates(
+            &format!("tests/{slug}"),
+            &format!("Crates with {label} tests"),
+            in_bucket,
+            crates,
+        )?;
+        buckets.push(liquid::object!({
+            "slug": slug,
+            "label": label,
+            "count": repositories.iter().copied().filter(in_bucket).count(),
+        }));
+    }
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/tests.html")?;
+
+    let filename = get_site_folder().join("tests/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Test Stats",
+        "checked": repositories.len(),
+        "passing": passing,
+        "failing": failing,
+        "broken": broken,
+        "buckets": buckets,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The slug of the `DOC_QUALITY` bucket of the crate, `None` if we have not measured it or it has no public items.
+const fn doc_quality(krate: &Crate) -> Option<&'static str> {
+    let coverage = &krate.crate_details.doc_coverage;
+    let (documented, public) = (coverage.documented_items, coverage.public_items);
+    if !coverage.measured || public == 0 {
+        None
+    } else if documented == public {
+        Some("fully-documented")
+    } else if 100 * documented >= 80 * public {
+        Some("mostly-documented")
+    } else if 100 * documented >= 50 * public {
+        Some("half-documented")
+    } else if documented > 0 {
+        Some("poorly-documented")
+    } else {
+        Some("undocumented")
+    }
+}
+
+/// The quality of the documentation of the released crates according to rustdoc
+/// and the use of the `missing_docs` lint.
+fn generate_documentation_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_documentation_pages");
+
+    let measured = crates
+        .iter()
+        .filter(|krate| krate.crate_details.doc_coverage.measured)
+        .collect::<Vec<_>>();
+
+    let mut quality = vec![];
+    for (slug, title) in DOC_QUALITY {
+        let count = render_filtered_crates(
+            &format!("docs/{slug}"),
+            title,
+            |krate| doc_quality(krate) == Some(slug),
+            crates,
+        )?;
+        quality.push(liquid::object!({
+            "slug": slug,
+            "title": title,
+            "count": count,
+        }));
+    }
+
+    let without_crate_docs = render_filtered_crates(
+        "docs/no-crate-docs",
+        "Crates without crate level documentation",
+        |krate| {
+            krate.crate_details.doc_coverage.measured
+                && !krate.crate_details.doc_coverage.has_crate_docs
+        },
+        crates,
+    )?;
+    let with_examples = measured
+        .iter()
+        .filter(|krate| krate.crate_details.doc_coverage.items_with_examples > 0)
+        .count();
+
+    let mut lint_levels = vec![];
+    for level in MISSING_DOCS_LEVELS {
+        let count = render_filtered_crates(
+            &format!("docs/missing-docs-{level}"),
+            &format!("Crates setting the missing_docs lint to {level}"),
+            |krate| {
+                krate
+                    .crate_details
+                    .missing_docs_lint
+                    .as_deref()
+                    .unwrap_or("none")
+                    == level
+            },
+            crates,
+        )?;
+        lint_levels.push(liquid::object!({
+            "level": level,
+            "count": count,
+        }));
+    }
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/docs.html")?;
+
+    let filename = get_site_folder().join("docs/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Documentation Quality",
+        "measured": measured.len(),
+        "quality": quality,
+        "without_crate_docs": without_crate_docs,
+        "with_examples": with_examples,
+        "lint_levels": lint_levels,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The releases where the public API changed in an incompatible way without a major version bump.
+fn generate_semver_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_semver_pages");
+
+    let with_violations = render_filtered_crates(
+        "semver/violations",
+        "Crates that released breaking changes in a minor or patch version",
+        |krate| krate.semver_history.iter().any(|diff| diff.violation),
+        crates,
+    )?;
+
+    let compared = crates
+        .iter()
+        .flat_map(|krate| krate.semver_history.iter().map(move |diff| (krate, diff)))
+        .collect::<Vec<_>>();
+    let failed = compared
+        .iter()
+        .filter(|release| !release.1.error.is_empty())
+        .count();
+    let breaking = compared.iter().filter(|release| release.1.breaking).count();
+
+    let mut violations = compared
+        .iter()
+        .filter(|release| release.1.violation)
+        .collect::<Vec<_>>();
+    violations.sort_by(|first, second| second.1.checked_at.cmp(&first.1.checked_at));
+    let releases = violations
+        .iter()
+        .take(PAGE_SIZE)
+        .map(|release| {
+            liquid::object!({
+                "name": release.0.name,
+                "old_version": release.1.old_version,
+                "new_version": release.1.new_version,
+                "bump": release.1.bump,
+                "removed_count": release.1.removed_count,
+                "changed_count": release.1.changed_count,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/semver.html")?;
+
+    let filename = get_site_folder().join("semver/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Semver Checks",
+        "compared": compared.len(),
+        "failed": failed,
+        "breaking": breaking,
+        "violations": violations.len(),
+        "with_violations": with_violations,
+        "releases": releases,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+/// The color of the coverage badge: green from 80%, yellow from 50%, red below that.
+const fn coverage_color(covered: usize, count: usize) -> &'static str {
+    if 100 * covered >= 80 * count {
+        "#4c1"
+    } else if 100 * covered >= 50 * count {
+        "#dfb317"
+    } else {
+        "#e05d44"
+    }
+}
+
+/// The coverage leaderboard of the crates and a coverage badge for each crate we measured.
+/// The numbers are those of the files in the folder of the crate, not the totals of its repository.
+fn generate_coverage_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("generate_coverage_pages");
+
+    let badge_template = liquid::ParserBuilder::with_stdlib()
+        .build()?
+        .parse_file("templates/coverage-badge.svg")?;
+    let mut measured_crates = crates
+        .iter()
+        .filter(|krate| krate.vcs_details.coverage.measured)
+        .filter_map(|krate| Some((krate, krate.vcs_details.coverage.crates.get(&krate.name)?)))
+        .collect::<Vec<_>>();
+    for &(krate, coverage) in &measured_crates {
+        let globals = liquid::object!({
+            "coverage": coverage.line_coverage,
+            "color": coverage_color(coverage.covered_lines, coverage.lines),
+        });
+        let svg = badge_template.render(&globals)?;
+        let filename = get_site_folder()
+            .join("coverage")
+            .join(format!("{}.svg", krate.name));
+        fs::write(filename, svg)?;
+    }
+
+    measured_crates.retain(|&(_, coverage)| coverage.lines > 0);
+    // compare covered_a / lines_a with covered_b / lines_b without floating point numbers
+    #[expect(clippy::min_ident_chars)]
+    measured_crates.sort_by(|&(_, a), &(_, b)| {
+        (b.covered_lines * a.lines)
+            .cmp(&(a.covered_lines * b.lines))
+            .then_with(|| b.lines.cmp(&a.lines))
+    });
+    let leaderboard = measured_crates
+        .iter()
+        .take(PAGE_SIZE)
+        .map(|&(krate, coverage)| {
+            liquid::object!({
+                "name": krate.name,
+                "lines": coverage.lines,
+                "line_coverage": coverage.line_coverage,
+                "function_coverage": coverage.function_coverage,
+                "commit": krate.vcs_details.coverage.commit,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let partials = load_templates()?;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .filter(Commafy)
+        .partials(partials)
+        .build()?
+        .parse_file("templates/coverage.html")?;
+
+    let filename = get_site_folder().join("coverage/index.html");
+    let utc: DateTime<Utc> = Utc::now();
+    let globals = liquid::object!({
+        "version": format!("{VERSION}"),
+        "utc":     format!("{}", utc),
+        "title":   "Test Coverage",
+        "measured": measured_crates.len(),
+        "crates": leaderboard,
+    });
+    let html = template.render(&globals)?;
+    let mut file = File::create(filename)?;
+    writeln!(&mut file, "{html}")?;
+
+    Ok(())
+}
+
+fn vectorize(editions: &HashMap<String, u32>) -> Vec<(String, String, u32)> {
+    let mut editions_vector = editions
+        .iter()
+        .map(|entry| {
+            (
+                entry.0.clone(),
+                if entry.0.is_empty() {
+                    String::from("na")
                 } else {
                     entry.0.clone()
                 },
@@ -1508,12 +2866,76 @@ fn vectorize(editions: &HashMap<String, u32>) -> Vec<(String, String, u32)> {
     editions_vector
 }
 
-fn generate_msrv_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
+/// Normalise the value of a `rust-version` field so `1.70` and `1.70.0` are counted together.
+/// Returns `na` if the field is missing and `malformed` if it cannot be parsed.
+fn normalize_rust_version(value: Option<&str>) -> String {
+    value.map_or_else(
+        || String::from("na"),
+        |text| {
+            RustVersion::parse(text)
+                .map_or_else(|| String::from("malformed"), |version| version.to_string())
+        },
+    )
+}
+
+fn has_malformed_msrv(krate: &Crate) -> bool {
+    krate
+        .declared_rust_version()
+        .is_some_and(|text| RustVersion::parse(text).is_none())
+}
+
+/// The declared MSRV is older than the first Rust release supporting the declared edition.
+fn has_impossible_msrv(krate: &Crate) -> bool {
+    let Some(msrv) = krate.declared_rust_version().and_then(RustVersion::parse) else {
+        return false;
+    };
+
+    krate
+        .cargo
+        .package
+        .edition
+        .as_deref()
+        .and_then(minimum_rust_version_for_edition)
+        .is_some_and(|required| msrv < required)
+}
+
+/// Compare the MSRV in the released Cargo.toml with the `rust_version` Crates.io recorded for the same release.
+/// Crates.io did not record it for old releases, an empty value is not a difference.
+fn msrv_differs_from_crates_io(krate: &Crate, crates_io_rust_version: &str) -> bool {
+    if crates_io_rust_version.trim().is_empty() {
+        return false;
+    }
+    let declared = krate.declared_rust_version().unwrap_or_default();
+    match (
+        RustVersion::parse(declared),
+        RustVersion::parse(crates_io_rust_version),
+    ) {
+        (Some(declared_version), Some(crates_io_version)) => declared_version != crates_io_version,
+        _ => declared.trim() != crates_io_rust_version.trim(),
+    }
+}
+
+/// The source code uses a feature that was stabilized in a Rust release newer than the declared MSRV.
+fn has_msrv_lower_than_inferred(krate: &Crate) -> bool {
+    let Some(msrv) = krate.declared_rust_version().and_then(RustVersion::parse) else {
+        return false;
+    };
+
+    krate
+        .crate_details
+        .inferred_rust_version
+        .as_deref()
+        .and_then(RustVersion::parse)
+        .is_some_and(|inferred| msrv < inferred)
+}
+
+fn generate_msrv_pages(crates: &[Crate], versions: &[CrateVersion]) -> Result<(), Box<dyn Error>> {
     log::info!("start generate_msrv_pages");
 
     let mut editions: HashMap<String, u32> = HashMap::new();
     let mut rust_versions: HashMap<String, u32> = HashMap::new();
     let mut rust_dash_versions: HashMap<String, u32> = HashMap::new();
+    let mut msrv_by_minor: HashMap<RustVersion, u32> = HashMap::new();
 
     for krate in crates {
         let key1 = krate
@@ -1524,23 +2946,28 @@ fn generate_msrv_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
             .map_or_else(|| String::from("na"), core::clone::Clone::clone);
         *editions.entry(key1).or_insert(0) += 1;
 
-        let key2 = krate
-            .cargo
-            .package
-            .rust_version
-            .as_ref()
-            .map_or_else(|| String::from("na"), core::clone::Clone::clone);
+        let key2 = normalize_rust_version(krate.cargo.package.rust_version.as_deref());
         *rust_versions.entry(key2).or_insert(0) += 1;
 
-        let key3 = krate
-            .cargo
-            .package
-            .rust_dash_version
-            .as_ref()
-            .map_or_else(|| String::from("na"), core::clone::Clone::clone);
+        let key3 = normalize_rust_version(krate.cargo.package.rust_dash_version.as_deref());
         *rust_dash_versions.entry(key3).or_insert(0) += 1;
+
+        if let Some(msrv) = krate.declared_rust_version().and_then(RustVersion::parse) {
+            *msrv_by_minor
+                .entry(RustVersion::new(msrv.major, msrv.minor, 0))
+                .or_insert(0) += 1;
+        }
     }
 
+    let mut msrv_by_minor_vector = msrv_by_minor
+        .into_iter()
+        .collect::<Vec<(RustVersion, u32)>>();
+    msrv_by_minor_vector.sort_by_key(|entry| core::cmp::Reverse(entry.0));
+    let msrv_histogram = msrv_by_minor_vector
+        .iter()
+        .map(|entry| (entry.0.minor_release(), entry.1))
+        .collect::<Vec<(String, u32)>>();
+
     log::info!("editions {editions:#?}");
     log::info!("rust_version {rust_versions:#?}");
     log::info!("rust_dash_version {rust_dash_versions:#?}");
@@ -1548,6 +2975,7 @@ fn generate_msrv_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
     let editions_vector = vectorize(&editions);
     let rust_versions_vector = vectorize(&rust_versions);
     let rust_dash_versions_vector = vectorize(&rust_dash_versions);
+    let consistency = generate_msrv_consistency_pages(crates, versions)?;
 
     let partials = load_templates()?;
 
@@ -1567,6 +2995,8 @@ fn generate_msrv_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
         "editions": editions_vector,
         "rust_versions": rust_versions_vector,
         "rust_dash_versions": rust_dash_versions_vector,
+        "msrv_histogram": msrv_histogram,
+        "consistency": consistency,
     });
     let html = template.render(&globals)?;
     let mut file = File::create(filename)?;
@@ -1575,11 +3005,108 @@ fn generate_msrv_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
     list_crates_with_edition(editions_vector, crates)?;
     list_crates_with_rust_version(rust_versions_vector, crates)?;
     list_crates_with_rust_dash_version(rust_dash_versions_vector, crates)?;
+    list_crates_with_msrv_minor_release(&msrv_by_minor_vector, crates)?;
 
     log::info!("end generate_msrv_pages");
     Ok(())
 }
 
+/// List the crates where the MSRV is malformed, contradicts the edition or the source code, or differs from what Crates.io recorded.
+/// Returns the number of crates in each list.
+fn generate_msrv_consistency_pages(
+    crates: &[Crate],
+    versions: &[CrateVersion],
+) -> Result<Vec<StatEntry<'static>>, Box<dyn Error>> {
+    let crates_io_rust_version = versions
+        .iter()
+        .map(|version| {
+            (
+                (version.crate_id.as_str(), version.num.as_str()),
+                version.rust_version.as_str(),
+            )
+        })
+        .collect::<HashMap<(&str, &str), &str>>();
+
+    let malformed = render_filtered_crates(
+        "msrv-malformed",
+        "Crates with a malformed rust-version field",
+        |krate| has_malformed_msrv(krate),
+        crates,
+    )?;
+
+    let impossible = render_filtered_crates(
+        "msrv-older-than-edition",
+        "Crates declaring an MSRV older than their edition requires",
+        |krate| has_impossible_msrv(krate),
+        crates,
+    )?;
+
+    let differs = render_filtered_crates(
+        "msrv-differs-from-crates-io",
+        "Crates where the MSRV in Cargo.toml differs from the one recorded by Crates.io",
+        |krate| {
+            crates_io_rust_version
+                .get(&(krate.id.as_str(), krate.cargo.package.version.as_str()))
+                .is_some_and(|rust_version| msrv_differs_from_crates_io(krate, rust_version))
+        },
+        crates,
+    )?;
+
+    let lower_than_inferred = render_filtered_crates(
+        "msrv-lower-than-inferred",
+        "Crates declaring an MSRV older than the language features they use",
+        |krate| has_msrv_lower_than_inferred(krate),
+        crates,
+    )?;
+
+    Ok(vec![
+        StatEntry {
+            path: "msrv-malformed",
+            title: "Malformed rust-version",
+            count: malformed,
+            percentage: percentage(malformed, crates.len()),
+        },
+        StatEntry {
+            path: "msrv-older-than-edition",
+            title: "MSRV older than the edition requires",
+            count: impossible,
+            percentage: percentage(impossible, crates.len()),
+        },
+        StatEntry {
+            path: "msrv-differs-from-crates-io",
+            title: "MSRV differs from the one recorded by Crates.io",
+            count: differs,
+            percentage: percentage(differs, crates.len()),
+        },
+        StatEntry {
+            path: "msrv-lower-than-inferred",
+            title: "MSRV older than the language features used in the source",
+            count: lower_than_inferred,
+            percentage: percentage(lower_than_inferred, crates.len()),
+        },
+    ])
+}
+
+fn list_crates_with_msrv_minor_release(
+    msrv_by_minor: &[(RustVersion, u32)],
+    crates: &[Crate],
+) -> Result<(), Box<dyn Error>> {
+    for &(minor, _count) in msrv_by_minor {
+        render_filtered_crates(
+            &format!("msrv-{}", minor.minor_release()),
+            &format!("Crates with MSRV {}.x", minor.minor_release()),
+            |krate| {
+                krate
+                    .declared_rust_version()
+                    .and_then(RustVersion::parse)
+                    .is_some_and(|msrv| msrv.major == minor.major && msrv.minor == minor.minor)
+            },
+            crates,
+        )?;
+    }
+    Ok(())
+}
+
 fn list_crates_with_rust_dash_version(
     rust_dash_versions_vector: Vec<(String, String, u32)>,
     crates: &[Crate],
@@ -1593,12 +3120,7 @@ fn list_crates_with_rust_dash_version(
             ),
             |krate| {
                 rust_dash_version.0
-                    == krate
-                        .cargo
-                        .package
-                        .rust_dash_version
-                        .as_ref()
-                        .map_or_else(|| String::from("na"), core::clone::Clone::clone)
+                    == normalize_rust_version(krate.cargo.package.rust_dash_version.as_deref())
             },
             crates,
         )?;
@@ -1616,12 +3138,7 @@ fn list_crates_with_rust_version(
             &format!("Crates with rust_version field being '{}'", rust_version.0),
             |krate| {
                 rust_version.0
-                    == krate
-                        .cargo
-                        .package
-                        .rust_version
-                        .as_ref()
-                        .map_or_else(|| String::from("na"), core::clone::Clone::clone)
+                    == normalize_rust_version(krate.cargo.package.rust_version.as_deref())
             },
             crates,
         )?;
@@ -1769,11 +3286,23 @@ fn generate_rustfmt_pages(crates: &[Crate]) -> Result<(), Box<dyn Error>> {
 }
 
 #[test]
+#[expect(clippy::tests_outside_test_module)]
 fn check_load_templates() {
     let _partials = load_templates();
 }
 
 #[test]
+#[expect(clippy::tests_outside_test_module, clippy::redundant_test_prefix)]
 fn test_get_repo_types() {
     let _repos = get_repo_types();
 }
+
+#[test]
+#[expect(clippy::tests_outside_test_module)]
+fn check_msrv_differs_from_crates_io() {
+    let mut krate = Crate::new();
+    krate.cargo.package.rust_dash_version = Some(String::from("1.70"));
+    assert!(!msrv_differs_from_crates_io(&krate, ""));
+    assert!(!msrv_differs_from_crates_io(&krate, "1.70.0"));
+    assert!(msrv_differs_from_crates_io(&krate, "1.65"));
+}
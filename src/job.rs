@@ -0,0 +1,78 @@
+use std::error::Error;
+use std::path::Path;
+
+use clap::Parser;
+
+use rust_digger::{
+    for_each_cloned_repository, load_job_result, read_crates, save_job_result, Crate, ElapsedTimer,
+    Runner,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(long, help = "The name of the job in runner.yaml, e.g. clippy.")]
+    job: String,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of git repositories to run the job on."
+    )]
+    limit: u32,
+
+    #[arg(
+        long,
+        default_value = "runner.yaml",
+        help = "The configuration of the container and the jobs."
+    )]
+    config: String,
+}
+
+/// Run one of the jobs of `runner.yaml` on the cloned repositories and save the results.
+/// Repositories where we already ran the job on the current commit are skipped.
+fn main() {
+    let args = Cli::parse();
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("start running job {}", args.job);
+
+    match run(&args) {
+        Ok(()) => {}
+        Err(err) => log::error!("Error: {err}"),
+    }
+}
+
+fn run(args: &Cli) -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("job.rs");
+
+    let runner = Runner::load(Path::new(&args.config))?;
+    if runner.job(&args.job).is_none() {
+        return Err(format!(
+            "Unknown job '{}', available jobs: {}",
+            args.job,
+            runner.job_names().join(", ")
+        )
+        .into());
+    }
+    runner.build_image(Path::new("."));
+
+    let crates: Vec<Crate> = read_crates(0)?;
+    for_each_cloned_repository(&crates, args.limit, |repo| {
+        if load_job_result(&args.job, &repo.key).is_some_and(|result| result.commit == repo.commit)
+        {
+            log::info!(
+                "{} already ran on {} of {}",
+                args.job,
+                repo.commit,
+                repo.key
+            );
+            return Ok(false);
+        }
+
+        log::info!("repo: {}", repo.key);
+        let mut result = runner.run(&args.job, &repo.path)?;
+        result.commit.clone_from(&repo.commit);
+        save_job_result(&repo.key, &result)?;
+        Ok(true)
+    })
+}
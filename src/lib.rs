@@ -1,6 +1,6 @@
 #![allow(clippy::pub_use)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fs;
@@ -12,14 +12,100 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
-use git_digger::{RepoPlatform, Repository};
+use git_digger::RepoPlatform;
+
+mod api_diff;
+pub use api_diff::{api_surface, load_semver_history, save_semver_history, version_bump, ApiDiff};
+
+mod repository_url;
+pub use repository_url::{
+    canonical_repository_url, load_repository_redirects, parse_repository_url,
+    resolve_repository_url, save_repository_redirects, RepositoryUrl,
+};
+
+mod clone_state;
+pub use clone_state::{parse_retry_after, CloneFailure, CloneMode, CloneState};
+
+mod disk_usage;
+pub use disk_usage::{load_disk_usage, save_disk_usage, DiskUsage, SpaceUsed};
+
+mod build_report;
+pub use build_report::{load_build_report, save_build_report, BuildOutcome, BuildReport};
+
+mod cargo_messages;
 
 mod cargo_toml_parser;
-pub use cargo_toml_parser::Cargo;
+pub use cargo_toml_parser::{load_cargo_toml_simplified, Cargo};
+
+mod ci;
+pub use ci::{github_workflows, CiDetails, CiFinding, CiRisk};
+
+mod ci_providers;
+pub use ci_providers::{
+    ci_providers, detect_ci_providers, gitlab_ci_includes, CiProvider, LegacyCiFlags,
+};
+
+mod clippy_report;
+pub use clippy_report::{
+    download_clippy_lints, lint_slug, load_clippy_lint_groups, load_clippy_report,
+    save_clippy_report, ClippyReport, LintCount,
+};
+
+mod community;
+pub use community::{
+    changelog_in_folder_mentions_version, changelog_mentions_version, find_changelog,
+    find_community_files, COMMUNITY_FILES,
+};
+
+mod coverage_report;
+pub use coverage_report::CoverageReport;
+
+mod doc_coverage;
+pub use doc_coverage::{missing_docs_lint, DocCoverage};
+
+mod fmt_report;
+pub use fmt_report::FmtReport;
+
+mod git;
+pub use git::{
+    find_version_tag, git_commit_exists, git_hash_files, git_lines, git_output, git_tags,
+    git_tree_blobs, version_tag_candidates,
+};
+
+mod git_history;
+pub use git_history::{
+    git_history, git_last_commit_date, github_login_from_email, Author, GitHistory,
+};
+
+mod release_comparison;
+pub use release_comparison::{load_release_comparison, save_release_comparison, ReleaseComparison};
+
+mod release_tags;
+pub use release_tags::{reconcile_release_tags, tag_name, ReleaseTags, TagReport, TAG_CONVENTIONS};
+
+mod runner;
+pub use runner::{
+    for_each_cloned_repository, load_job_result, save_job_result, ClonedRepository, Engine, Job,
+    JobResult, Runner, RunnerConfig, SOURCE_MOUNT, WORK_MOUNT,
+};
+
+mod rust_version;
+pub use rust_version::{minimum_rust_version_for_edition, RustVersion};
+
+mod rustdoc_json;
+
+mod source_scan;
+pub use source_scan::{inferred_rust_version, scan_rust_features, RustFeature};
+
+mod test_report;
+pub use test_report::TestReport;
 
 mod timer;
 pub use timer::ElapsedTimer;
 
+mod tooling;
+pub use tooling::{detect_tooling, Tooling, TOOLS};
+
 #[expect(clippy::struct_excessive_bools)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CrateDetails {
@@ -34,6 +120,41 @@ pub struct CrateDetails {
     pub has_main_rs: bool,
     pub nonstandard_folders: Vec<String>,
     pub size: u64,
+
+    #[serde(default = "no_entries")]
+    pub binaries: Vec<Binary>,
+
+    #[serde(default = "none")]
+    pub default_dash_run: Option<String>,
+
+    #[serde(default = "no_entries")]
+    pub rust_features: Vec<RustFeature>,
+
+    #[serde(default = "none")]
+    pub inferred_rust_version: Option<String>,
+
+    /// The changelog included in the released crate, empty if there is none.
+    #[serde(default = "empty_string")]
+    pub changelog: String,
+
+    /// The changelog in the released crate mentions the version of the release.
+    #[serde(default = "default_false")]
+    pub changelog_mentions_version: bool,
+
+    /// The level of the `missing_docs` lint, e.g. `deny` for `#![deny(missing_docs)]`.
+    #[serde(default = "none")]
+    pub missing_docs_lint: Option<String>,
+
+    /// How well the public API is documented, according to rustdoc.
+    #[serde(default = "empty_doc_coverage")]
+    pub doc_coverage: DocCoverage,
+}
+
+/// A binary target of a crate: the name of the executable `cargo install` creates and the path of its source.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Binary {
+    pub name: String,
+    pub path: String,
 }
 
 impl CrateDetails {
@@ -50,6 +171,14 @@ impl CrateDetails {
             has_main_rs: false,
             nonstandard_folders: vec![],
             size: 0,
+            binaries: vec![],
+            default_dash_run: None,
+            rust_features: vec![],
+            inferred_rust_version: None,
+            changelog: String::new(),
+            changelog_mentions_version: false,
+            missing_docs_lint: None,
+            doc_coverage: DocCoverage::new(),
         }
     }
 
@@ -97,7 +226,32 @@ impl CrateDetails {
         Ok(())
     }
 
-    pub fn disk_size(&mut self, root: &PathBuf) {
+    /// Look for language features and std APIs with a known stabilisation version in the source code
+    /// and use the newest one as the minimum Rust version the crate needs.
+    pub fn scan_source(&mut self, path: &Path) {
+        self.rust_features = scan_rust_features(path);
+        log::info!("rust_features: {:?}", self.rust_features);
+        self.inferred_rust_version =
+            inferred_rust_version(&self.rust_features).map(|version| version.to_string());
+    }
+
+    /// Check if the `missing_docs` lint is enabled in `src/lib.rs` or in the `Cargo.toml` of the released crate.
+    pub fn check_missing_docs_lint(&mut self, path: &Path) {
+        let lib_rs = fs::read_to_string(path.join("src/lib.rs")).unwrap_or_default();
+        let cargo_toml = fs::read_to_string(path.join("Cargo.toml")).unwrap_or_default();
+        self.missing_docs_lint = missing_docs_lint(&lib_rs, &cargo_toml);
+    }
+
+    /// Look for a changelog in the released crate and check if it has an entry for the released version.
+    pub fn check_changelog(&mut self, path: &Path, version: &str) {
+        if let Some(changelog) = find_changelog(path) {
+            self.changelog_mentions_version =
+                changelog_in_folder_mentions_version(path, &changelog, version);
+            self.changelog = changelog;
+        }
+    }
+
+    pub fn disk_size(&mut self, root: &Path) {
         self.size = disk_usage(root);
     }
 
@@ -116,7 +270,8 @@ impl Default for CrateDetails {
     }
 }
 
-fn disk_usage(root: &PathBuf) -> u64 {
+/// The total size of the files in the folder.
+pub fn disk_usage(root: &Path) -> u64 {
     let mut size = 0;
     for dir_entry in WalkDir::new(root).into_iter().flatten() {
         if dir_entry.path().is_file() {
@@ -132,30 +287,42 @@ const REPO_FOLDERS: [&str; 2] = ["github", "gitlab"];
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[expect(clippy::struct_excessive_bools)]
 pub struct VCSDetails {
-    #[serde(default = "default_false")]
-    pub has_github_action: bool,
-    #[serde(default = "default_false")]
-    pub has_gitlab_pipeline: bool,
-    #[serde(default = "default_false")]
-    pub has_circle_ci: bool,
-    #[serde(default = "default_false")]
-    pub has_cirrus_ci: bool,
-    #[serde(default = "default_false")]
-    pub has_travis_ci: bool,
-    #[serde(default = "default_false")]
-    pub has_jenkins: bool,
-    #[serde(default = "default_false")]
-    pub has_appveyor: bool,
-    #[serde(default = "default_false")]
-    pub has_azure_pipeline: bool,
-    #[serde(default = "default_false")]
-    pub has_bitbucket_pipeline: bool,
+    /// The names of the CI systems configured in the repository, see `ci_providers.yaml`.
+    #[serde(default = "no_entries")]
+    pub ci_providers: Vec<String>,
+
+    /// The `has_*` fields of the old JSON files, `load_vcs_details` converts them to `ci_providers`.
+    #[serde(flatten, skip_serializing)]
+    pub legacy_ci: LegacyCiFlags,
+
+    /// The files and templates included by `.gitlab-ci.yml`, e.g. `local: ci/test.yml` or `template: Rust.gitlab-ci.yml`.
+    #[serde(default = "no_entries")]
+    pub gitlab_ci_includes: Vec<String>,
 
     pub commit_count: i32,
     pub cargo_toml_in_root: bool,
-    pub cargo_fmt: String,
 
-    #[serde(default = "empty_string")]
+    /// The result of `cargo fmt --check`, old files have an (always empty) string here.
+    #[serde(
+        default = "empty_fmt_report",
+        deserialize_with = "fmt_report::fmt_report_or_legacy_string"
+    )]
+    pub cargo_fmt: FmtReport,
+
+    /// The result of `cargo test --no-fail-fast`.
+    #[serde(default = "empty_test_report")]
+    pub cargo_test: TestReport,
+
+    /// The test coverage measured by `cargo llvm-cov`.
+    #[serde(default = "empty_coverage_report")]
+    pub coverage: CoverageReport,
+
+    /// The result of the last attempts to clone or pull the repository.
+    #[serde(default = "empty_clone_state")]
+    pub clone_state: CloneState,
+
+    /// The error of the clone in old files, `load_vcs_details` moves it to `clone_state`.
+    #[serde(default = "empty_string", skip_serializing)]
     pub git_clone_error: String,
 
     #[serde(default = "default_false")]
@@ -163,30 +330,108 @@ pub struct VCSDetails {
 
     #[serde(default = "default_false")]
     pub has_dot_rustfmt_toml: bool,
+
+    /// The root Cargo.toml has a `[workspace]` section.
+    #[serde(default = "default_false")]
+    pub is_workspace: bool,
+
+    /// The folders of the workspace members relative to the root of the repository.
+    #[serde(default = "no_entries")]
+    pub workspace_members: Vec<String>,
+
+    /// Every package in the repository: the name of the package and the folder of its Cargo.toml.
+    #[serde(default = "empty_map")]
+    pub packages: BTreeMap<String, String>,
+
+    /// Crates that link to this repository, but that we could not find in it.
+    #[serde(default = "no_entries")]
+    pub missing_crates: Vec<String>,
+
+    /// The naming convention of the release tags, e.g. `v{version}`, empty if no release is tagged.
+    #[serde(default = "empty_string")]
+    pub tag_convention: String,
+
+    /// The releases and the untagged releases of each crate in the repository.
+    #[serde(default = "empty_map")]
+    pub release_tags: BTreeMap<String, ReleaseTags>,
+
+    /// Tags that look like a version number, but don't belong to any release.
+    #[serde(default = "no_entries")]
+    pub tags_without_release: Vec<String>,
+
+    #[serde(default = "empty_history")]
+    pub history: GitHistory,
+
+    /// What the GitHub Actions workflows do.
+    #[serde(default = "empty_ci_details")]
+    pub github_workflows: CiDetails,
+
+    /// Automation beyond CI, e.g. Dependabot or cargo-deny.
+    #[serde(default = "empty_tooling")]
+    pub tooling: Tooling,
+
+    /// The community files in the repository (see `COMMUNITY_FILES`) and their path.
+    #[serde(default = "empty_map")]
+    pub community_files: BTreeMap<String, String>,
+
+    /// The name of each crate in the repository and whether the changelog mentions its latest release.
+    #[serde(default = "empty_map")]
+    pub changelog_has_latest_release: BTreeMap<String, bool>,
 }
 
 impl VCSDetails {
     pub const fn new() -> Self {
         Self {
-            has_github_action: false,
-            has_gitlab_pipeline: false,
-            has_circle_ci: false,
-            has_cirrus_ci: false,
-            has_travis_ci: false,
-            has_jenkins: false,
-            has_appveyor: false,
-            has_azure_pipeline: false,
-            has_bitbucket_pipeline: false,
+            ci_providers: vec![],
+            legacy_ci: LegacyCiFlags::new(),
+            gitlab_ci_includes: vec![],
 
             commit_count: 0,
             cargo_toml_in_root: false,
-            cargo_fmt: String::new(),
+            cargo_fmt: FmtReport::new(),
+            cargo_test: TestReport::new(),
+            coverage: CoverageReport::new(),
             has_rustfmt_toml: false,
             has_dot_rustfmt_toml: false,
-
+            is_workspace: false,
+            workspace_members: vec![],
+            packages: BTreeMap::new(),
+            missing_crates: vec![],
+            tag_convention: String::new(),
+            release_tags: BTreeMap::new(),
+            tags_without_release: vec![],
+            history: GitHistory::new(),
+            github_workflows: CiDetails::new(),
+            tooling: Tooling::new(),
+            community_files: BTreeMap::new(),
+            changelog_has_latest_release: BTreeMap::new(),
+
+            clone_state: CloneState::new(),
             git_clone_error: String::new(),
         }
     }
+
+    /// The CI system with the given name (e.g. `github-actions`) is configured in the repository.
+    pub fn has_ci(&self, name: &str) -> bool {
+        self.ci_providers.iter().any(|provider| provider == name)
+    }
+
+    /// Files written before we had `ci_providers` only have one boolean field per CI system.
+    fn upgrade_legacy_ci(&mut self) {
+        if self.ci_providers.is_empty() {
+            self.ci_providers = self.legacy_ci.providers();
+        }
+        self.legacy_ci = LegacyCiFlags::new();
+    }
+
+    /// Files written before we had `clone_state` only have the error of the clone.
+    fn upgrade_legacy_clone_error(&mut self) {
+        if !self.git_clone_error.is_empty() && !self.clone_state.has_failed() {
+            self.clone_state.error = core::mem::take(&mut self.git_clone_error);
+            self.clone_state.consecutive_failures = 1;
+            self.clone_state.failure = Some(CloneFailure::from_git_error(&self.clone_state.error));
+        }
+    }
 }
 
 impl Default for VCSDetails {
@@ -261,6 +506,25 @@ pub struct Crate {
 
     #[serde(default = "empty_crate_details")]
     pub crate_details: CrateDetails,
+
+    #[serde(default = "empty_release_comparison")]
+    pub release_comparison: ReleaseComparison,
+
+    /// The result of `cargo clippy` on the released crate.
+    #[serde(default = "empty_clippy_report")]
+    pub clippy: ClippyReport,
+
+    /// The result of `cargo check` on the released crate.
+    #[serde(default = "empty_build_report")]
+    pub build: BuildReport,
+
+    /// The public API changes between the releases we compared, the oldest first.
+    #[serde(default = "no_entries")]
+    pub semver_history: Vec<ApiDiff>,
+
+    /// The number of downloads in the period covered by the database dump.
+    #[serde(default = "get_zero")]
+    pub recent_downloads: usize,
 }
 
 impl Crate {
@@ -284,7 +548,60 @@ impl Crate {
             vcs_details: VCSDetails::new(),
             cargo: Cargo::new(),
             crate_details: CrateDetails::new(),
+            release_comparison: ReleaseComparison::new(),
+            clippy: ClippyReport::new(),
+            build: BuildReport::new(),
+            semver_history: vec![],
+            recent_downloads: 0,
+        }
+    }
+
+    /// The minimum supported Rust version as declared in the Cargo.toml file.
+    /// Most crates use `rust-version`, some use `rust_version`.
+    pub fn declared_rust_version(&self) -> Option<&str> {
+        self.cargo.package.rust_dash_version.as_deref().or(self
+            .cargo
+            .package
+            .rust_version
+            .as_deref())
+    }
+
+    /// The folder of the crate inside its repository, `.` if it is in the root.
+    pub fn path_in_repository(&self) -> Option<&str> {
+        self.vcs_details
+            .packages
+            .get(&self.name)
+            .map(String::as_str)
+    }
+
+    /// The canonical URL of the new repository if the repository of the crate was renamed or moved.
+    pub fn moved_repository(&self) -> Option<String> {
+        let canonical = canonical_repository_url(&self.repository)?.url;
+        let resolved = resolve_repository_url(&self.repository)?;
+        (canonical != resolved).then_some(resolved)
+    }
+
+    /// What is wrong with the link to the repository of the crate.
+    /// A link to a moved repository is outdated even if we could clone the new one.
+    /// Only the failures about the repository count, not our own problems, e.g. a timeout or a full disk.
+    pub fn broken_repository_link(&self) -> Option<CloneFailure> {
+        if self.moved_repository().is_some() {
+            return Some(CloneFailure::Renamed);
         }
+        self.vcs_details
+            .clone_state
+            .failure
+            .filter(|failure| failure.is_about_repository())
+    }
+
+    /// How the releases of the crate are tagged in its repository.
+    pub fn release_tags(&self) -> Option<&ReleaseTags> {
+        self.vcs_details.release_tags.get(&self.name)
+    }
+
+    /// The repository was cloned and analyzed, but the crate is not in it.
+    pub fn missing_from_repository(&self) -> bool {
+        self.vcs_details.missing_crates.contains(&self.name)
     }
 }
 impl Default for Crate {
@@ -355,6 +672,50 @@ const fn empty_crate_details() -> CrateDetails {
     CrateDetails::new()
 }
 
+const fn empty_history() -> GitHistory {
+    GitHistory::new()
+}
+
+const fn empty_ci_details() -> CiDetails {
+    CiDetails::new()
+}
+
+const fn empty_tooling() -> Tooling {
+    Tooling::new()
+}
+
+const fn empty_fmt_report() -> FmtReport {
+    FmtReport::new()
+}
+
+const fn empty_doc_coverage() -> DocCoverage {
+    DocCoverage::new()
+}
+
+const fn empty_coverage_report() -> CoverageReport {
+    CoverageReport::new()
+}
+
+const fn empty_clone_state() -> CloneState {
+    CloneState::new()
+}
+
+const fn empty_test_report() -> TestReport {
+    TestReport::new()
+}
+
+const fn empty_release_comparison() -> ReleaseComparison {
+    ReleaseComparison::new()
+}
+
+const fn empty_build_report() -> BuildReport {
+    BuildReport::new()
+}
+
+const fn empty_clippy_report() -> ClippyReport {
+    ClippyReport::new()
+}
+
 const fn empty_string() -> String {
     String::new()
 }
@@ -367,6 +728,18 @@ const fn default_false() -> bool {
     false
 }
 
+const fn no_entries<T>() -> Vec<T> {
+    Vec::new()
+}
+
+const fn empty_map<K, V>() -> BTreeMap<K, V> {
+    BTreeMap::new()
+}
+
+const fn none<T>() -> Option<T> {
+    None
+}
+
 pub fn get_data_folder() -> PathBuf {
     PathBuf::from("data")
 }
@@ -387,6 +760,58 @@ pub fn crates_root() -> PathBuf {
     get_data_folder().join("crates")
 }
 
+/// Cargo only accepts a folder of `crates_root` as part of a vendored registry if it has this file.
+pub const CARGO_CHECKSUM_FILE: &str = ".cargo-checksum.json";
+
+/// The folders we moved out of `crates_root` as they had no `CARGO_CHECKSUM_FILE`.
+pub fn crates_without_checksum_root() -> PathBuf {
+    get_data_folder().join("crates-without-checksum")
+}
+
+/// Move the folders without `CARGO_CHECKSUM_FILE` out of `crates_root`.
+///
+/// Cargo refuses a vendored registry if any of its folders lacks the checksum file, e.g. the crates
+/// extracted by an old version of download-crates. We move them away before download-crates fetches them again,
+/// so every binary mounting `crates_root` as a registry can rely on it.
+///
+/// # Errors
+///
+/// If we cannot read `crates_root` or move one of its folders.
+pub fn quarantine_crates_without_checksum() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("quarantine_crates_without_checksum");
+
+    let mut moved: usize = 0;
+    for entry in crates_root().read_dir()? {
+        let crate_path = entry?.path();
+        if crate_path.join(CARGO_CHECKSUM_FILE).exists() {
+            continue;
+        }
+        let Some(dirname) = crate_path.file_name() else {
+            continue;
+        };
+        let target = crates_without_checksum_root().join(dirname);
+        log::warn!(
+            "No {CARGO_CHECKSUM_FILE} in {:?}, moving it to {:?}",
+            crate_path.display(),
+            target.display()
+        );
+        fs::create_dir_all(crates_without_checksum_root())?;
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(&crate_path, &target)?;
+        moved += 1;
+    }
+    log::info!("Moved {moved} crates without {CARGO_CHECKSUM_FILE}");
+
+    Ok(())
+}
+
+/// The release before the newest one of each crate, we compare their public API.
+pub fn previous_crates_root() -> PathBuf {
+    get_data_folder().join("previous-crates")
+}
+
 pub fn analyzed_crates_root() -> PathBuf {
     get_data_folder().join("analyzed-crates")
 }
@@ -399,6 +824,30 @@ pub fn collected_data_root() -> PathBuf {
     get_data_folder().join("collected-data")
 }
 
+pub fn release_comparison_root() -> PathBuf {
+    get_data_folder().join("release-comparison")
+}
+
+/// The results of `cargo clippy` on the released crates.
+pub fn clippy_root() -> PathBuf {
+    get_data_folder().join("clippy")
+}
+
+/// The results of `cargo check` on the released crates.
+pub fn builds_root() -> PathBuf {
+    get_data_folder().join("builds")
+}
+
+/// The public API changes between the releases of each crate.
+pub fn semver_root() -> PathBuf {
+    get_data_folder().join("semver")
+}
+
+/// The results of the jobs we run in containers, see `runner.yaml`.
+pub fn job_results_root() -> PathBuf {
+    get_data_folder().join("job-results")
+}
+
 /// Creates the data folders we need if they do not exist.
 pub fn create_data_folders() -> Result<(), Box<dyn Error>> {
     for folder in [
@@ -407,6 +856,7 @@ pub fn create_data_folders() -> Result<(), Box<dyn Error>> {
         get_db_dump_folder(),
         get_temp_folder(),
         crates_root(),
+        previous_crates_root(),
         analyzed_crates_root(),
         // repo_details_root(),
         // collected_data_root(),
@@ -420,13 +870,20 @@ pub fn create_data_folders() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// rustdoc only documents the library of the crate.
+pub fn has_library(crate_path: &Path) -> bool {
+    crate_path.join("src/lib.rs").exists()
+        || fs::read_to_string(crate_path.join("Cargo.toml"))
+            .is_ok_and(|cargo_toml| cargo_toml.contains("[lib]"))
+}
+
 pub fn percentage(num: usize, total: usize) -> String {
     let total_f32 = (10000.0 * num as f32 / total as f32).floor();
     (total_f32 / 100.0).to_string()
 }
 
 pub fn get_vcs_details_path(url: &str) -> Option<PathBuf> {
-    let repository = Repository::from_url(url);
+    let repository = parse_repository_url(url);
     if url.is_empty() {
         log::warn!("Repository URL is empty, not saving details");
         return None;
@@ -458,8 +915,12 @@ pub fn load_vcs_details(repository: &str) -> VCSDetails {
 
     match File::open(&details_path) {
         Ok(file) => {
-            match serde_json::from_reader(file) {
-                Ok(details) => return details,
+            match serde_json::from_reader::<_, VCSDetails>(file) {
+                Ok(mut details) => {
+                    details.upgrade_legacy_ci();
+                    details.upgrade_legacy_clone_error();
+                    return details;
+                }
                 Err(err) => {
                     log::error!(
                         "Error reading details from '{}' {}",
@@ -499,7 +960,7 @@ pub fn save_details(repository: &str, details: &VCSDetails) -> Result<(), Box<dy
         return Ok(());
     }
 
-    match Repository::from_url(repository) {
+    match parse_repository_url(repository) {
         Ok(repo) => {
             let _res = fs::create_dir_all(repo.owner_path(repo_details_root().as_path()));
             let mut details_path = repo.path(repo_details_root().as_path());
@@ -602,6 +1063,44 @@ pub fn add_cargo_toml_to_crates(crates: Vec<Crate>) -> Result<Vec<Crate>, Box<dy
     Ok(updated_crates)
 }
 
+#[derive(Debug, Deserialize)]
+struct VersionDownloads {
+    version_id: String,
+    downloads: usize,
+}
+
+/// The number of downloads of each crate (by crate id) in the period covered by the database dump.
+/// The dump only contains the downloads of the last 90 days.
+pub fn read_recent_downloads(
+    versions: &[CrateVersion],
+) -> Result<HashMap<String, usize>, Box<dyn Error>> {
+    let filepath = get_db_dump_folder().join("data/version_downloads.csv");
+    if !filepath.exists() {
+        log::warn!("File {:?} does not exist", filepath.display());
+        return Ok(HashMap::new());
+    }
+    log::info!("Start reading {:?}", filepath.display());
+
+    let crate_by_version = versions
+        .iter()
+        .map(|version| (version.id.as_str(), version.crate_id.as_str()))
+        .collect::<HashMap<&str, &str>>();
+
+    let mut downloads: HashMap<String, usize> = HashMap::new();
+    let file = File::open(&filepath)?;
+    let mut rdr = csv::Reader::from_reader(file);
+    for result in rdr.deserialize() {
+        let record: VersionDownloads = result?;
+        if let Some(crate_id) = crate_by_version.get(record.version_id.as_str()) {
+            *downloads.entry((*crate_id).to_owned()).or_default() += record.downloads;
+        }
+    }
+
+    log::info!("Finished reading {:?}", filepath.display());
+
+    Ok(downloads)
+}
+
 /// Reads the `crates.csv` file (the database dump from Crates.io) and returns a vector of `Crate` structs.
 ///
 /// # Errors
@@ -658,6 +1157,7 @@ mod tests {
     //use crate::repo_details_root;
 
     #[test]
+    #[expect(clippy::redundant_test_prefix)]
     fn test_percentage() {
         assert_eq!(percentage(20, 100), "20");
         assert_eq!(percentage(5, 20), "25");
@@ -666,6 +1166,7 @@ mod tests {
     }
 
     #[test]
+    #[expect(clippy::redundant_test_prefix)]
     fn test_get_details_path() {
         let expected = repo_details_root()
             .join("github.com")
@@ -679,15 +1180,81 @@ mod tests {
         );
 
         assert_eq!(
-            get_vcs_details_path("https://github.com/foo/bar/baz")
+            get_vcs_details_path("http://www.github.com/foo/bar.git")
+                .expect("X")
+                .as_path(),
+            expected
+        );
+        assert_eq!(
+            get_vcs_details_path("https://github.com/foo/bar/tree/main/baz")
                 .expect("X")
                 .as_path(),
             expected
-        ); // TODO this should not work I think
+        );
+        assert_eq!(get_vcs_details_path("https://github.com/foo/bar/baz"), None);
         assert_eq!(get_vcs_details_path("https://zorg.com/foo/bar"), None);
     }
 
     #[test]
+    fn check_upgrade_legacy_ci() {
+        let mut details: VCSDetails = serde_json::from_str(
+            r#"{"has_github_action": true, "has_cirrus_ci": true, "has_travis_ci": false,
+            "commit_count": 1, "cargo_toml_in_root": true, "cargo_fmt": ""}"#,
+        )
+        .unwrap();
+        details.upgrade_legacy_ci();
+        assert_eq!(details.ci_providers, vec!["github-actions", "cirrus-ci"]);
+        assert_eq!(details.cargo_fmt, FmtReport::new());
+        assert!(details.has_ci("cirrus-ci"));
+        assert!(!details.has_ci("travis-ci"));
+
+        let content = serde_json::to_string(&details).unwrap();
+        assert!(!content.contains("has_github_action"));
+        let mut reloaded: VCSDetails = serde_json::from_str(&content).unwrap();
+        reloaded.upgrade_legacy_ci();
+        assert_eq!(reloaded.ci_providers, details.ci_providers);
+    }
+
+    #[test]
+    fn check_broken_repository_link() {
+        let mut krate = Crate::new();
+        krate.repository = String::from("https://github.com/foo/bar");
+        assert_eq!(krate.broken_repository_link(), None);
+
+        for (stderr, expected) in [
+            (
+                "remote: Repository not found.\nfatal: repository 'https://github.com/foo/bar/' not found",
+                Some(CloneFailure::NotFound),
+            ),
+            ("fatal: Remote branch main not found in upstream origin", None),
+            ("error: cannot open .git/FETCH_HEAD: Permission denied", None),
+            ("fatal: write error: No space left on device", None),
+            ("CONFLICT (content): Merge conflict in src/lib.rs", None),
+        ] {
+            krate.vcs_details.clone_state.failure = Some(CloneFailure::from_git_error(stderr));
+            assert_eq!(krate.broken_repository_link(), expected, "{stderr}");
+        }
+
+        krate.vcs_details.clone_state.failure = Some(CloneFailure::Timeout);
+        assert_eq!(krate.broken_repository_link(), None);
+    }
+
+    #[test]
+    fn check_upgrade_legacy_clone_error() {
+        let mut details: VCSDetails = serde_json::from_str(
+            r#"{"commit_count": 1, "cargo_toml_in_root": true, "cargo_fmt": "", "git_clone_error": "status 404"}"#,
+        )
+        .unwrap();
+        details.upgrade_legacy_clone_error();
+        assert!(details.clone_state.has_failed());
+        assert_eq!(details.clone_state.error, "status 404");
+
+        let content = serde_json::to_string(&details).unwrap();
+        assert!(!content.contains("git_clone_error"));
+    }
+
+    #[test]
+    #[expect(clippy::shadow_unrelated)]
     fn check_build_path() {
         // empty
         let path = build_path(PathBuf::from("root"), &[], None);
@@ -706,15 +1273,16 @@ mod tests {
     }
 
     #[test]
+    #[expect(clippy::shadow_unrelated)]
     fn check_disk_usage() {
         use tempdir::TempDir;
         let tmp_dir = TempDir::new("demo").unwrap();
-        let size = disk_usage(&tmp_dir.path().to_path_buf());
+        let size = disk_usage(tmp_dir.path());
         assert_eq!(size, 0, "Empty directory should have size 0");
 
         let text_file = tmp_dir.path().join("test.txt");
         std::fs::write(text_file, "Hello, world!").unwrap();
-        let size = disk_usage(&tmp_dir.path().to_path_buf());
+        let size = disk_usage(tmp_dir.path());
         assert_eq!(size, 13, "Disk usage is the expected value");
     }
 }
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::git::{find_version_tag, git_commit_exists, git_hash_files, git_tags, git_tree_blobs};
+use crate::release_comparison_root;
+
+/// Files Cargo generates or rewrites when it packages a crate. They are never the same as in the repository.
+/// `.cargo-checksum.json` is added by us when we use the released crates as a vendored registry.
+const GENERATED_FILES: [&str; 4] = [
+    ".cargo_vcs_info.json",
+    "Cargo.toml",
+    "Cargo.lock",
+    ".cargo-checksum.json",
+];
+
+/// The result of comparing the released tarball of a crate with the content of its repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools)]
+pub struct ReleaseComparison {
+    /// We had both the release and the clone of the repository to compare.
+    pub compared: bool,
+
+    /// The tarball contains a `.cargo_vcs_info.json` file.
+    pub has_vcs_info: bool,
+
+    /// The commit the crate was published from according to `.cargo_vcs_info.json`.
+    pub sha1: String,
+
+    /// The folder of the crate in the repository according to `.cargo_vcs_info.json`,
+    /// or where we found its Cargo.toml if the release has no such file.
+    pub path_in_vcs: String,
+
+    /// The crate was published with uncommitted changes (`cargo publish --allow-dirty`).
+    pub dirty: bool,
+
+    /// The `sha1` exists in the repository.
+    pub commit_found: bool,
+
+    /// The tag matching the version, we use it if there is no usable `sha1`.
+    pub tag: String,
+
+    /// The files of the tarball that are not in the repository.
+    pub only_in_tarball: Vec<String>,
+
+    /// The files of the tarball that are in the repository with a different content.
+    pub different_content: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoVcsInfo {
+    git: Option<CargoVcsInfoGit>,
+    #[serde(default)]
+    path_in_vcs: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoVcsInfoGit {
+    sha1: String,
+    #[serde(default)]
+    dirty: bool,
+}
+
+impl ReleaseComparison {
+    pub const fn new() -> Self {
+        Self {
+            compared: false,
+            has_vcs_info: false,
+            sha1: String::new(),
+            path_in_vcs: String::new(),
+            dirty: false,
+            commit_found: false,
+            tag: String::new(),
+            only_in_tarball: vec![],
+            different_content: vec![],
+        }
+    }
+
+    /// Compare the extracted release in `crate_path` with the cloned repository in `repo_path`.
+    /// `path_in_repository` is the folder of the crate in the current clone, see `VCSDetails::packages`.
+    pub fn compare(
+        crate_path: &Path,
+        repo_path: &Path,
+        name: &str,
+        version: &str,
+        path_in_repository: Option<&str>,
+    ) -> Self {
+        let mut comparison = Self::new();
+        comparison.compared = true;
+        comparison.read_vcs_info(crate_path);
+        if !comparison.has_vcs_info {
+            path_in_repository
+                .filter(|path| *path != ".")
+                .unwrap_or_default()
+                .clone_into(&mut comparison.path_in_vcs);
+        }
+
+        if comparison.sha1.is_empty() || !comparison.commit_found_in(repo_path) {
+            if let Some(tag) = find_version_tag(&git_tags(repo_path), name, version) {
+                comparison.tag = tag;
+            }
+        }
+
+        let revision = comparison.revision().to_owned();
+        if revision.is_empty() {
+            log::info!("Could not find the commit of {name} {version}");
+            return comparison;
+        }
+
+        let blobs = git_tree_blobs(repo_path, &revision);
+        let files = list_files(crate_path);
+        let Ok(absolute_crate_path) = fs::canonicalize(crate_path) else {
+            return comparison;
+        };
+        let Some(hashes) = git_hash_files(
+            repo_path,
+            &files
+                .iter()
+                .map(|file| absolute_crate_path.join(file))
+                .collect::<Vec<PathBuf>>(),
+        ) else {
+            return comparison;
+        };
+
+        for (file, hash) in files.iter().zip(hashes) {
+            if GENERATED_FILES.contains(&file.as_str()) {
+                continue;
+            }
+            let repository_file = if file == "Cargo.toml.orig" {
+                "Cargo.toml"
+            } else {
+                file
+            };
+            match find_blob(&blobs, &comparison.path_in_vcs, repository_file) {
+                None => comparison.only_in_tarball.push(file.clone()),
+                Some(blob) if *blob != hash => comparison.different_content.push(file.clone()),
+                Some(_) => {}
+            }
+        }
+
+        comparison
+    }
+
+    /// The revision we compare to, the commit from `.cargo_vcs_info.json` or the tag.
+    pub fn revision(&self) -> &str {
+        if self.commit_found {
+            &self.sha1
+        } else {
+            &self.tag
+        }
+    }
+
+    fn commit_found_in(&mut self, repo_path: &Path) -> bool {
+        self.commit_found = git_commit_exists(repo_path, &self.sha1);
+        self.commit_found
+    }
+
+    fn read_vcs_info(&mut self, crate_path: &Path) {
+        let Ok(content) = fs::read_to_string(crate_path.join(".cargo_vcs_info.json")) else {
+            return;
+        };
+        self.has_vcs_info = true;
+
+        match serde_json::from_str::<CargoVcsInfo>(&content) {
+            Ok(info) => {
+                self.path_in_vcs = info.path_in_vcs;
+                if let Some(git) = info.git {
+                    self.sha1 = git.sha1;
+                    self.dirty = git.dirty;
+                }
+            }
+            Err(err) => log::error!("Error parsing .cargo_vcs_info.json: {err}"),
+        }
+    }
+
+    /// There is something to look at in the comparison.
+    pub const fn has_problems(&self) -> bool {
+        self.dirty || !self.only_in_tarball.is_empty() || !self.different_content.is_empty()
+    }
+}
+
+impl Default for ReleaseComparison {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find a file of the tarball in the repository. Cargo copies files from outside of the crate (e.g. a `README.md`
+/// or a `LICENSE` from the root of a workspace) to the root of the crate so for these we also check the root of
+/// the repository.
+fn find_blob<'blobs>(
+    blobs: &'blobs HashMap<String, String>,
+    path_in_vcs: &str,
+    file: &str,
+) -> Option<&'blobs String> {
+    let path = if path_in_vcs.is_empty() {
+        file.to_owned()
+    } else {
+        format!("{path_in_vcs}/{file}")
+    };
+
+    blobs.get(&path).or_else(|| {
+        if file.contains('/') {
+            None
+        } else {
+            blobs.get(file)
+        }
+    })
+}
+
+/// The files of the extracted crate, relative to its root and using `/` as separator.
+fn list_files(crate_path: &Path) -> Vec<String> {
+    let mut files = WalkDir::new(crate_path)
+        .into_iter()
+        .flatten()
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| {
+            entry.path().strip_prefix(crate_path).ok().map(|path| {
+                path.components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+        })
+        .collect::<Vec<String>>();
+    files.sort();
+    files
+}
+
+fn release_comparison_path(name: &str, version: &str) -> PathBuf {
+    release_comparison_root().join(format!("{name}-{version}.json"))
+}
+
+pub fn load_release_comparison(name: &str, version: &str) -> ReleaseComparison {
+    let filepath = release_comparison_path(name, version);
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return ReleaseComparison::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        ReleaseComparison::new()
+    })
+}
+
+pub fn save_release_comparison(
+    name: &str,
+    version: &str,
+    comparison: &ReleaseComparison,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(release_comparison_root())?;
+    let content = serde_json::to_string(comparison)?;
+    fs::write(release_comparison_path(name, version), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_find_blob() {
+        let blobs = HashMap::from([
+            (String::from("README.md"), String::from("aaa")),
+            (String::from("crates/foo/src/lib.rs"), String::from("bbb")),
+            (String::from("crates/foo/Cargo.toml"), String::from("ccc")),
+        ]);
+
+        assert_eq!(
+            find_blob(&blobs, "crates/foo", "src/lib.rs"),
+            Some(&String::from("bbb"))
+        );
+        assert_eq!(
+            find_blob(&blobs, "crates/foo", "README.md"),
+            Some(&String::from("aaa"))
+        );
+        assert_eq!(find_blob(&blobs, "crates/foo", "src/main.rs"), None);
+        assert_eq!(find_blob(&blobs, "", "src/lib.rs"), None);
+        assert_eq!(
+            find_blob(&blobs, "", "README.md"),
+            Some(&String::from("aaa"))
+        );
+    }
+
+    #[test]
+    fn check_read_vcs_info() {
+        let info: CargoVcsInfo = serde_json::from_str(
+            r#"{"git": {"sha1": "0123abcd", "dirty": true}, "path_in_vcs": "crates/foo"}"#,
+        )
+        .unwrap();
+        assert_eq!(info.path_in_vcs, "crates/foo");
+        let git = info.git.unwrap();
+        assert_eq!(git.sha1, "0123abcd");
+        assert!(git.dirty);
+
+        let clean: CargoVcsInfo = serde_json::from_str(r#"{"git": {"sha1": "0123abcd"}}"#).unwrap();
+        assert_eq!(clean.path_in_vcs, "");
+        assert!(!clean.git.unwrap().dirty);
+    }
+}
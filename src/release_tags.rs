@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The naming conventions of release tags.
+///
+/// `{crate}` and `{version}` are replaced by the name and the version of the crate.
+/// Repositories with a single crate usually use `v{version}` or `{version}`, monorepos usually include the name of the crate.
+pub const TAG_CONVENTIONS: [&str; 9] = [
+    "v{version}",
+    "{version}",
+    "{crate}-v{version}",
+    "{crate}-{version}",
+    "{crate}/v{version}",
+    "{crate}/{version}",
+    "{crate}@v{version}",
+    "{crate}@{version}",
+    "{crate}_v{version}",
+];
+
+/// How the releases of a crate are tagged in its repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct ReleaseTags {
+    /// The number of versions released to Crates.io.
+    pub released: usize,
+
+    /// The released versions without a matching tag in the repository.
+    pub untagged: Vec<String>,
+}
+
+/// The result of matching the tags of a repository with the releases of the crates in it.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct TagReport {
+    /// The convention most of the tagged releases follow, empty if none of them is tagged.
+    pub convention: String,
+
+    /// The tags of each crate in the repository.
+    pub crates: BTreeMap<String, ReleaseTags>,
+
+    /// Tags that look like a version number, but don't belong to any of the releases.
+    pub tags_without_release: Vec<String>,
+}
+
+#[expect(clippy::literal_string_with_formatting_args)]
+pub fn tag_name(convention: &str, name: &str, version: &str) -> String {
+    convention
+        .replace("{crate}", name)
+        .replace("{version}", version)
+}
+
+/// Match the tags of a repository with the released versions of the crates in it.
+/// `releases` maps the name of each crate to the list of its released versions.
+pub fn reconcile_release_tags(tags: &[String], releases: &BTreeMap<&str, Vec<&str>>) -> TagReport {
+    static VERSION_TAG: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?$").unwrap()
+    });
+
+    let tag_set = tags.iter().map(String::as_str).collect::<HashSet<&str>>();
+    let mut release_tags: HashSet<String> = HashSet::new();
+    let mut convention_counts = [0; TAG_CONVENTIONS.len()];
+    let mut report = TagReport::default();
+
+    for (name, versions) in releases {
+        let mut crate_tags = ReleaseTags {
+            released: versions.len(),
+            untagged: vec![],
+        };
+        for version in versions {
+            let mut tagged = false;
+            for (convention, count) in TAG_CONVENTIONS.iter().zip(convention_counts.iter_mut()) {
+                let tag = tag_name(convention, name, version);
+                if tag_set.contains(tag.as_str()) {
+                    *count += 1;
+                    tagged = true;
+                }
+                release_tags.insert(tag);
+            }
+            if !tagged {
+                crate_tags.untagged.push((*version).to_owned());
+            }
+        }
+        report.crates.insert((*name).to_owned(), crate_tags);
+    }
+
+    // In case of a tie the convention listed first wins.
+    let mut best: Option<(usize, &str)> = None;
+    for (convention, count) in TAG_CONVENTIONS.iter().zip(convention_counts) {
+        if 0 < count && best.is_none_or(|(best_count, _)| best_count < count) {
+            best = Some((count, convention));
+        }
+    }
+    report.convention = best
+        .map(|(_, convention)| convention.to_owned())
+        .unwrap_or_default();
+
+    report.tags_without_release = tags
+        .iter()
+        .filter(|tag| VERSION_TAG.is_match(tag) && !release_tags.contains(tag.as_str()))
+        .cloned()
+        .collect();
+    report.tags_without_release.sort();
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_reconcile_release_tags() {
+        let tags = [
+            "foo-v0.1.0",
+            "foo-v0.2.0",
+            "v0.2.0",
+            "foo-v0.3.0",
+            "nightly",
+        ]
+        .map(String::from)
+        .to_vec();
+        let releases = BTreeMap::from([("foo", vec!["0.1.0", "0.2.0", "0.2.1"])]);
+
+        let report = reconcile_release_tags(&tags, &releases);
+        assert_eq!(report.convention, "{crate}-v{version}");
+        assert_eq!(
+            report.crates.get("foo"),
+            Some(&ReleaseTags {
+                released: 3,
+                untagged: vec![String::from("0.2.1")],
+            })
+        );
+        assert_eq!(report.tags_without_release, vec!["foo-v0.3.0"]);
+
+        let untagged = reconcile_release_tags(&[], &releases);
+        assert_eq!(untagged.convention, "");
+        assert!(untagged.tags_without_release.is_empty());
+    }
+}
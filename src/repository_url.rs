@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use git_digger::Repository;
+
+use crate::get_data_folder;
+
+/// How many redirects we follow, in case the recorded redirects form a loop.
+const MAX_REDIRECTS: usize = 5;
+
+/// The renamed and moved repositories: the canonical URL of the old repository and of the new one.
+static REDIRECTS: LazyLock<BTreeMap<String, String>> = LazyLock::new(load_repository_redirects);
+
+/// The URL of a repository as we use it to identify the repository, e.g. `https://github.com/foo/bar`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryUrl {
+    pub url: String,
+
+    /// The folder the original URL pointed to, e.g. `crates/bar` in `https://github.com/foo/bar/tree/main/crates/bar`.
+    pub sub_path: String,
+}
+
+/// Remove the parts of a repository URL that don't change the repository.
+///
+/// `.git` at the end, `www.` in the host, the branch and the folder (`/tree/<branch>/<path>`), the query and the fragment.
+/// `http://` is replaced by `https://`. Returns `None` if this is not the URL of a repository.
+pub fn canonical_repository_url(url: &str) -> Option<RepositoryUrl> {
+    let trimmed = url.trim();
+    let without_git = trimmed.strip_prefix("git+").unwrap_or(trimmed);
+    let without_scheme = without_git
+        .strip_prefix("https://")
+        .or_else(|| without_git.strip_prefix("http://"))?;
+    let path = without_scheme.split(['?', '#']).next().unwrap_or_default();
+
+    let mut parts = path.split('/').filter(|part| !part.is_empty()).peekable();
+    let full_host = parts.next()?.to_lowercase();
+    let host = full_host.strip_prefix("www.").unwrap_or(&full_host);
+    let owner = parts.next()?.to_lowercase();
+    let full_name = parts.next()?.to_lowercase();
+    let name = full_name.strip_suffix(".git").unwrap_or(&full_name);
+    if name.is_empty() {
+        return None;
+    }
+
+    // GitLab has a `-` before the `tree`
+    if parts.peek() == Some(&"-") {
+        parts.next();
+    }
+    let sub_path = match parts.next() {
+        None => String::new(),
+        // Bitbucket uses `src` instead of `tree`
+        Some("tree" | "blob" | "src") => parts.skip(1).collect::<Vec<_>>().join("/"),
+        Some(_) => return None,
+    };
+
+    Some(RepositoryUrl {
+        url: format!("https://{host}/{owner}/{name}"),
+        sub_path,
+    })
+}
+
+/// The canonical URL of the repository after following the redirects we found when cloning it.
+pub fn resolve_repository_url(url: &str) -> Option<String> {
+    let canonical = canonical_repository_url(url)?;
+    Some(follow_redirects(canonical.url, &REDIRECTS))
+}
+
+fn follow_redirects(url: String, redirects: &BTreeMap<String, String>) -> String {
+    let mut resolved = url;
+    for _ in 0..MAX_REDIRECTS {
+        match redirects.get(&resolved) {
+            Some(target) => resolved.clone_from(target),
+            None => break,
+        }
+    }
+    resolved
+}
+
+/// Parse the URL of a repository after canonicalizing it and following the redirects.
+pub fn parse_repository_url(url: &str) -> Result<Repository, Box<dyn Error>> {
+    let resolved =
+        resolve_repository_url(url).ok_or_else(|| format!("Invalid repository URL '{url}'"))?;
+    Repository::from_url(&resolved)
+}
+
+fn repository_redirects_path() -> PathBuf {
+    get_data_folder().join("repository-redirects.json")
+}
+
+pub fn load_repository_redirects() -> BTreeMap<String, String> {
+    let filepath = repository_redirects_path();
+    let Ok(content) = fs::read_to_string(&filepath) else {
+        return BTreeMap::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        log::error!("Error parsing {:?}: {err}", filepath.display());
+        BTreeMap::new()
+    })
+}
+
+pub fn save_repository_redirects(
+    redirects: &BTreeMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    let content = serde_json::to_string_pretty(redirects)?;
+    fs::write(repository_redirects_path(), content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_canonical_repository_url() {
+        let canonical = |url: &str| canonical_repository_url(url).map(|repository| repository.url);
+        let expected = Some(String::from("https://github.com/foo/bar"));
+        assert_eq!(canonical("https://github.com/foo/bar"), expected);
+        assert_eq!(canonical("https://github.com/Foo/Bar/"), expected);
+        assert_eq!(canonical("https://github.com/foo/bar.git"), expected);
+        assert_eq!(canonical("http://www.github.com/foo/bar"), expected);
+        assert_eq!(canonical("git+https://github.com/foo/bar.git"), expected);
+        assert_eq!(canonical("https://github.com/foo/bar#readme"), expected);
+        assert_eq!(canonical("https://github.com/foo/bar/baz"), None);
+        assert_eq!(canonical("https://github.com/foo"), None);
+        assert_eq!(canonical("github.com/foo/bar"), None);
+
+        assert_eq!(
+            canonical_repository_url("https://github.com/foo/bar/tree/main/crates/Baz"),
+            Some(RepositoryUrl {
+                url: String::from("https://github.com/foo/bar"),
+                sub_path: String::from("crates/Baz"),
+            })
+        );
+        assert_eq!(
+            canonical_repository_url("https://gitlab.com/foo/bar/-/tree/master/qux")
+                .map(|repository| repository.sub_path),
+            Some(String::from("qux"))
+        );
+    }
+
+    #[test]
+    fn check_follow_redirects() {
+        let redirects = BTreeMap::from([
+            (
+                String::from("https://github.com/foo/old"),
+                String::from("https://github.com/foo/new"),
+            ),
+            (
+                String::from("https://github.com/foo/a"),
+                String::from("https://github.com/foo/b"),
+            ),
+            (
+                String::from("https://github.com/foo/b"),
+                String::from("https://github.com/foo/a"),
+            ),
+        ]);
+        assert_eq!(
+            follow_redirects(String::from("https://github.com/foo/old"), &redirects),
+            "https://github.com/foo/new"
+        );
+        assert_eq!(
+            follow_redirects(String::from("https://github.com/foo/bar"), &redirects),
+            "https://github.com/foo/bar"
+        );
+        // a loop does not hang
+        assert_eq!(
+            follow_redirects(String::from("https://github.com/foo/a"), &redirects),
+            "https://github.com/foo/b"
+        );
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{get_repos_folder, git_lines, job_results_root, parse_repository_url, Crate};
+
+/// Where we mount the source code in the container.
+pub const SOURCE_MOUNT: &str = "/crate";
+
+/// The scratch target directory in the container.
+const TARGET_MOUNT: &str = "/target";
+
+/// The writable copy of the source for the jobs with `copy_source`.
+pub const WORK_MOUNT: &str = "/work";
+
+/// How often we check if the container has finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The container engine we use, they accept the same command line options.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Engine {
+    Docker,
+    Podman,
+}
+
+impl Engine {
+    pub const fn program(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+/// A command we run in the container, e.g. `cargo clippy`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    pub command: Vec<String>,
+
+    /// The number of seconds after which we kill the container.
+    pub timeout: u64,
+
+    /// Extra environment variables, e.g. `RUSTFLAGS`.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Run the command on a writable copy of the source, e.g. because Cargo creates the `Cargo.lock` file.
+    #[serde(default)]
+    pub copy_source: bool,
+
+    /// Extra folders mounted read-only: the path on the host and the path in the container,
+    /// e.g. `data/crates: /registry`.
+    #[serde(default)]
+    pub volumes: BTreeMap<String, String>,
+}
+
+/// The content of `runner.yaml`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RunnerConfig {
+    pub engine: Engine,
+    pub image: String,
+    pub user: String,
+
+    /// The number of CPUs each container can use, passed to `--cpus`.
+    pub cpus: String,
+
+    /// The memory limit of each container, passed to `--memory`, e.g. `4g`.
+    pub memory: String,
+
+    /// The size of the tmpfs we use as the target directory.
+    pub target_size: String,
+
+    /// The size of the tmpfs holding the writable copy of the source, used by the jobs with `copy_source`.
+    /// The files in a tmpfs count against the memory of the container, so `target_size` and `work_size` together
+    /// must stay below `memory`.
+    pub work_size: String,
+
+    pub jobs: BTreeMap<String, Job>,
+}
+
+/// What happened when we ran a job.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct JobResult {
+    pub job: String,
+
+    /// The commit of the repository, empty for released crates.
+    pub commit: String,
+
+    pub started_at: String,
+    pub duration_secs: u64,
+
+    /// `None` if the job timed out or the container was killed.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl JobResult {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run the jobs of `runner.yaml` in containers.
+pub struct Runner {
+    config: RunnerConfig,
+}
+
+impl Runner {
+    pub const fn new(config: RunnerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Load the configuration from a YAML file, usually `runner.yaml`.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let config: RunnerConfig = serde_yaml::from_str(&content)?;
+        Ok(Self::new(config))
+    }
+
+    pub fn job(&self, name: &str) -> Option<&Job> {
+        self.config.jobs.get(name)
+    }
+
+    pub fn job_names(&self) -> Vec<&str> {
+        self.config.jobs.keys().map(String::as_str).collect()
+    }
+
+    /// Build the image from the Dockerfile in the given folder.
+    pub fn build_image(&self, context: &Path) -> bool {
+        log::info!("build image {}", self.config.image);
+        match Command::new(self.config.engine.program())
+            .args(["build", "-t", &self.config.image])
+            .arg(context)
+            .output()
+        {
+            Ok(result) if result.status.success() => true,
+            Ok(result) => {
+                log::warn!("{}", String::from_utf8_lossy(&result.stdout));
+                log::warn!("{}", String::from_utf8_lossy(&result.stderr));
+                false
+            }
+            Err(err) => {
+                log::error!("Could not run {}: {err}", self.config.engine.program());
+                false
+            }
+        }
+    }
+
+    /// The output of `program --version` in the image, e.g. `rustc 1.73.0 (cc66ad468 2023-10-03)`.
+    /// The program can include a toolchain, e.g. `rustc +nightly`.
+    pub fn version(&self, program: &str) -> String {
+        match Command::new(self.config.engine.program())
+            .args(["run", "--rm", "--network", "none", &self.config.image])
+            .args(program.split_whitespace())
+            .arg("--version")
+            .output()
+        {
+            Ok(result) => String::from_utf8_lossy(&result.stdout).trim().to_owned(),
+            Err(err) => {
+                log::error!("Could not get the version of {program}: {err}");
+                String::new()
+            }
+        }
+    }
+
+    /// Run the job on the source code in the given folder and wait for it to finish or to time out.
+    pub fn run(&self, job_name: &str, source: &Path) -> Result<JobResult, Box<dyn Error>> {
+        let job = self
+            .job(job_name)
+            .ok_or_else(|| format!("Unknown job '{job_name}'"))?;
+        // the engine needs an absolute path for the volume
+        let absolute_source = fs::canonicalize(source)?;
+        let volumes = job
+            .volumes
+            .iter()
+            .map(|(host, target)| Ok((fs::canonicalize(host)?, target.clone())))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        let container = container_name(job_name);
+        log::info!(
+            "run {job_name} in {container} on {:?}",
+            absolute_source.display()
+        );
+
+        let started_at = Utc::now().to_rfc3339();
+        let start = Instant::now();
+        let mut child = Command::new(self.config.engine.program())
+            .args(self.container_args(&container, job, &absolute_source, &volumes))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let timeout = Duration::from_secs(job.timeout);
+        let mut timed_out = false;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if timeout <= start.elapsed() {
+                log::warn!("{job_name} timed out after {} seconds", job.timeout);
+                timed_out = true;
+                self.kill(&container);
+                if let Err(err) = child.kill() {
+                    log::warn!("Could not kill {container}: {err}");
+                }
+                break child.wait()?;
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        let result = JobResult {
+            job: job_name.to_owned(),
+            commit: String::new(),
+            started_at,
+            duration_secs: start.elapsed().as_secs(),
+            exit_code: if timed_out { None } else { status.code() },
+            timed_out,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        };
+        log::info!(
+            "{job_name} exit code: {:?} in {} seconds",
+            result.exit_code,
+            result.duration_secs
+        );
+        Ok(result)
+    }
+
+    /// The arguments of `docker` (or `podman`) to run the job.
+    fn container_args(
+        &self,
+        container: &str,
+        job: &Job,
+        source: &Path,
+        volumes: &[(PathBuf, String)],
+    ) -> Vec<String> {
+        let mut args = vec![
+            String::from("run"),
+            String::from("--rm"),
+            String::from("--name"),
+            container.to_owned(),
+            String::from("--network"),
+            String::from("none"),
+            String::from("--cpus"),
+            self.config.cpus.clone(),
+            String::from("--memory"),
+            self.config.memory.clone(),
+            String::from("--user"),
+            self.config.user.clone(),
+            String::from("--volume"),
+            format!("{}:{SOURCE_MOUNT}:ro", source.display()),
+            String::from("--tmpfs"),
+            format!("{TARGET_MOUNT}:rw,exec,size={}", self.config.target_size),
+            String::from("--env"),
+            format!("CARGO_TARGET_DIR={TARGET_MOUNT}"),
+        ];
+        for (name, value) in &job.env {
+            args.push(String::from("--env"));
+            args.push(format!("{name}={value}"));
+        }
+        for volume in volumes {
+            args.push(String::from("--volume"));
+            args.push(format!("{}:{}:ro", volume.0.display(), volume.1));
+        }
+
+        if job.copy_source {
+            args.extend([
+                String::from("--tmpfs"),
+                format!("{WORK_MOUNT}:rw,exec,size={}", self.config.work_size),
+                String::from("--workdir"),
+                WORK_MOUNT.to_owned(),
+                self.config.image.clone(),
+                String::from("sh"),
+                String::from("-c"),
+                format!("cp -R {SOURCE_MOUNT}/. {WORK_MOUNT} && exec \"$@\""),
+                String::from("sh"),
+            ]);
+        } else {
+            args.extend([
+                String::from("--workdir"),
+                SOURCE_MOUNT.to_owned(),
+                self.config.image.clone(),
+            ]);
+        }
+        args.extend(job.command.iter().cloned());
+        args
+    }
+
+    fn kill(&self, container: &str) {
+        if let Err(err) = Command::new(self.config.engine.program())
+            .args(["kill", container])
+            .output()
+        {
+            log::warn!("Could not kill {container}: {err}");
+        }
+    }
+}
+
+/// A name that is unique among the containers we start.
+fn container_name(job_name: &str) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "rust-digger-{job_name}-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Read the output of the container in a separate thread so it cannot block when the pipe is full.
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut reader) = pipe {
+            if let Err(err) = reader.read_to_end(&mut bytes) {
+                log::warn!("Could not read the output of the container: {err}");
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// A cloned repository of one of the crates, see `for_each_cloned_repository`.
+pub struct ClonedRepository<'crates> {
+    /// The first crate pointing to this repository.
+    pub krate: &'crates Crate,
+
+    /// e.g. `github.com/szabgab/rust-digger`, we use it as the key of the job results.
+    pub key: String,
+
+    pub path: PathBuf,
+
+    /// The current commit of the clone, `git rev-parse HEAD`.
+    pub commit: String,
+}
+
+/// Call `process` once for each repository of the crates that we have cloned and that has a `Cargo.toml` file.
+///
+/// `process` returns `false` if it skipped the repository, e.g. because it already processed the current commit.
+/// `limit` is the number of repositories we process, 0 means all of them.
+pub fn for_each_cloned_repository<F>(
+    crates: &[Crate],
+    limit: u32,
+    mut process: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&ClonedRepository) -> Result<bool, Box<dyn Error>>,
+{
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut count: u32 = 0;
+    for krate in crates {
+        if 0 < limit && limit <= count {
+            break;
+        }
+        if krate.repository.is_empty() {
+            continue;
+        }
+
+        let repository = match parse_repository_url(&krate.repository) {
+            Ok(repo) => repo,
+            Err(err) => {
+                log::error!("Error: {err} when parsing the repository url");
+                continue;
+            }
+        };
+        if !seen.insert(repository.url()) {
+            continue;
+        }
+        let path = repository.path(&get_repos_folder());
+        if !path.join("Cargo.toml").exists() {
+            continue;
+        }
+
+        let commit = git_lines(&path, &["rev-parse", "HEAD"])
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let cloned = ClonedRepository {
+            krate,
+            key: repository.path(Path::new("")).display().to_string(),
+            path,
+            commit,
+        };
+        if process(&cloned)? {
+            count += 1;
+            log::info!("processed {count} repositories");
+        }
+    }
+
+    Ok(())
+}
+
+/// `key` identifies what we ran the job on, e.g. `github.com/szabgab/rust-digger` for a repository.
+fn job_result_path(job: &str, key: &str) -> PathBuf {
+    job_results_root().join(job).join(format!("{key}.json"))
+}
+
+pub fn load_job_result(job: &str, key: &str) -> Option<JobResult> {
+    let filepath = job_result_path(job, key);
+    let content = fs::read_to_string(&filepath).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|err| log::error!("Error parsing {:?}: {err}", filepath.display()))
+        .ok()
+}
+
+pub fn save_job_result(key: &str, result: &JobResult) -> Result<(), Box<dyn Error>> {
+    let filepath = job_result_path(&result.job, key);
+    if let Some(folder) = filepath.parent() {
+        fs::create_dir_all(folder)?;
+    }
+    fs::write(filepath, serde_json::to_string(result)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_runner_config() {
+        let config: RunnerConfig = serde_yaml::from_str(include_str!("../runner.yaml")).unwrap();
+        assert_eq!(config.engine, Engine::Docker);
+        let runner = Runner::new(config);
+        assert_eq!(
+            runner.job_names(),
+            vec!["build", "check", "clippy", "coverage", "doc", "fmt", "rustdoc", "test"]
+        );
+        assert!(runner.job("fmt").unwrap().timeout > 0);
+        assert!(runner.job("miri").is_none());
+        for name in runner.job_names() {
+            let job = runner.job(name).unwrap();
+            if name == "fmt" {
+                assert!(!job.copy_source);
+                assert!(job.volumes.is_empty());
+            } else {
+                assert!(job.copy_source, "{name}");
+                assert_eq!(job.volumes["data/crates"], "/registry", "{name}");
+                assert_eq!(
+                    job.volumes["vendored-registry.toml"], "/.cargo/config.toml",
+                    "{name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn check_container_args() {
+        let config: RunnerConfig = serde_yaml::from_str(
+            "engine: podman\nimage: img\nuser: tester\ncpus: '1'\nmemory: 4g\ntarget_size: 2g\nwork_size: 1g\njobs:\n  check:\n    command: [cargo, check]\n    timeout: 10\n    env:\n      RUSTFLAGS: -Dwarnings\n",
+        )
+        .unwrap();
+        assert_eq!(config.engine.program(), "podman");
+        let runner = Runner::new(config);
+        let args = runner.container_args(
+            "box",
+            runner.job("check").unwrap(),
+            Path::new("/repos/foo"),
+            &[],
+        );
+        assert_eq!(
+            args.join(" "),
+            "run --rm --name box --network none --cpus 1 --memory 4g --user tester --volume /repos/foo:/crate:ro --tmpfs /target:rw,exec,size=2g --env CARGO_TARGET_DIR=/target --env RUSTFLAGS=-Dwarnings --workdir /crate img cargo check"
+        );
+
+        let mut job = runner.job("check").unwrap().clone();
+        job.copy_source = true;
+        job.env.clear();
+        assert_eq!(
+            runner
+                .container_args(
+                    "box",
+                    &job,
+                    Path::new("/repos/foo"),
+                    &[(PathBuf::from("/data/crates"), String::from("/registry"))]
+                )
+                .join(" "),
+            "run --rm --name box --network none --cpus 1 --memory 4g --user tester --volume /repos/foo:/crate:ro --tmpfs /target:rw,exec,size=2g --env CARGO_TARGET_DIR=/target --volume /data/crates:/registry:ro --tmpfs /work:rw,exec,size=1g --workdir /work img sh -c cp -R /crate/. /work && exec \"$@\" sh cargo check"
+        );
+    }
+}
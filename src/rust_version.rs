@@ -0,0 +1,117 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A Rust release as it appears in the `rust-version` field of Cargo.toml, e.g. `1.70` or `1.70.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct RustVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RustVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a version string. Cargo accepts `major.minor` and `major.minor.patch` with no
+    /// pre-release or build suffix, anything else is considered malformed.
+    pub fn parse(text: &str) -> Option<Self> {
+        let parts = text
+            .trim()
+            .split('.')
+            .map(|part| {
+                if part.is_empty() || !part.chars().all(|chr| chr.is_ascii_digit()) {
+                    None
+                } else {
+                    part.parse::<u32>().ok()
+                }
+            })
+            .collect::<Option<Vec<u32>>>()?;
+
+        match *parts.as_slice() {
+            [major, minor] => Some(Self::new(major, minor, 0)),
+            [major, minor, patch] => Some(Self::new(major, minor, patch)),
+            _ => None,
+        }
+    }
+
+    /// The minor release the version belongs to, e.g. `1.70` for both `1.70` and `1.70.2`.
+    pub fn minor_release(&self) -> String {
+        format!("{}.{}", self.major, self.minor)
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The first Rust release that supports the given edition.
+pub fn minimum_rust_version_for_edition(edition: &str) -> Option<RustVersion> {
+    match edition.trim() {
+        "2015" => Some(RustVersion::new(1, 0, 0)),
+        "2018" => Some(RustVersion::new(1, 31, 0)),
+        "2021" => Some(RustVersion::new(1, 56, 0)),
+        "2024" => Some(RustVersion::new(1, 85, 0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse() {
+        assert_eq!(RustVersion::parse("1.70"), Some(RustVersion::new(1, 70, 0)));
+        assert_eq!(
+            RustVersion::parse("1.70.0"),
+            Some(RustVersion::new(1, 70, 0))
+        );
+        assert_eq!(RustVersion::parse("1.70"), RustVersion::parse("1.70.0"));
+        assert_eq!(
+            RustVersion::parse(" 1.82.1 "),
+            Some(RustVersion::new(1, 82, 1))
+        );
+
+        for malformed in [
+            "",
+            "1",
+            "1.70.0.1",
+            "^1.70",
+            "1.70.0-nightly",
+            "1.x",
+            "stable",
+        ] {
+            assert_eq!(RustVersion::parse(malformed), None, "{malformed}");
+        }
+    }
+
+    #[test]
+    fn check_display() {
+        let version = RustVersion::parse("1.70").unwrap();
+        assert_eq!(version.to_string(), "1.70.0");
+        assert_eq!(version.minor_release(), "1.70");
+    }
+
+    #[test]
+    fn check_minimum_rust_version_for_edition() {
+        assert_eq!(
+            minimum_rust_version_for_edition("2021"),
+            Some(RustVersion::new(1, 56, 0))
+        );
+        assert_eq!(
+            minimum_rust_version_for_edition("2024"),
+            Some(RustVersion::new(1, 85, 0))
+        );
+        assert_eq!(minimum_rust_version_for_edition("2019"), None);
+        assert!(RustVersion::new(1, 70, 0) < RustVersion::new(1, 85, 0));
+    }
+}
@@ -0,0 +1,32 @@
+use serde_json::Value;
+
+/// The ids are strings in older versions of the format and numbers in newer ones, the keys of the index are always strings.
+pub fn id_key(id: &Value) -> String {
+    id.as_str().map_or_else(|| id.to_string(), str::to_owned)
+}
+
+/// The kind of the item is the only key of its `inner` field, e.g. `function` or `struct`.
+pub fn item_kind(item: &Value) -> Option<&str> {
+    item.get("inner")
+        .and_then(Value::as_object)
+        .and_then(|inner| inner.keys().next())
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_id_key() {
+        assert_eq!(id_key(&serde_json::json!("0:3:1234")), "0:3:1234");
+        assert_eq!(id_key(&serde_json::json!(42)), "42");
+    }
+
+    #[test]
+    fn check_item_kind() {
+        let item = serde_json::json!({"name": "parse", "inner": {"function": {"sig": {}}}});
+        assert_eq!(item_kind(&item), Some("function"));
+        assert_eq!(item_kind(&serde_json::json!({"name": "parse"})), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use rust_digger::{
+    api_surface, crates_root, has_library, load_cargo_toml_simplified, load_semver_history,
+    previous_crates_root, save_semver_history, ApiDiff, ElapsedTimer, Runner,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Limit the number of crates we process."
+    )]
+    limit: usize,
+}
+
+/// For each crate where we have the release before the newest one (see `download-crates --previous`)
+///     skip it if we already compared these two releases
+///     run `cargo +nightly rustdoc` with the JSON output on both releases in the container
+///     compare the public items of the two releases
+///     add the result to the semver history of the crate.
+fn main() {
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("Start comparing the public API of the releases.");
+    let start_time = std::time::Instant::now();
+
+    if let Err(err) = run() {
+        log::error!("Error: {err}");
+    }
+
+    log::info!("Elapsed time: {} sec.", start_time.elapsed().as_secs());
+    log::info!("End comparing the public API");
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let _a = ElapsedTimer::new("semver_check");
+
+    let args = Cli::parse();
+    log::info!("Limit: {}", args.limit);
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+
+    let newest = newest_releases()?;
+
+    let mut count: usize = 0;
+    for entry in previous_crates_root().read_dir()? {
+        if 0 < args.limit && args.limit <= count {
+            break;
+        }
+        let crate_path = entry?.path();
+        if !has_library(&crate_path) {
+            continue;
+        }
+        let Ok((name, old_version)) = load_cargo_toml_simplified(&crate_path.join("Cargo.toml"))
+        else {
+            continue;
+        };
+        let Some(release) = newest.get(&name) else {
+            continue;
+        };
+        let (new_version, new_path) = (&release.0, &release.1);
+
+        let mut history = load_semver_history(&name);
+        if history
+            .iter()
+            .any(|diff| diff.old_version == old_version && &diff.new_version == new_version)
+        {
+            continue;
+        }
+
+        log::info!(
+            "semver check ({count}/{}) {name} {old_version} -> {new_version}",
+            args.limit
+        );
+        let mut diff = ApiDiff::new(&old_version, new_version);
+        diff.checked_at = chrono::Utc::now().to_rfc3339();
+        match (
+            public_api(&runner, &crate_path),
+            public_api(&runner, new_path),
+        ) {
+            (Ok(old), Ok(new)) => diff.compare(&old, &new),
+            (Err(err), _) | (_, Err(err)) => diff.error = err,
+        }
+        log::info!(
+            "bump: {} removed: {} changed: {} added: {} violation: {}",
+            diff.bump,
+            diff.removed_count,
+            diff.changed_count,
+            diff.added_count,
+            diff.violation
+        );
+        history.push(diff);
+        save_semver_history(&name, &history)?;
+
+        count += 1;
+    }
+
+    log::info!("Compared the releases of {count} crates");
+
+    Ok(())
+}
+
+/// The version and the folder of the newest release of each crate we have locally.
+fn newest_releases() -> Result<HashMap<String, (String, PathBuf)>, Box<dyn Error>> {
+    let mut newest = HashMap::new();
+    for entry in crates_root().read_dir()? {
+        let crate_path = entry?.path();
+        if let Ok((name, version)) = load_cargo_toml_simplified(&crate_path.join("Cargo.toml")) {
+            newest.insert(name, (version, crate_path));
+        }
+    }
+    Ok(newest)
+}
+
+/// Run rustdoc on the release and collect its public items.
+fn public_api(runner: &Runner, crate_path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let result = runner
+        .run("rustdoc", crate_path)
+        .map_err(|err| format!("Could not run rustdoc: {err}"))?;
+    if !result.succeeded() {
+        return Err(format!(
+            "rustdoc failed on {}",
+            crate_path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+    }
+    let rustdoc = serde_json::from_str(&result.stdout)
+        .map_err(|err| format!("Could not parse the output of rustdoc: {err}"))?;
+    Ok(api_surface(&rustdoc))
+}
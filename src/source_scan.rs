@@ -0,0 +1,383 @@
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::{Regex, RegexSet};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::RustVersion;
+
+/// Files larger than this are usually generated code, we don't scan them.
+const MAX_FILE_SIZE: u64 = 1_000_000;
+
+/// Language features and std APIs we can recognize with a regex, the Rust release that stabilized them
+/// and the regex. The regexes are heuristics, they might have false positives and false negatives.
+const FEATURES: [(&str, (u32, u32), &str); 16] = [
+    (
+        "let-else",
+        (1, 65),
+        r"(?m)^\s*let\s+[^=;]+=\s*[^;{]*\belse\s*\{",
+    ),
+    (
+        "generic associated types",
+        (1, 65),
+        r"(?m)^\s+type\s+\w+\s*<[^=;]*>\s*(?:;|:|where\b)",
+    ),
+    ("OnceLock", (1, 70), r"\bOnceLock\b"),
+    (
+        "async fn in traits",
+        (1, 75),
+        r"\btrait\s+\w+[^{;]*\{[^}]*\basync\s+fn\b",
+    ),
+    (
+        "impl Trait in trait return position",
+        (1, 75),
+        r"\btrait\s+\w+[^{;]*\{[^}]*->\s*impl\s",
+    ),
+    ("C string literals", (1, 77), r#"(?:^|[^\w"'])c""#),
+    ("offset_of!", (1, 77), r"\boffset_of!"),
+    ("#[diagnostic] attributes", (1, 78), r"#\[diagnostic::"),
+    ("LazyLock", (1, 80), r"\bLazyLock\b"),
+    ("LazyCell", (1, 80), r"\bLazyCell\b"),
+    ("#[expect] attributes", (1, 81), r"#!?\[expect\("),
+    ("Option::is_none_or", (1, 82), r"\.is_none_or\("),
+    (
+        "unsafe extern blocks",
+        (1, 82),
+        r#"\bunsafe\s+extern\s*"[^"]*"\s*\{"#,
+    ),
+    ("unsafe attributes", (1, 82), r"#\[unsafe\("),
+    ("async closures", (1, 85), r"\basync\s+(?:move\s+)?\|"),
+    ("let chains", (1, 88), r"\bif\s+let\b[^{;]*&&\s*let\b"),
+];
+
+/// A trait annotated with `#[async_trait]` of the async-trait crate, up to its `trait` keyword.
+/// The macro rewrites the async fns of the trait, so they compile on older Rust releases too.
+const ASYNC_TRAIT: &str = r"#\[\s*(?:async_trait::)?async_trait\s*(?:\([^)]*\))?\s*\]\s*((?:#\[[^\]]*\]\s*)*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?)trait\b";
+
+/// A language feature or std API found in the source code of a crate.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RustFeature {
+    pub name: String,
+    pub since: String,
+}
+
+/// Scan the Rust files in `path` and return the features with a known stabilisation version they use.
+pub fn scan_rust_features(path: &Path) -> Vec<RustFeature> {
+    let mut found = [false; FEATURES.len()];
+
+    for entry in WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != "target")
+        .flatten()
+    {
+        let entry_path = entry.path();
+        if !entry_path.is_file() || entry_path.extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        if entry
+            .metadata()
+            .is_ok_and(|meta| meta.len() > MAX_FILE_SIZE)
+        {
+            log::info!("Skipping large file {:?}", entry_path.display());
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry_path) else {
+            continue;
+        };
+
+        for index in find_rust_features(&content) {
+            found[index] = true;
+        }
+    }
+
+    FEATURES
+        .iter()
+        .zip(found)
+        .filter(|pair| pair.1)
+        .map(|pair| RustFeature {
+            name: pair.0 .0.to_owned(),
+            since: RustVersion::new(pair.0 .1 .0, pair.0 .1 .1, 0).minor_release(),
+        })
+        .collect()
+}
+
+/// The newest Rust release among the ones that stabilized the given features.
+pub fn inferred_rust_version(features: &[RustFeature]) -> Option<RustVersion> {
+    features
+        .iter()
+        .filter_map(|feature| RustVersion::parse(&feature.since))
+        .max()
+}
+
+/// Return the indexes of the entries in `FEATURES` used in the given source code.
+fn find_rust_features(content: &str) -> Vec<usize> {
+    static FEATURE_SET: LazyLock<RegexSet> =
+        LazyLock::new(|| RegexSet::new(FEATURES.iter().map(|feature| feature.2)).unwrap());
+    static ASYNC_TRAIT_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(ASYNC_TRAIT).unwrap());
+
+    let stripped = strip_comments_and_strings(content);
+    // hide the `trait` keyword of these traits from the regexes of the trait features
+    let code = ASYNC_TRAIT_REGEX.replace_all(&stripped, "${1}async_trait");
+    FEATURE_SET.matches(&code).into_iter().collect()
+}
+
+/// Remove the `//` and `/* */` comments (including the doc-comments) so the examples in the documentation don't count.
+/// Also remove the content of the string and character literals, so `"https://..."` is not taken for a comment
+/// and `"-c"` is not taken for code. The quotes and the newlines are kept.
+fn strip_comments_and_strings(content: &str) -> String {
+    let chars = content.chars().collect::<Vec<char>>();
+    let mut code = String::with_capacity(content.len());
+    let mut index = 0;
+    while let Some(&current) = chars.get(index) {
+        let next = chars.get(index + 1).copied();
+        index += 1;
+        match current {
+            '/' if next == Some('/') => {
+                while chars.get(index).is_some_and(|&ch| ch != '\n') {
+                    index += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                index = skip_block_comment(&chars, index + 1, &mut code);
+            }
+            '"' => {
+                code.push('"');
+                index = skip_string(&chars, index, None, &mut code);
+            }
+            'r' if is_raw_string_start(&chars, index) => {
+                let hashes = chars
+                    .iter()
+                    .skip(index)
+                    .take_while(|&&ch| ch == '#')
+                    .count();
+                code.push_str("r\"");
+                index = skip_string(&chars, index + hashes + 1, Some(hashes), &mut code);
+            }
+            '\'' => {
+                code.push('\'');
+                index = skip_char_literal(&chars, index, &mut code);
+            }
+            _ => code.push(current),
+        }
+    }
+    code
+}
+
+/// The `r` at `index - 1` starts a raw string: `r"`, `r#"`, `br"` or `cr"`, but not the end of an identifier or `r#ident`.
+fn is_raw_string_start(chars: &[char], index: usize) -> bool {
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let before = index
+        .checked_sub(2)
+        .and_then(|position| chars.get(position));
+    let prefix_ok = before.is_none_or(|&ch| {
+        !is_word(ch)
+            || ((ch == 'b' || ch == 'c')
+                && index
+                    .checked_sub(3)
+                    .and_then(|position| chars.get(position))
+                    .is_none_or(|&earlier| !is_word(earlier)))
+    });
+    prefix_ok
+        && chars
+            .iter()
+            .skip(index)
+            .find(|&&ch| ch != '#')
+            .is_some_and(|&ch| ch == '"')
+}
+
+/// Skip the content of a string starting at `index`, `hashes` is the number of `#` of a raw string.
+/// Returns the index after the closing quote.
+fn skip_string(chars: &[char], start: usize, hashes: Option<usize>, code: &mut String) -> usize {
+    let mut index = start;
+    while let Some(&current) = chars.get(index) {
+        index += 1;
+        match current {
+            '\\' if hashes.is_none() => index += 1,
+            '"' => {
+                let closing = hashes.unwrap_or(0);
+                if chars
+                    .iter()
+                    .skip(index)
+                    .take(closing)
+                    .filter(|&&ch| ch == '#')
+                    .count()
+                    == closing
+                {
+                    code.push('"');
+                    return index + closing;
+                }
+            }
+            '\n' => code.push('\n'),
+            _ => {}
+        }
+    }
+    index
+}
+
+/// Skip a block comment starting after the opening `/*`, block comments can be nested.
+/// Returns the index after the closing `*/`.
+fn skip_block_comment(chars: &[char], start: usize, code: &mut String) -> usize {
+    let mut depth: usize = 1;
+    let mut index = start;
+    while let Some(&current) = chars.get(index) {
+        let next = chars.get(index + 1).copied();
+        index += 1;
+        match current {
+            '/' if next == Some('*') => {
+                depth += 1;
+                index += 1;
+            }
+            '*' if next == Some('/') => {
+                index += 1;
+                depth -= 1;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            '\n' => code.push('\n'),
+            _ => {}
+        }
+    }
+    index
+}
+
+/// Skip a character literal like `'"'` or `'\''` starting after the opening quote.
+/// A lifetime like `'a` has no closing quote, we keep it.
+fn skip_char_literal(chars: &[char], start: usize, code: &mut String) -> usize {
+    match (chars.get(start).copied(), chars.get(start + 1).copied()) {
+        (Some('\\'), _) => {
+            let closing = chars
+                .iter()
+                .skip(start + 2)
+                .position(|&ch| ch == '\'')
+                .map_or(chars.len(), |position| start + 2 + position);
+            code.push('\'');
+            closing + 1
+        }
+        (Some(_), Some('\'')) => {
+            code.push('\'');
+            start + 2
+        }
+        _ => start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature_names(content: &str) -> Vec<&'static str> {
+        find_rust_features(content)
+            .into_iter()
+            .map(|index| FEATURES[index].0)
+            .collect()
+    }
+
+    #[test]
+    fn check_find_rust_features() {
+        assert_eq!(
+            feature_names(
+                "fn main() {\n    let Some(x) = foo() else {\n        return;\n    };\n}\n"
+            ),
+            vec!["let-else"]
+        );
+        assert!(feature_names("    let x = if a { 1 } else { 2 };\n").is_empty());
+
+        assert_eq!(
+            feature_names("trait Lending {\n    type Item<'a> where Self: 'a;\n}\n"),
+            vec!["generic associated types"]
+        );
+        assert!(feature_names("pub type Result<T> = core::result::Result<T, Error>;\n").is_empty());
+
+        assert_eq!(
+            feature_names("pub trait Fetch {\n    async fn fetch(&self) -> u8;\n}\n"),
+            vec!["async fn in traits"]
+        );
+
+        assert_eq!(
+            feature_names("static RE: LazyLock<Regex> = LazyLock::new(|| todo!());\n"),
+            vec!["LazyLock"]
+        );
+        assert_eq!(
+            feature_names("#[diagnostic::on_unimplemented(message = \"x\")]\ntrait A {}\n"),
+            vec!["#[diagnostic] attributes"]
+        );
+        assert!(
+            feature_names("/// let Some(x) = foo() else { return };\n// LazyLock\n").is_empty()
+        );
+        assert!(feature_names("/* LazyLock\n /* nested */ OnceLock */\nfn main() {}\n").is_empty());
+    }
+
+    #[test]
+    fn check_async_trait() {
+        for source in [
+            "#[async_trait]\npub trait Fetch {\n    async fn fetch(&self) -> u8;\n}\n",
+            "#[async_trait::async_trait]\ntrait Fetch {\n    async fn fetch(&self) -> u8;\n}\n",
+            "#[async_trait(?Send)]\n#[allow(unused)]\npub(crate) trait Fetch {\n    async fn fetch(&self) -> u8;\n}\n",
+        ] {
+            assert!(feature_names(source).is_empty(), "{source}");
+        }
+        assert_eq!(
+            feature_names(
+                "#[async_trait]\ntrait Fetch {\n    async fn fetch(&self);\n}\ntrait Load {\n    async fn load(&self);\n}\n"
+            ),
+            vec!["async fn in traits"]
+        );
+    }
+
+    #[test]
+    fn check_c_string_literals() {
+        assert_eq!(
+            feature_names("let name = c\"hello\";\n"),
+            vec!["C string literals"]
+        );
+        assert_eq!(
+            feature_names("call(c\"a\", c\"b\");\n"),
+            vec!["C string literals"]
+        );
+        assert!(feature_names("let flag = \"-c\";\n").is_empty());
+        assert!(feature_names("let name = \"abc\";\n").is_empty());
+        assert!(feature_names("let args = [\"sh\", \"-c\", \"ls\"];\n").is_empty());
+        assert!(feature_names("let quote = '\"'; let abc = 1;\n").is_empty());
+    }
+
+    #[test]
+    fn check_strip_comments_and_strings() {
+        assert_eq!(
+            feature_names("let url = \"https://example.com\"; static A: LazyLock<u8> = todo!();\n"),
+            vec!["LazyLock"]
+        );
+        assert!(feature_names("let text = \"// LazyLock\";\n").is_empty());
+        assert!(feature_names("let text = r#\"\"LazyLock\"\"#;\n").is_empty());
+        assert_eq!(
+            strip_comments_and_strings(
+                "let a = \"x\\\"y\"; // note\nlet b: &'static str = r#\"a\"b\"#; let c = '\\'';\n"
+            ),
+            "let a = \"\"; \nlet b: &'static str = r\"\"; let c = '';\n"
+        );
+        assert_eq!(
+            strip_comments_and_strings("a /* x\n/* y */ z */ b\n"),
+            "a \n b\n"
+        );
+    }
+
+    #[test]
+    fn check_inferred_rust_version() {
+        let features = vec![
+            RustFeature {
+                name: String::from("let-else"),
+                since: String::from("1.65"),
+            },
+            RustFeature {
+                name: String::from("LazyLock"),
+                since: String::from("1.80"),
+            },
+        ];
+        assert_eq!(
+            inferred_rust_version(&features),
+            Some(RustVersion::new(1, 80, 0))
+        );
+        assert_eq!(inferred_rust_version(&[]), None);
+    }
+}
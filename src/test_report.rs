@@ -0,0 +1,187 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo_messages::{compiler_messages, Diagnostic};
+use crate::JobResult;
+
+/// How many lines of the error output we keep if the tests could not be compiled or run.
+const ERROR_LINES: usize = 5;
+
+/// The result of running `cargo test --no-fail-fast` on a repository.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TestReport {
+    /// The commit we tested (`git rev-parse HEAD`), empty if we have not run the tests yet.
+    pub commit: String,
+
+    pub checked_at: String,
+
+    /// The output of `rustc --version` in the container.
+    pub rustc_version: String,
+
+    /// `None` if the tests timed out or could not be run at all.
+    pub exit_code: Option<i32>,
+
+    pub timed_out: bool,
+
+    /// The number of test binaries (including the doc-tests) that reported a result.
+    pub suites: usize,
+
+    /// The number of tests that were run or ignored.
+    pub tests: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+
+    /// How long it took to build and run the tests.
+    pub duration_secs: u64,
+
+    /// The first compiler error or the first lines of the error output if the tests did not run.
+    pub error: String,
+}
+
+impl TestReport {
+    pub const fn new() -> Self {
+        Self {
+            commit: String::new(),
+            checked_at: String::new(),
+            rustc_version: String::new(),
+            exit_code: None,
+            timed_out: false,
+            suites: 0,
+            tests: 0,
+            passed: 0,
+            failed: 0,
+            ignored: 0,
+            duration_secs: 0,
+            error: String::new(),
+        }
+    }
+
+    /// We have already tested this commit.
+    pub fn is_up_to_date(&self, commit: &str) -> bool {
+        !commit.is_empty() && self.commit == commit
+    }
+
+    /// All the tests were compiled and passed.
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Collect the results of the `test` job, see `runner.yaml`.
+    pub fn from_job_result(result: &JobResult) -> Self {
+        let mut report = Self::new();
+        report.checked_at.clone_from(&result.started_at);
+        report.exit_code = result.exit_code;
+        report.timed_out = result.timed_out;
+        report.duration_secs = result.duration_secs;
+        report.parse_test_output(&result.stdout);
+
+        if !result.succeeded() && report.failed == 0 {
+            report.error = compiler_messages(&result.stdout)
+                .into_iter()
+                .find(Diagnostic::is_compiler_error)
+                .map_or_else(
+                    || {
+                        result
+                            .stderr
+                            .lines()
+                            .filter(|line| !line.trim().is_empty())
+                            .take(ERROR_LINES)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    },
+                    |diagnostic| diagnostic.message,
+                );
+        }
+        report
+    }
+
+    /// Add up the `test result:` lines each test binary prints, e.g.
+    /// `test result: ok. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s`.
+    pub fn parse_test_output(&mut self, stdout: &str) {
+        static TEST_RESULT: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored;")
+                .expect("valid regex")
+        });
+
+        for line in stdout.lines() {
+            let Some(captures) = TEST_RESULT.captures(line) else {
+                continue;
+            };
+            let count = |index| {
+                captures
+                    .get(index)
+                    .and_then(|number| number.as_str().parse::<usize>().ok())
+                    .unwrap_or_default()
+            };
+            self.suites += 1;
+            self.passed += count(1);
+            self.failed += count(2);
+            self.ignored += count(3);
+        }
+        self.tests = self.passed + self.failed + self.ignored;
+    }
+}
+
+impl Default for TestReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse_test_output() {
+        let stdout = [
+            r#"{"reason":"compiler-artifact","package_id":"foo 0.1.0"}"#,
+            "",
+            "running 3 tests",
+            "test tests::a ... ok",
+            "test tests::b ... FAILED",
+            "test tests::c ... ignored",
+            "",
+            "test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s",
+            "",
+            "running 2 tests",
+            "test src/lib.rs - add (line 3) ... ok",
+            "test src/lib.rs - sub (line 9) ... ok",
+            "",
+            "test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.20s",
+        ]
+        .join("\n");
+
+        let mut report = TestReport::new();
+        report.parse_test_output(&stdout);
+        assert_eq!(report.suites, 2);
+        assert_eq!(report.tests, 5);
+        assert_eq!(report.passed, 3);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.ignored, 1);
+    }
+
+    #[test]
+    fn check_from_job_result() {
+        let result = JobResult {
+            job: String::from("test"),
+            commit: String::new(),
+            started_at: String::from("2024-01-01T00:00:00+00:00"),
+            duration_secs: 12,
+            exit_code: Some(101),
+            timed_out: false,
+            stdout: String::from(
+                r#"{"reason":"compiler-message","package_id":"foo 0.1.0","message":{"message":"mismatched types","level":"error","code":{"code":"E0308","explanation":"..."}}}"#,
+            ),
+            stderr: String::from("error: could not compile `foo`"),
+        };
+        let report = TestReport::from_job_result(&result);
+        assert_eq!(report.tests, 0);
+        assert_eq!(report.duration_secs, 12);
+        assert_eq!(report.error, "mismatched types");
+        assert!(!report.succeeded());
+    }
+}
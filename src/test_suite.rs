@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::path::Path;
+
+use clap::Parser;
+
+use rust_digger::{
+    for_each_cloned_repository, load_vcs_details, read_crates, save_details, Crate, ElapsedTimer,
+    Runner, TestReport,
+};
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Number of git repositories to run the tests of."
+    )]
+    limit: u32,
+}
+
+/// For each repo load the details (if they already exist)
+///    If we have not ran the tests on the current commit of the given repo then
+///          run `cargo test --no-fail-fast` in the container
+///          save the number of passed, failed and ignored tests back to the details.
+fn main() {
+    let args = Cli::parse();
+    simple_logger::init_with_level(log::Level::Info).unwrap();
+    log::info!("start running cargo test");
+
+    match run_cargo_test(args.limit) {
+        Ok(()) => {}
+        Err(err) => log::error!("Error: {err}"),
+    }
+}
+
+fn run_cargo_test(limit: u32) -> Result<(), Box<dyn Error>> {
+    log::info!("start running cargo test on the repositories. limit {limit}.");
+    let _a = ElapsedTimer::new("test_suite.rs");
+
+    let runner = Runner::load(Path::new("runner.yaml"))?;
+    runner.build_image(Path::new("."));
+    let rustc_version = runner.version("rustc");
+    log::info!("{rustc_version}");
+
+    let crates: Vec<Crate> = read_crates(0)?;
+    for_each_cloned_repository(&crates, limit, |repo| {
+        let mut details = load_vcs_details(&repo.krate.repository);
+        if details.cargo_test.is_up_to_date(&repo.commit) {
+            log::info!(
+                "cargo test already ran on {} of {}",
+                repo.commit,
+                repo.krate.repository
+            );
+            return Ok(false);
+        }
+
+        log::info!("repo: {:?}", repo.path.display());
+        let mut report = match runner.run("test", &repo.path) {
+            Ok(result) => TestReport::from_job_result(&result),
+            Err(err) => {
+                log::error!("Could not run cargo test: {err}");
+                let mut report = TestReport::new();
+                report.error = err.to_string();
+                report
+            }
+        };
+        log::info!(
+            "exit code: {:?} tests: {} passed: {} failed: {} ignored: {}",
+            report.exit_code,
+            report.tests,
+            report.passed,
+            report.failed,
+            report.ignored
+        );
+        report.commit.clone_from(&repo.commit);
+        report.rustc_version.clone_from(&rustc_version);
+        details.cargo_test = report;
+        save_details(&repo.krate.repository, &details)?;
+        Ok(true)
+    })
+}
@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use toml::Table;
+
+/// The tools we look for and the files configuring them, relative to the root of the repository.
+/// If there are several candidates we use the first one we find.
+pub const TOOLS: [(&str, &[&str]); 12] = [
+    (
+        "dependabot",
+        &[".github/dependabot.yml", ".github/dependabot.yaml"],
+    ),
+    (
+        "renovate",
+        &[
+            "renovate.json",
+            "renovate.json5",
+            ".github/renovate.json",
+            ".github/renovate.json5",
+            ".gitlab/renovate.json",
+            ".renovaterc",
+            ".renovaterc.json",
+        ],
+    ),
+    ("cargo-deny", &["deny.toml", ".deny.toml"]),
+    ("cargo-audit", &[".cargo/audit.toml", "audit.toml"]),
+    (
+        "pre-commit",
+        &[".pre-commit-config.yaml", ".pre-commit-config.yml"],
+    ),
+    ("rust-toolchain", &["rust-toolchain.toml", "rust-toolchain"]),
+    ("clippy", &["clippy.toml", ".clippy.toml"]),
+    ("cargo-config", &[".cargo/config.toml", ".cargo/config"]),
+    ("cargo-make", &["Makefile.toml"]),
+    ("just", &["justfile", "Justfile", ".justfile"]),
+    ("nix-flake", &["flake.nix"]),
+    ("editorconfig", &[".editorconfig"]),
+];
+
+/// The checks of cargo-deny, each one has its own section in `deny.toml`.
+const DENY_CHECKS: [&str; 4] = ["advisories", "bans", "licenses", "sources"];
+
+/// The automation configured in a repository beyond CI and the key settings of each tool.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Tooling {
+    /// The name of each tool we found (see `TOOLS`) and the file configuring it.
+    pub files: BTreeMap<String, String>,
+
+    /// The files we could not parse, with the error message.
+    pub invalid_files: Vec<String>,
+
+    /// The package ecosystems Dependabot updates and the schedule, e.g. `cargo (weekly)`.
+    pub dependabot_updates: Vec<String>,
+
+    /// The presets the Renovate configuration extends, e.g. `config:recommended`.
+    pub renovate_presets: Vec<String>,
+
+    /// The cargo-deny checks configured in `deny.toml`, e.g. `licenses`.
+    pub deny_checks: Vec<String>,
+
+    /// The advisories cargo-audit is told to ignore.
+    pub audit_ignored: Vec<String>,
+
+    /// The ids of the pre-commit hooks.
+    pub pre_commit_hooks: Vec<String>,
+
+    /// The channel in `rust-toolchain(.toml)`, e.g. `stable`, `nightly-2024-01-01` or `1.75.0`.
+    pub toolchain_channel: String,
+
+    /// The components in `rust-toolchain.toml`, e.g. `clippy`.
+    pub toolchain_components: Vec<String>,
+
+    /// The settings in `clippy.toml`, e.g. `msrv`.
+    pub clippy_settings: Vec<String>,
+
+    /// The sections of `.cargo/config.toml`, e.g. `alias` or `target`.
+    pub cargo_config_sections: Vec<String>,
+
+    /// The tasks in `Makefile.toml`.
+    pub cargo_make_tasks: Vec<String>,
+
+    /// The recipes in the `justfile`.
+    pub just_recipes: Vec<String>,
+
+    /// The inputs of `flake.nix`, e.g. `nixpkgs` or `rust-overlay`.
+    pub flake_inputs: Vec<String>,
+
+    /// The file patterns of the sections in `.editorconfig`, e.g. `*.rs`.
+    pub editorconfig_sections: Vec<String>,
+}
+
+impl Tooling {
+    pub const fn new() -> Self {
+        Self {
+            files: BTreeMap::new(),
+            invalid_files: vec![],
+            dependabot_updates: vec![],
+            renovate_presets: vec![],
+            deny_checks: vec![],
+            audit_ignored: vec![],
+            pre_commit_hooks: vec![],
+            toolchain_channel: String::new(),
+            toolchain_components: vec![],
+            clippy_settings: vec![],
+            cargo_config_sections: vec![],
+            cargo_make_tasks: vec![],
+            just_recipes: vec![],
+            flake_inputs: vec![],
+            editorconfig_sections: vec![],
+        }
+    }
+
+    /// The tool with the given name (see `TOOLS`) is configured in the repository.
+    pub fn has(&self, tool: &str) -> bool {
+        self.files.contains_key(tool)
+    }
+
+    fn parse(&mut self, tool: &str, file: &str, content: &str) -> Result<(), String> {
+        match tool {
+            "dependabot" => self.dependabot_updates = dependabot_updates(content)?,
+            // We cannot parse JSON5 so for those files we only know that Renovate is used.
+            "renovate" if !has_extension(file, "json5") => {
+                self.renovate_presets = renovate_presets(content)?;
+            }
+            "cargo-deny" => {
+                let table = parse_toml(content)?;
+                self.deny_checks = DENY_CHECKS
+                    .iter()
+                    .filter(|check| table.contains_key(**check))
+                    .map(|check| (*check).to_owned())
+                    .collect();
+            }
+            "cargo-audit" => {
+                self.audit_ignored = parse_toml(content)?
+                    .get("advisories")
+                    .and_then(|advisories| advisories.get("ignore"))
+                    .and_then(toml::Value::as_array)
+                    .map(|ignore| {
+                        ignore
+                            .iter()
+                            .filter_map(toml::Value::as_str)
+                            .map(str::to_owned)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+            "pre-commit" => self.pre_commit_hooks = pre_commit_hooks(content)?,
+            "rust-toolchain" => self.parse_rust_toolchain(file, content)?,
+            "clippy" => self.clippy_settings = parse_toml(content)?.keys().cloned().collect(),
+            "cargo-config" => {
+                self.cargo_config_sections = parse_toml(content)?.keys().cloned().collect();
+            }
+            "cargo-make" => {
+                self.cargo_make_tasks = parse_toml(content)?
+                    .get("tasks")
+                    .and_then(toml::Value::as_table)
+                    .map(|tasks| tasks.keys().cloned().collect())
+                    .unwrap_or_default();
+            }
+            "just" => self.just_recipes = just_recipes(content),
+            "nix-flake" => self.flake_inputs = flake_inputs(content),
+            "editorconfig" => self.editorconfig_sections = editorconfig_sections(content),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The old format is a single line with the channel, the new one is a TOML file with a `[toolchain]` table.
+    fn parse_rust_toolchain(&mut self, file: &str, content: &str) -> Result<(), String> {
+        if !has_extension(file, "toml") && !content.contains("[toolchain]") {
+            content
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .clone_into(&mut self.toolchain_channel);
+            return Ok(());
+        }
+
+        let table = parse_toml(content)?;
+        let Some(toolchain) = table.get("toolchain") else {
+            return Ok(());
+        };
+        toolchain
+            .get("channel")
+            .and_then(toml::Value::as_str)
+            .unwrap_or_default()
+            .clone_into(&mut self.toolchain_channel);
+        self.toolchain_components = toolchain
+            .get("components")
+            .and_then(toml::Value::as_array)
+            .map(|components| {
+                components
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(str::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(())
+    }
+}
+
+impl Default for Tooling {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Look for the configuration files of the tools in `TOOLS` and read their key settings.
+pub fn detect_tooling(repo_path: &Path) -> Tooling {
+    let mut tooling = Tooling::new();
+
+    for (tool, candidates) in TOOLS {
+        let Some(file) = candidates
+            .iter()
+            .find(|candidate| repo_path.join(candidate).is_file())
+        else {
+            continue;
+        };
+        log::info!("tooling: {tool} {file}");
+        tooling.files.insert(tool.to_owned(), (*file).to_owned());
+
+        let result = fs::read_to_string(repo_path.join(file))
+            .map_err(|err| err.to_string())
+            .and_then(|content| tooling.parse(tool, file, &content));
+        if let Err(err) = result {
+            log::warn!("Could not parse {file}: {err}");
+            tooling.invalid_files.push(format!("{file}: {err}"));
+        }
+    }
+
+    tooling
+}
+
+fn has_extension(file: &str, extension: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+fn parse_toml(content: &str) -> Result<Table, String> {
+    content.parse::<Table>().map_err(|err| err.to_string())
+}
+
+fn parse_yaml(content: &str) -> Result<Value, String> {
+    serde_yaml::from_str(content).map_err(|err| err.to_string())
+}
+
+fn dependabot_updates(content: &str) -> Result<Vec<String>, String> {
+    let config = parse_yaml(content)?;
+    let updates = config
+        .get("updates")
+        .and_then(Value::as_sequence)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(updates
+        .iter()
+        .filter_map(|update| {
+            let ecosystem = update.get("package-ecosystem")?.as_str()?;
+            let interval = update
+                .get("schedule")
+                .and_then(|schedule| schedule.get("interval"))
+                .and_then(Value::as_str);
+            Some(interval.map_or_else(
+                || ecosystem.to_owned(),
+                |every| format!("{ecosystem} ({every})"),
+            ))
+        })
+        .collect())
+}
+
+fn renovate_presets(content: &str) -> Result<Vec<String>, String> {
+    let config: serde_json::Value = serde_json::from_str(content).map_err(|err| err.to_string())?;
+    Ok(config
+        .get("extends")
+        .and_then(serde_json::Value::as_array)
+        .map(|presets| {
+            presets
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn pre_commit_hooks(content: &str) -> Result<Vec<String>, String> {
+    let config = parse_yaml(content)?;
+    let repos = config
+        .get("repos")
+        .and_then(Value::as_sequence)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(repos
+        .iter()
+        .filter_map(|repo| repo.get("hooks").and_then(Value::as_sequence))
+        .flatten()
+        .filter_map(|hook| hook.get("id").and_then(Value::as_str))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Recipes start at the beginning of a line with their name followed by the parameters and a `:`.
+/// Settings, aliases, imports and variable assignments (`:=`) are not recipes.
+fn just_recipes(content: &str) -> Vec<String> {
+    static RECIPE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new("^@?([A-Za-z_][A-Za-z0-9_-]*)[^:=]*:(?:[^=]|$)").unwrap());
+    const KEYWORDS: [&str; 6] = ["set", "alias", "import", "mod", "export", "unexport"];
+
+    content
+        .lines()
+        .filter_map(|line| RECIPE.captures(line))
+        .filter_map(|captures| captures.get(1))
+        .map(|name| name.as_str())
+        .filter(|name| !KEYWORDS.contains(name))
+        .map(str::to_owned)
+        .collect()
+}
+
+fn flake_inputs(content: &str) -> Vec<String> {
+    static INPUT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"([A-Za-z][A-Za-z0-9_-]*)\.url\s*=").unwrap());
+
+    let mut inputs = INPUT
+        .captures_iter(content)
+        .filter_map(|captures| captures.get(1))
+        .map(|name| name.as_str().to_owned())
+        .collect::<Vec<String>>();
+    inputs.sort();
+    inputs.dedup();
+    inputs
+}
+
+fn editorconfig_sections(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix('[')?.strip_suffix(']'))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_parse() {
+        let mut tooling = Tooling::new();
+        tooling
+            .parse(
+                "dependabot",
+                ".github/dependabot.yml",
+                "version: 2\nupdates:\n  - package-ecosystem: cargo\n    directory: /\n    schedule:\n      interval: weekly\n  - package-ecosystem: github-actions\n",
+            )
+            .unwrap();
+        assert_eq!(
+            tooling.dependabot_updates,
+            vec!["cargo (weekly)", "github-actions"]
+        );
+
+        tooling
+            .parse(
+                "cargo-deny",
+                "deny.toml",
+                "[licenses]\nallow = [\"MIT\"]\n\n[bans]\nmultiple-versions = \"warn\"\n",
+            )
+            .unwrap();
+        assert_eq!(tooling.deny_checks, vec!["bans", "licenses"]);
+
+        tooling
+            .parse("rust-toolchain", "rust-toolchain", "nightly-2024-01-01\n")
+            .unwrap();
+        assert_eq!(tooling.toolchain_channel, "nightly-2024-01-01");
+        tooling
+            .parse(
+                "rust-toolchain",
+                "rust-toolchain.toml",
+                "[toolchain]\nchannel = \"1.75.0\"\ncomponents = [\"clippy\", \"rustfmt\"]\n",
+            )
+            .unwrap();
+        assert_eq!(tooling.toolchain_channel, "1.75.0");
+        assert_eq!(tooling.toolchain_components, vec!["clippy", "rustfmt"]);
+
+        assert!(tooling.parse("clippy", "clippy.toml", "msrv = ").is_err());
+    }
+
+    #[test]
+    fn check_just_recipes() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+version := `cargo pkgid`
+alias t := test
+
+# run the tests
+test *ARGS:
+    cargo test {{ARGS}}
+
+@lint: test
+    cargo clippy
+";
+        assert_eq!(just_recipes(justfile), vec!["test", "lint"]);
+    }
+
+    #[test]
+    fn check_flake_inputs() {
+        let flake = r#"{
+  inputs.nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+  inputs = {
+    rust-overlay.url = "github:oxalica/rust-overlay";
+    flake-utils.url = "github:numtide/flake-utils";
+  };
+}"#;
+        assert_eq!(
+            flake_inputs(flake),
+            vec!["flake-utils", "nixpkgs", "rust-overlay"]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::error::Error;
 use std::fs;
@@ -7,14 +7,17 @@ use std::io::Write as _;
 use std::path::Path;
 use std::process::Command;
 
+use chrono::Utc;
 use clap::Parser;
 use toml::Table;
-
-use git_digger::Repository;
+use walkdir::WalkDir;
 
 use rust_digger::{
-    collected_data_root, get_repos_folder, load_vcs_details, read_crates, save_details, Crate,
-    ElapsedTimer, VCSDetails,
+    changelog_in_folder_mentions_version, collected_data_root, detect_ci_providers, detect_tooling,
+    find_community_files, get_repos_folder, git_history, git_last_commit_date, git_tags,
+    github_workflows, gitlab_ci_includes, load_vcs_details, parse_repository_url, read_crates,
+    read_versions, reconcile_release_tags, resolve_repository_url, save_details, Crate,
+    CrateVersion, ElapsedTimer, VCSDetails,
 };
 
 #[derive(Parser, Debug)]
@@ -48,13 +51,15 @@ fn run() -> Result<(), Box<dyn Error>> {
     let _a = ElapsedTimer::new("vcs.rs");
 
     let crates: Vec<Crate> = read_crates(0)?;
-    collect_data_from_vcs(&crates, args.limit)?;
+    let versions: Vec<CrateVersion> = read_versions()?;
+    collect_data_from_vcs(&crates, &versions, args.limit)?;
 
     Ok(())
 }
 
 fn collect_data_from_vcs(
     crates: &Vec<Crate>,
+    versions: &[CrateVersion],
     limit: u32,
 ) -> Result<(), Box<dyn std::error::Error>> {
     log::info!("process collect_data_from_vcs start");
@@ -63,6 +68,38 @@ fn collect_data_from_vcs(
         log::info!("We are going to process only {limit} crates");
     }
 
+    // A repository might contain several crates (monorepo) so we collect them to check each one of them.
+    let mut crates_by_repository: HashMap<String, Vec<&Crate>> = HashMap::new();
+    for krate in crates {
+        if let Some(repository_url) = resolve_repository_url(&krate.repository) {
+            crates_by_repository
+                .entry(repository_url)
+                .or_default()
+                .push(krate);
+        }
+    }
+
+    let mut versions_by_crate_id: HashMap<&str, Vec<&str>> = HashMap::new();
+    for version in versions {
+        versions_by_crate_id
+            .entry(version.crate_id.as_str())
+            .or_default()
+            .push(version.num.as_str());
+    }
+
+    // The most recent release of each crate that was not yanked.
+    let mut latest_version_by_crate_id: HashMap<&str, &CrateVersion> = HashMap::new();
+    for version in versions.iter().filter(|version| version.yanked != "t") {
+        latest_version_by_crate_id
+            .entry(version.crate_id.as_str())
+            .and_modify(|latest| {
+                if latest.created_at < version.created_at {
+                    *latest = version;
+                }
+            })
+            .or_insert(version);
+    }
+
     let mut rustfmt: Vec<String> = vec![];
     let mut seen: HashSet<String> = HashSet::new();
     let mut count: u32 = 0;
@@ -80,7 +117,7 @@ fn collect_data_from_vcs(
             continue;
         }
 
-        let repository = match Repository::from_url(&krate.repository) {
+        let repository = match parse_repository_url(&krate.repository) {
             Ok(repo) => repo,
             Err(err) => {
                 log::error!("Error: {err} when parsing the repository url");
@@ -88,11 +125,9 @@ fn collect_data_from_vcs(
             }
         };
 
-        if seen.contains(&krate.repository.to_lowercase()) {
+        if !seen.insert(repository.url()) {
             continue;
         }
-        //log::info!("{:?}", seen);
-        seen.insert(krate.repository.to_lowercase());
 
         let mut details = load_vcs_details(&krate.repository);
 
@@ -105,11 +140,31 @@ fn collect_data_from_vcs(
         env::set_current_dir(&repo_path)?;
         log::info!("in folder: {:?}", env::current_dir()?.display());
 
-        collect_data_about_ci(&mut details)?;
+        collect_data_about_ci(&mut details);
+        details.tooling = detect_tooling(Path::new("."));
 
         collect_data_about_rustfmt(&mut details, &mut rustfmt, krate);
 
-        details.commit_count = git_get_count();
+        let crates_in_repository = crates_by_repository
+            .get(&repository.url())
+            .map_or(&[][..], Vec::as_slice);
+        collect_data_about_workspace(&mut details, crates_in_repository);
+        collect_data_about_community_files(
+            &mut details,
+            crates_in_repository,
+            &latest_version_by_crate_id,
+        );
+
+        // A shallow clone only has the last commit, we keep what we found in an earlier clone with history.
+        if details.clone_state.mode.has_history() {
+            collect_data_about_release_tags(
+                &mut details,
+                crates_in_repository,
+                &versions_by_crate_id,
+            );
+            collect_data_about_history(&mut details, crates_in_repository);
+            details.commit_count = git_get_count();
+        }
 
         env::set_current_dir(&current_dir)?;
         save_details(&krate.repository, &details)?;
@@ -133,26 +188,209 @@ fn collect_data_about_rustfmt(details: &mut VCSDetails, rustfmt: &mut Vec<String
     }
 }
 
-fn collect_data_about_ci(details: &mut VCSDetails) -> Result<(), Box<dyn Error>> {
-    let workflows = Path::new(".github/workflows");
-    if workflows.exists() {
-        for entry in workflows.read_dir()?.flatten() {
-            log::info!("workflows: {:?}", entry.path().display());
-            details.has_github_action = true;
+/// Find the packages in the repository and check if all the crates linking to it are really there.
+fn collect_data_about_workspace(details: &mut VCSDetails, crates_in_repository: &[&Crate]) {
+    details.cargo_toml_in_root = Path::new("Cargo.toml").exists();
+
+    let root = details
+        .cargo_toml_in_root
+        .then(|| read_toml(Path::new("Cargo.toml")))
+        .flatten();
+    let workspace = root
+        .as_ref()
+        .and_then(|table| table.get("workspace"))
+        .and_then(toml::Value::as_table);
+    details.is_workspace = workspace.is_some();
+    details.workspace_members = workspace
+        .and_then(|table| table.get("members"))
+        .and_then(toml::Value::as_array)
+        .map(|members| {
+            expand_workspace_members(
+                Path::new("."),
+                &members
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .collect::<Vec<&str>>(),
+            )
+        })
+        .unwrap_or_default();
+
+    details.packages = find_packages(Path::new("."));
+
+    details.missing_crates = crates_in_repository
+        .iter()
+        .filter(|krate| !details.packages.contains_key(&krate.name))
+        .map(|krate| krate.name.clone())
+        .collect();
+    details.missing_crates.sort();
+    details.missing_crates.dedup();
+    if !details.missing_crates.is_empty() {
+        log::warn!(
+            "Crates not found in the repository: {:?}",
+            details.missing_crates
+        );
+    }
+}
+
+/// Collect activity metrics from the git log of the whole repository and of the folder of each crate.
+fn collect_data_about_history(details: &mut VCSDetails, crates_in_repository: &[&Crate]) {
+    details.history = git_history(Path::new("."), Utc::now());
+    details.history.last_commit_by_crate = crates_in_repository
+        .iter()
+        .filter_map(|krate| {
+            let path = details.packages.get(&krate.name)?;
+            git_last_commit_date(Path::new("."), path).map(|date| (krate.name.clone(), date))
+        })
+        .collect();
+}
+
+/// Check which releases of the crates in the repository have a git tag and which tags have no release.
+fn collect_data_about_release_tags(
+    details: &mut VCSDetails,
+    crates_in_repository: &[&Crate],
+    versions_by_crate_id: &HashMap<&str, Vec<&str>>,
+) {
+    let releases = crates_in_repository
+        .iter()
+        .map(|krate| {
+            (
+                krate.name.as_str(),
+                versions_by_crate_id
+                    .get(krate.id.as_str())
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect::<BTreeMap<&str, Vec<&str>>>();
+
+    let report = reconcile_release_tags(&git_tags(Path::new(".")), &releases);
+    log::info!(
+        "tag convention: '{}' tags without release: {}",
+        report.convention,
+        report.tags_without_release.len()
+    );
+    details.tag_convention = report.convention;
+    details.release_tags = report.crates;
+    details.tags_without_release = report.tags_without_release;
+}
+
+/// Look for the community files and check if the changelog mentions the latest release of each crate.
+fn collect_data_about_community_files(
+    details: &mut VCSDetails,
+    crates_in_repository: &[&Crate],
+    latest_version_by_crate_id: &HashMap<&str, &CrateVersion>,
+) {
+    details.community_files = find_community_files(Path::new("."));
+    let Some(changelog) = details.community_files.get("changelog") else {
+        details.changelog_has_latest_release = BTreeMap::new();
+        return;
+    };
+
+    details.changelog_has_latest_release = crates_in_repository
+        .iter()
+        .filter_map(|krate| {
+            let latest = latest_version_by_crate_id.get(krate.id.as_str())?;
+            Some((
+                krate.name.clone(),
+                changelog_in_folder_mentions_version(Path::new("."), changelog, &latest.num),
+            ))
+        })
+        .collect();
+}
+
+/// The `members` of a workspace can be folder names or patterns like `crates/*`.
+/// We only handle `*` as the last part of the pattern, as that is what most of the workspaces use.
+fn expand_workspace_members(root: &Path, patterns: &[&str]) -> Vec<String> {
+    let mut members = vec![];
+    for raw_pattern in patterns {
+        let pattern = raw_pattern.trim_end_matches('/');
+        if let Some(parent) = pattern.strip_suffix("/*") {
+            let Ok(entries) = fs::read_dir(root.join(parent)) else {
+                log::warn!("Could not read workspace folder '{parent}'");
+                continue;
+            };
+            for entry in entries.flatten() {
+                if entry.path().join("Cargo.toml").exists() {
+                    members.push(format!("{parent}/{}", entry.file_name().to_string_lossy()));
+                }
+            }
+        } else if pattern.contains('*') {
+            log::warn!("Unsupported workspace member pattern '{pattern}'");
+        } else {
+            members.push(pattern.to_owned());
         }
     }
+    members.sort();
+    members
+}
 
-    details.has_gitlab_pipeline = Path::new(".gitlab-ci.yml").exists();
-    details.has_circle_ci = Path::new(".circleci").exists();
-    details.has_cirrus_ci = Path::new(".cirrus.yaml").exists();
-    details.has_travis_ci = Path::new(".travis.yaml").exists();
-    details.has_jenkins = Path::new("Jenkinsfile").exists();
-    details.has_appveyor =
-        Path::new(".appveyor.yml").exists() || Path::new("appveyor.yml").exists();
-    details.has_azure_pipeline = Path::new("azure-pipelines.yml").exists();
-    details.has_bitbucket_pipeline = Path::new("bitbucket-pipelines.yml").exists();
+/// Map the name of every package in the repository to the folder of its Cargo.toml file.
+fn find_packages(root: &Path) -> BTreeMap<String, String> {
+    let mut packages = BTreeMap::new();
 
-    Ok(())
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git" && entry.file_name() != "target")
+        .flatten()
+    {
+        if entry.file_name() != "Cargo.toml" || entry.file_type().is_dir() {
+            continue;
+        }
+        let Some(table) = read_toml(entry.path()) else {
+            continue;
+        };
+        let Some(name) = table
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+        else {
+            continue;
+        };
+
+        let folder = entry
+            .path()
+            .parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|parent| parent.display().to_string())
+            .filter(|parent| !parent.is_empty())
+            .unwrap_or_else(|| String::from("."));
+
+        // Test fixtures might reuse the name of the real crate, we keep the one closest to the root.
+        packages
+            .entry(name.to_owned())
+            .and_modify(|existing: &mut String| {
+                if folder.matches('/').count() < existing.matches('/').count() {
+                    existing.clone_from(&folder);
+                }
+            })
+            .or_insert(folder);
+    }
+
+    packages
+}
+
+fn read_toml(path: &Path) -> Option<Table> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            log::error!("Error: {err} when reading {}", path.display());
+            return None;
+        }
+    };
+    match content.parse::<Table>() {
+        Ok(table) => Some(table),
+        Err(err) => {
+            log::error!("Error: {err} when parsing {}", path.display());
+            None
+        }
+    }
+}
+
+fn collect_data_about_ci(details: &mut VCSDetails) {
+    details.ci_providers = detect_ci_providers(Path::new("."));
+    log::info!("CI providers: {:?}", details.ci_providers);
+    details.gitlab_ci_includes = gitlab_ci_includes(Path::new("."));
+    details.github_workflows = github_workflows(Path::new("."));
 }
 
 fn save_rustfm(rustfmt: &[String]) {
@@ -208,3 +446,62 @@ fn read_rustfmt(rustfmt: &mut Vec<String>, filename: &str, name: &str) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_cargo_toml(folder: &Path, content: &str) {
+        fs::create_dir_all(folder).unwrap();
+        fs::write(folder.join("Cargo.toml"), content).unwrap();
+    }
+
+    #[test]
+    fn check_expand_workspace_members() {
+        let tmp_dir = tempdir::TempDir::new("workspace").unwrap();
+        let root = tmp_dir.path();
+        write_cargo_toml(&root.join("crates/alpha"), "");
+        write_cargo_toml(&root.join("crates/beta"), "");
+        fs::create_dir_all(root.join("crates/docs")).unwrap();
+
+        assert_eq!(
+            expand_workspace_members(root, &["cli", "crates/*", "tools/*/x", "missing/*"]),
+            vec!["cli", "crates/alpha", "crates/beta"]
+        );
+        assert_eq!(
+            expand_workspace_members(root, &["crates/*/"]),
+            vec!["crates/alpha", "crates/beta"]
+        );
+    }
+
+    #[test]
+    fn check_find_packages() {
+        let tmp_dir = tempdir::TempDir::new("workspace").unwrap();
+        let root = tmp_dir.path();
+        write_cargo_toml(root, "[workspace]\nmembers = [\"crates/*\"]\n");
+        write_cargo_toml(
+            &root.join("crates/alpha"),
+            "[package]\nname = \"alpha\"\nversion = \"0.1.0\"\n",
+        );
+        write_cargo_toml(
+            &root.join("crates/alpha/tests/fixtures/alpha"),
+            "[package]\nname = \"alpha\"\nversion = \"0.0.0\"\n",
+        );
+        write_cargo_toml(
+            &root.join("target/package/beta"),
+            "[package]\nname = \"beta\"\nversion = \"0.1.0\"\n",
+        );
+        write_cargo_toml(&root.join("broken"), "[package");
+
+        assert_eq!(
+            find_packages(root),
+            BTreeMap::from([(String::from("alpha"), String::from("crates/alpha"))])
+        );
+
+        write_cargo_toml(root, "[package]\nname = \"gamma\"\nversion = \"0.1.0\"\n");
+        assert_eq!(
+            find_packages(root).get("gamma").map(String::as_str),
+            Some(".")
+        );
+    }
+}